@@ -1,6 +1,6 @@
-use anyhow::{bail, Context, Result};
 #[cfg(feature = "fec-rs")]
-use bytes::Bytes;
+use anyhow::Context;
+use anyhow::{bail, Result};
 #[cfg(feature = "fec-rs")]
 use std::fs;
 #[cfg(feature = "fec-rs")]
@@ -10,17 +10,20 @@ use tracing::info;
 
 #[cfg(feature = "fec-rs")]
 use durapack_core::{
-    fec::{RedundancyEncoder, RsEncoder},
+    fec::{RedundancyEncoder, RsEncoder, RS_SHARD_LAYOUT},
     linker::link_frames,
+    parity::{parity_descriptor, FecCode, ParityDescriptor, RS_PARAM_WHOLE_FRAME},
     scanner::scan_stream,
     types::Frame,
 };
 
 /// Post-facto parity injection:
 /// - Reads an existing .durp file (or stdin)
-/// - Scans frames, groups them into blocks of N, computes K parity frames per block (RS)
-/// - Appends the parity frames to the original output (or a new file if --dry-run is used)
-/// - Writes/updates a sidecar index JSON
+/// - Scans frames, groups the data frames into blocks of N, computes K parity frames
+///   per block (RS); parity frames already in the file are not protected again
+/// - Appends the parity frames, each carrying its block descriptor, to the original
+///   output (or a new file if --dry-run is used)
+/// - Optionally writes a sidecar index JSON
 pub fn inject_parity(
     input: &str,
     output: Option<&str>,
@@ -28,10 +31,19 @@ pub fn inject_parity(
     k_parity: usize,
     fec_index_out: Option<&str>,
     dry_run: bool,
+    whole_frames: bool,
 ) -> Result<()> {
     #[cfg(not(feature = "fec-rs"))]
     {
-        let _ = (input, output, n_data, k_parity, fec_index_out, dry_run);
+        let _ = (
+            input,
+            output,
+            n_data,
+            k_parity,
+            fec_index_out,
+            dry_run,
+            whole_frames,
+        );
         bail!("This build does not include RS FEC support. Rebuild with --features fec-rs");
     }
 
@@ -68,13 +80,20 @@ pub fn inject_parity(
             data: usize,
             parity: usize,
             parity_frame_ids: Vec<u64>,
+            #[serde(skip_serializing_if = "std::ops::Not::not")]
+            whole_frames: bool,
+            shard_layout: u8,
         }
         let mut fec_index: Vec<FecIndexEntry> = Vec::new();
 
         // Prepare output buffer seeded with original data (append parity afterwards)
         let mut out_bytes: Vec<u8> = if dry_run { Vec::new() } else { data.clone() };
 
-        let enc = RsEncoder::new(n_data, k_parity);
+        let enc = if whole_frames {
+            RsEncoder::new(n_data, k_parity).whole_frames()
+        } else {
+            RsEncoder::new(n_data, k_parity)
+        };
         let mut block: Vec<Frame> = Vec::with_capacity(n_data);
         let mut prev_hash = timeline
             .frames
@@ -87,7 +106,12 @@ pub fn inject_parity(
             .map(|f| f.header.frame_id + 1)
             .unwrap_or(1);
 
-        for f in &timeline.frames {
+        // Parity already in the file (from pack or an earlier run) is not data
+        for f in timeline
+            .frames
+            .iter()
+            .filter(|f| parity_descriptor(f).is_none())
+        {
             block.push(f.clone());
             if block.len() == n_data {
                 let blocks = enc.encode_batch(&block, 0).with_context(|| {
@@ -98,11 +122,23 @@ pub fn inject_parity(
                 })?;
                 let parity_blocks = blocks.into_iter().skip(n_data);
                 let mut parity_ids = Vec::new();
-                for pb in parity_blocks {
-                    // Build parity frame using the shard as payload
+                for (j, pb) in parity_blocks.enumerate() {
+                    // Parity frame: block descriptor followed by the shard
+                    let desc = ParityDescriptor {
+                        code: FecCode::ReedSolomon,
+                        param: if whole_frames {
+                            RS_PARAM_WHOLE_FRAME
+                        } else {
+                            0
+                        },
+                        block_start_id: block[0].header.frame_id,
+                        data: n_data as u32,
+                        parity: k_parity as u32,
+                        index: j as u32,
+                    };
                     let mut b = durapack_core::encoder::FrameBuilder::new(next_id)
                         .prev_hash(prev_hash)
-                        .payload(Bytes::from(pb.data));
+                        .as_parity(&desc, &pb.data);
                     // Use same trailer scheme as original frames best-effort: default CRC32C
                     b = b.with_crc32c();
                     let frame_struct = b.build_struct()?;
@@ -119,6 +155,8 @@ pub fn inject_parity(
                     data: n_data,
                     parity: k_parity,
                     parity_frame_ids: parity_ids,
+                    whole_frames,
+                    shard_layout: RS_SHARD_LAYOUT,
                 });
                 block.clear();
             }
@@ -200,6 +238,7 @@ mod tests {
             1,
             Some(sidecar_path.to_str().unwrap()),
             true,
+            false,
         )
         .unwrap();
 
@@ -240,6 +279,7 @@ mod tests {
             1,
             Some(sidecar_path.to_str().unwrap()),
             false,
+            false,
         )
         .unwrap();
 
@@ -271,7 +311,7 @@ mod tests {
         fs::write(&input_path, &data).unwrap();
         let orig_len = fs::metadata(&input_path).unwrap().len();
 
-        inject_parity(input_path.to_str().unwrap(), None, 2, 1, None, false).unwrap();
+        inject_parity(input_path.to_str().unwrap(), None, 2, 1, None, false, false).unwrap();
 
         let new_len = fs::metadata(&input_path).unwrap().len();
         assert!(
@@ -298,6 +338,7 @@ mod tests {
             1,
             Some(sidecar_path.to_str().unwrap()),
             true,
+            false,
         )
         .unwrap();
 
@@ -308,13 +349,45 @@ mod tests {
         assert_eq!(arr[0]["block_start_id"], serde_json::json!(1));
     }
 
+    #[cfg(feature = "fec-rs")]
+    #[test]
+    fn test_inject_parity_twice_does_not_protect_parity() {
+        let dir = tempdir().unwrap();
+        let input_path = dir.path().join("twice.durp");
+        let sidecar_path = dir.path().join("twice.durp.fec.json");
+        fs::write(&input_path, make_durp_with_frames(4)).unwrap();
+
+        // First run appends parity frames 5 and 6
+        inject_parity(input_path.to_str().unwrap(), None, 2, 1, None, false, false).unwrap();
+        inject_parity(
+            input_path.to_str().unwrap(),
+            None,
+            2,
+            1,
+            Some(sidecar_path.to_str().unwrap()),
+            false,
+            false,
+        )
+        .unwrap();
+
+        // The second run covers data frames 1..=4 again, never 5 or 6
+        let side = fs::read_to_string(&sidecar_path).unwrap();
+        let v: serde_json::Value = serde_json::from_str(&side).unwrap();
+        let arr = v.as_array().unwrap();
+        assert_eq!(arr.len(), 2);
+        assert_eq!(arr[0]["block_start_id"], serde_json::json!(1));
+        assert_eq!(arr[1]["block_start_id"], serde_json::json!(3));
+        assert_eq!(arr[0]["parity_frame_ids"], serde_json::json!([7]));
+        assert_eq!(arr[1]["parity_frame_ids"], serde_json::json!([8]));
+    }
+
     #[cfg(feature = "fec-rs")]
     #[test]
     fn test_inject_parity_no_frames_error() {
         let dir = tempdir().unwrap();
         let input_path = dir.path().join("empty.durp");
         fs::write(&input_path, &[] as &[u8]).unwrap();
-        let err = inject_parity(input_path.to_str().unwrap(), None, 2, 1, None, true)
+        let err = inject_parity(input_path.to_str().unwrap(), None, 2, 1, None, true, false)
             .expect_err("expected error for empty input");
         let msg = format!("{}", err);
         assert!(msg.contains("No frames found to protect"));
@@ -326,7 +399,7 @@ mod tests {
         let dir = tempdir().unwrap();
         let input_path = dir.path().join("noop.durp");
         fs::write(&input_path, &[] as &[u8]).unwrap();
-        let err = inject_parity(input_path.to_str().unwrap(), None, 2, 1, None, true)
+        let err = inject_parity(input_path.to_str().unwrap(), None, 2, 1, None, true, false)
             .expect_err("expected feature-gated error");
         let msg = format!("{}", err);
         assert!(msg.contains("Rebuild with --features fec-rs"));
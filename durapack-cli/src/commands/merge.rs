@@ -0,0 +1,174 @@
+use anyhow::{bail, Context, Result};
+use durapack_core::merge::{merge_streams, MergeOrigin, Provenance};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Read, Write};
+use tracing::info;
+
+#[derive(Serialize, Deserialize)]
+struct CopyJson {
+    file: String,
+    offset: usize,
+    confidence: f32,
+    intact: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MergedFrameJson {
+    frame_id: u64,
+    /// "copy" or "voted"
+    origin: String,
+    /// Input the chosen copy came from (copies only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    /// Offset of the chosen copy in `file` (copies only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    offset: Option<usize>,
+    /// Bytes settled by trying tied values (voted frames only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tied_bytes: Option<usize>,
+    dissenting: usize,
+    copies: Vec<CopyJson>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct UnrecoveredJson {
+    frame_id: u64,
+    copies: Vec<CopyJson>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct GapJson {
+    before: u64,
+    after: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MergeReportJson {
+    inputs: Vec<String>,
+    frames: Vec<MergedFrameJson>,
+    unrecovered: Vec<UnrecoveredJson>,
+    gaps: Vec<GapJson>,
+}
+
+/// Merge several captures of the same stream into one, keeping per-frame provenance
+pub fn execute(inputs: &[String], output: &str, report_out: Option<&str>) -> Result<()> {
+    if inputs.len() < 2 {
+        bail!("merge needs at least two inputs");
+    }
+    if inputs.iter().filter(|i| *i == "-").count() > 1 {
+        bail!("stdin (\"-\") can only be used for one input");
+    }
+    if output == "-" && report_out == Some("-") {
+        bail!("--output and --report cannot both write to stdout (\"-\")");
+    }
+
+    let mut data = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        info!("Reading: {}", input);
+        let bytes = if input == "-" {
+            let mut buf = Vec::new();
+            io::stdin().read_to_end(&mut buf)?;
+            buf
+        } else {
+            fs::read(input).with_context(|| format!("Failed to read input file: {}", input))?
+        };
+        data.push(bytes);
+    }
+
+    let report = merge_streams(&data);
+
+    // Write the merged stream in timeline order, orphans last
+    let mut merged = Vec::new();
+    for &i in report.index.frames.iter().chain(&report.index.orphans) {
+        merged.extend_from_slice(&report.frames[i].bytes);
+    }
+    if output == "-" {
+        io::stdout().write_all(&merged)?;
+    } else {
+        fs::write(output, &merged)
+            .with_context(|| format!("Failed to write output file: {}", output))?;
+        info!("Merged stream written to: {}", output);
+    }
+
+    let voted = report.voted();
+    let dissenting = report.frames.iter().filter(|f| f.dissenting > 0).count();
+
+    // Keep stdout clean when the merged stream or the report goes there
+    if output != "-" && report_out != Some("-") {
+        println!("\n=== Merge Results ===");
+        for (input, stats) in inputs.iter().zip(&report.source_stats) {
+            println!(
+                "{}: {} frames, {} damaged",
+                input, stats.frames_found, stats.damaged_frames
+            );
+        }
+        println!("Merged frames:     {}", report.frames.len());
+        println!("  copied intact:   {}", report.frames.len() - voted);
+        println!("  rebuilt by vote: {}", voted);
+        println!("Unrecovered:       {}", report.unrecovered.len());
+        println!("Gaps:              {}", report.index.gaps.len());
+        if dissenting > 0 {
+            println!("Disagreeing IDs:   {}", dissenting);
+        }
+    }
+
+    if let Some(path) = report_out {
+        let copy_json = |p: &Provenance| CopyJson {
+            file: inputs[p.source].clone(),
+            offset: p.offset,
+            confidence: p.confidence,
+            intact: p.intact,
+        };
+        let json = MergeReportJson {
+            inputs: inputs.to_vec(),
+            frames: report
+                .frames
+                .iter()
+                .map(|f| {
+                    let (origin, chosen, tied_bytes) = match f.origin {
+                        MergeOrigin::Copy { chosen } => ("copy", Some(&f.copies[chosen]), None),
+                        MergeOrigin::Voted { tied_bytes } => ("voted", None, Some(tied_bytes)),
+                    };
+                    MergedFrameJson {
+                        frame_id: f.frame.header.frame_id,
+                        origin: origin.to_string(),
+                        file: chosen.map(|p| inputs[p.source].clone()),
+                        offset: chosen.map(|p| p.offset),
+                        tied_bytes,
+                        dissenting: f.dissenting,
+                        copies: f.copies.iter().map(copy_json).collect(),
+                    }
+                })
+                .collect(),
+            unrecovered: report
+                .unrecovered
+                .iter()
+                .map(|u| UnrecoveredJson {
+                    frame_id: u.frame_id,
+                    copies: u.copies.iter().map(copy_json).collect(),
+                })
+                .collect(),
+            gaps: report
+                .index
+                .gaps
+                .iter()
+                .map(|g| GapJson {
+                    before: g.before,
+                    after: g.after,
+                })
+                .collect(),
+        };
+        let text = serde_json::to_string_pretty(&json)
+            .with_context(|| "Failed to serialize merge report")?;
+        if path == "-" {
+            println!("{}", text);
+        } else {
+            fs::write(path, text)
+                .with_context(|| format!("Failed to write merge report: {}", path))?;
+            info!("Merge report written to: {}", path);
+        }
+    }
+
+    Ok(())
+}
@@ -1,5 +1,80 @@
 pub mod fec;
+pub mod merge;
 pub mod pack;
 pub mod scan;
 pub mod timeline;
 pub mod verify;
+
+use anyhow::{Context, Result};
+use durapack_core::capture_index::{CaptureIndex, Refresh};
+use durapack_core::parity::{FecCode, ParityDescriptor};
+use durapack_core::scanner::{scan_stream, LocatedFrame};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tracing::info;
+
+/// Sidecar path of the persisted timeline index for `input`
+pub(crate) fn index_path(input: &str) -> String {
+    format!("{}.idx", input)
+}
+
+/// Locate frames in `data`, optionally through the `<input>.idx` sidecar
+///
+/// With `use_index`, a stored index is reused or extended when the capture is
+/// unchanged or has only grown, and rewritten whenever it had to change.
+pub(crate) fn locate_frames(
+    input: &str,
+    data: &[u8],
+    use_index: bool,
+) -> Result<Vec<LocatedFrame>> {
+    if !use_index {
+        return Ok(scan_stream(data));
+    }
+    let path = index_path(input);
+    let stored = fs::read(&path).ok();
+    let (index, frames, refresh) = CaptureIndex::refresh(data, stored.as_deref());
+    match refresh {
+        Refresh::Reused => info!("Reused index: {}", path),
+        Refresh::Extended { tail_frames } => {
+            info!("Index extended with {} appended frames", tail_frames)
+        }
+        Refresh::Rebuilt => info!("Index rebuilt from a full scan"),
+    }
+    if refresh != Refresh::Reused {
+        fs::write(&path, index.to_bytes())
+            .with_context(|| format!("Failed to write index: {}", path))?;
+    }
+    Ok(frames)
+}
+
+/// Descriptor of an in-band parity frame, as reported by scan and timeline
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ParityJson {
+    /// rs, raptorq or ldpc
+    pub code: String,
+    pub block_start_id: u64,
+    pub data: u32,
+    pub parity: u32,
+    pub index: u32,
+}
+
+impl From<ParityDescriptor> for ParityJson {
+    fn from(d: ParityDescriptor) -> Self {
+        Self {
+            code: d.code.name().to_string(),
+            block_start_id: d.block_start_id,
+            data: d.data,
+            parity: d.parity,
+            index: d.index,
+        }
+    }
+}
+
+/// Display name of a FEC code for human-readable output
+pub(crate) fn code_label(code: FecCode) -> &'static str {
+    match code {
+        FecCode::ReedSolomon => "RS",
+        FecCode::RaptorQ => "RaptorQ",
+        FecCode::Ldpc => "LDPC",
+    }
+}
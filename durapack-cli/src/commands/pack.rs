@@ -1,10 +1,16 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use bytes::Bytes;
 #[cfg(feature = "ed25519-signatures")]
 use durapack_core::encoder::encode_frame_struct_signed;
 use durapack_core::encoder::FrameBuilder;
+#[cfg(feature = "fec-raptorq")]
+use durapack_core::fec::RaptorQEncoder;
 #[cfg(feature = "fec-rs")]
-use durapack_core::fec::{FecBlock, RedundancyEncoder, RsEncoder};
+use durapack_core::fec::RsEncoder;
+#[cfg(any(feature = "fec-rs", feature = "fec-raptorq"))]
+use durapack_core::fec::{FecBlock, RedundancyEncoder, RS_SHARD_LAYOUT};
+#[cfg(any(feature = "fec-rs", feature = "fec-raptorq"))]
+use durapack_core::parity::{FecCode, ParityDescriptor, RS_PARAM_WHOLE_FRAME};
 #[cfg(feature = "ed25519-signatures")]
 use ed25519_dalek::SigningKey;
 use serde_json::Value;
@@ -14,7 +20,7 @@ use std::thread::sleep;
 use std::time::{Duration, Instant};
 use tracing::{debug, info};
 
-use crate::ChunkStrategy;
+use crate::{ChunkStrategy, FecScheme};
 
 #[allow(dead_code)]
 pub fn execute(input: &str, output: &str, use_blake3: bool, start_id: u64) -> Result<()> {
@@ -30,10 +36,17 @@ pub fn execute(input: &str, output: &str, use_blake3: bool, start_id: u64) -> Re
         None,
         None,
         None,
+        false,
+        FecScheme::Rs,
+        false,
     )
 }
 
 #[allow(clippy::too_many_arguments)]
+#[cfg_attr(
+    not(any(feature = "fec-rs", feature = "fec-raptorq")),
+    allow(clippy::explicit_counter_loop)
+)]
 pub fn execute_ext(
     input: &str,
     output: &str,
@@ -46,9 +59,27 @@ pub fn execute_ext(
     fec_rs: Option<(usize, usize)>,
     fec_index_out: Option<&str>,
     sign_key_path: Option<&str>,
+    header_crc: bool,
+    fec_scheme: FecScheme,
+    fec_whole_frames: bool,
 ) -> Result<()> {
     info!("Packing data from {} to {}", input, output);
 
+    // RS without its feature packs no parity frames (a requested sidecar lists
+    // no blocks); RaptorQ without its feature is an error
+    let fec_enabled = match fec_scheme {
+        FecScheme::Rs => cfg!(feature = "fec-rs"),
+        FecScheme::Raptorq => cfg!(feature = "fec-raptorq"),
+    };
+    if fec_rs.is_some() && !fec_enabled && matches!(fec_scheme, FecScheme::Raptorq) {
+        bail!(
+            "This build does not include RaptorQ FEC support. Rebuild with --features fec-raptorq"
+        );
+    }
+    if fec_whole_frames && !matches!(fec_scheme, FecScheme::Rs) {
+        bail!("Whole-frame FEC is only available with --fec rs");
+    }
+
     #[cfg(feature = "ed25519-signatures")]
     let signing_key: Option<SigningKey> = if let Some(path) = sign_key_path {
         let bytes = fs::read(path).with_context(|| format!("Failed to read key: {}", path))?;
@@ -96,11 +127,21 @@ pub fn execute_ext(
     // FEC sidecar structure
     #[derive(serde::Serialize)]
     struct FecIndexEntry {
+        scheme: &'static str,
         block_start_id: u64,
         data: usize,
         parity: usize,
         parity_frame_ids: Vec<u64>,
+        #[serde(skip_serializing_if = "std::ops::Not::not")]
+        whole_frames: bool,
+        /// RS data shard layout (`fec::RS_SHARD_LAYOUT`); absent for RaptorQ
+        #[serde(skip_serializing_if = "Option::is_none")]
+        shard_layout: Option<u8>,
     }
+    #[cfg_attr(
+        not(any(feature = "fec-rs", feature = "fec-raptorq")),
+        allow(unused_mut)
+    )]
     let mut fec_index: Vec<FecIndexEntry> = Vec::new();
 
     // Progress bar
@@ -125,8 +166,13 @@ pub fn execute_ext(
     let mut block_frames: Vec<durapack_core::types::Frame> = Vec::new();
     let mut next_frame_id = start_id;
 
-    for payload in payloads.iter() {
+    for (i, payload) in payloads.iter().enumerate() {
         let frame_id = next_frame_id;
+        // The stream's last frame carries IS_LAST; parity frames completing
+        // a block come after the final data frame
+        let is_final = i + 1 == payloads.len();
+        let parity_follows =
+            fec_enabled && fec_rs.is_some_and(|(n, _)| block_frames.len() + 1 == n);
 
         // Serialize payload to JSON bytes
         let payload_bytes = serde_json::to_vec(payload)
@@ -139,6 +185,12 @@ pub fn execute_ext(
         if frame_id == start_id {
             builder = builder.mark_first();
         }
+        if is_final && !parity_follows {
+            builder = builder.mark_last();
+        }
+        if header_crc {
+            builder = builder.with_header_crc();
+        }
 
         let use_sig = sign_key_path.is_some();
         if use_sig {
@@ -175,26 +227,58 @@ pub fn execute_ext(
 
         output_data.extend_from_slice(&encoded);
         bytes_written_total += encoded.len() as u64;
+        // Parity frames of a completed block take the IDs after this frame
+        next_frame_id += 1;
 
         // FEC block accumulation
         if let Some((n, k)) = fec_rs {
-            block_frames.push(frame_struct.clone());
+            // Keep the trailer as written so whole-frame shards carry the signature
+            let mut written = frame_struct.clone();
+            let trailer_size = written.header.flags.trailer_type().size();
+            if trailer_size > 0 {
+                written.trailer = Some(encoded.slice(encoded.len() - trailer_size..));
+            }
+            block_frames.push(written);
             if block_frames.len() == n {
                 // Emit parity frames for this block
-                #[cfg(feature = "fec-rs")]
-                {
-                    let enc = RsEncoder::new(n, k);
-                    let blocks = enc.encode_batch(&block_frames, 0).context(format!(
-                        "RS encode failed for block starting at {}",
-                        frame_id + 1 - n as u64
-                    ))?;
-                    let parity_blocks: Vec<FecBlock> = blocks.into_iter().skip(n).collect();
+                #[cfg(any(feature = "fec-rs", feature = "fec-raptorq"))]
+                if fec_enabled {
+                    let parity_blocks =
+                        encode_parity(fec_scheme, fec_whole_frames, &block_frames, n, k).context(
+                            format!(
+                                "FEC encode failed for block starting at {}",
+                                frame_id + 1 - n as u64
+                            ),
+                        )?;
+                    let code = match fec_scheme {
+                        FecScheme::Rs => FecCode::ReedSolomon,
+                        FecScheme::Raptorq => FecCode::RaptorQ,
+                    };
                     let mut parity_ids = Vec::new();
-                    for pb in parity_blocks {
-                        // Wrap parity shard into a frame with IS_SUPERFRAME flag off; mark trailer
+                    let parity_count = parity_blocks.len();
+                    for (j, pb) in parity_blocks.into_iter().enumerate() {
+                        // Parity frames carry their block descriptor ahead of the shard
+                        let desc = ParityDescriptor {
+                            code,
+                            param: if fec_whole_frames {
+                                RS_PARAM_WHOLE_FRAME
+                            } else {
+                                0
+                            },
+                            block_start_id: frame_id + 1 - n as u64,
+                            data: n as u32,
+                            parity: parity_count as u32,
+                            index: j as u32,
+                        };
                         let mut b = FrameBuilder::new(next_frame_id)
-                            .payload(Bytes::from(pb.data))
+                            .as_parity(&desc, &pb.data)
                             .prev_hash(prev_hash);
+                        if is_final && j + 1 == parity_count {
+                            b = b.mark_last();
+                        }
+                        if header_crc {
+                            b = b.with_header_crc();
+                        }
                         if use_sig {
                             b = b.with_blake3_signature();
                         } else if use_blake3 {
@@ -224,15 +308,18 @@ pub fn execute_ext(
                         next_frame_id += 1;
                     }
                     fec_index.push(FecIndexEntry {
+                        scheme: code.name(),
                         block_start_id: frame_id + 1 - n as u64,
                         data: n,
                         parity: k,
                         parity_frame_ids: parity_ids,
+                        whole_frames: fec_whole_frames,
+                        shard_layout: (code == FecCode::ReedSolomon).then_some(RS_SHARD_LAYOUT),
                     });
                 }
-                #[cfg(not(feature = "fec-rs"))]
+                #[cfg(not(any(feature = "fec-rs", feature = "fec-raptorq")))]
                 {
-                    let _ = k; // silence unused when RS is not enabled
+                    let _ = k; // silence unused when no FEC backend is enabled
                 }
                 block_frames.clear();
             }
@@ -254,7 +341,6 @@ pub fn execute_ext(
         }
 
         info!("Packed frame {} ({} bytes)", frame_id, encoded.len());
-        next_frame_id += 1;
     }
 
     // If there are leftover frames in a partial block, you can choose to emit parity or skip.
@@ -273,20 +359,16 @@ pub fn execute_ext(
             .with_context(|| format!("Failed to write output file: {}", output))?;
     }
 
-    // Write FEC index sidecar if requested or implied
-    if let Some((n, k)) = fec_rs {
-        let sidecar_path = fec_index_out
-            .map(|s| s.to_string())
-            .unwrap_or_else(|| format!("{}.fec.json", output));
+    // Parity frames describe themselves; the sidecar is only written on request
+    if let (Some(_), Some(sidecar_path)) = (fec_rs, fec_index_out) {
         let json = serde_json::to_string_pretty(&fec_index)?;
-        fs::write(&sidecar_path, json)
+        fs::write(sidecar_path, json)
             .with_context(|| format!("Failed to write FEC index: {}", sidecar_path))?;
         info!(
             "Wrote FEC index sidecar: {} (blocks: {})",
             sidecar_path,
             fec_index.len()
         );
-        let _ = (n, k); // silence unused if compiled without feature
     }
 
     info!(
@@ -297,3 +379,27 @@ pub fn execute_ext(
 
     Ok(())
 }
+
+/// Parity blocks (everything after the N data shards) for one block of frames
+#[cfg(any(feature = "fec-rs", feature = "fec-raptorq"))]
+fn encode_parity(
+    scheme: FecScheme,
+    whole_frames: bool,
+    frames: &[durapack_core::types::Frame],
+    n: usize,
+    k: usize,
+) -> Result<Vec<FecBlock>> {
+    let blocks = match scheme {
+        #[cfg(feature = "fec-rs")]
+        FecScheme::Rs if whole_frames => RsEncoder::new(n, k)
+            .whole_frames()
+            .encode_batch(frames, 0)?,
+        #[cfg(feature = "fec-rs")]
+        FecScheme::Rs => RsEncoder::new(n, k).encode_batch(frames, 0)?,
+        #[cfg(feature = "fec-raptorq")]
+        FecScheme::Raptorq => RaptorQEncoder::new(k).encode_batch(frames, 0)?,
+        #[allow(unreachable_patterns)]
+        _ => bail!("FEC scheme {:?} is not enabled in this build", scheme),
+    };
+    Ok(blocks.into_iter().skip(n).collect())
+}
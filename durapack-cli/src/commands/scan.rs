@@ -1,6 +1,8 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use bytes::Bytes;
-use durapack_core::linker::link_frames;
+use durapack_core::damage_map::{Content, DamageMap, RegionKind};
+use durapack_core::linker::link_indexed;
+use durapack_core::parity::{in_band_blocks, parity_descriptor};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -8,6 +10,8 @@ use std::fs::File;
 use std::io::{self, Read, Write};
 use tracing::info;
 
+use super::ParityJson;
+
 #[derive(Serialize, Deserialize)]
 struct RecoveredFrame {
     offset: usize,
@@ -17,6 +21,19 @@ struct RecoveredFrame {
     payload: String,
     /// Optional confidence score [0.0, 1.0]
     confidence: f32,
+    /// Bit offset within the byte at `offset` (bit-aligned scans only)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    bit_shift: u8,
+    /// Frame was recovered from inverted polarity (bit-aligned scans only)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    inverted: bool,
+    /// Block descriptor when this is an in-band parity frame
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parity: Option<ParityJson>,
+}
+
+fn is_zero(v: &u8) -> bool {
+    *v == 0
 }
 
 #[derive(Serialize, Deserialize)]
@@ -35,13 +52,164 @@ enum ScanRecord {
         markers_found: usize,
         frames_found: usize,
         decode_failures: usize,
+        header_checksum_failures: usize,
+        damaged_frames: usize,
+        overlaps_rejected: usize,
         bytes_recovered: usize,
         recovery_rate: f64,
+        /// Frames carrying an in-band parity descriptor
+        #[serde(default)]
+        parity_frames: usize,
     },
     Frame(RecoveredFrame),
     Gap(GapRange),
 }
 
+#[derive(Serialize, Deserialize)]
+struct RegionJson {
+    start: usize,
+    end: usize,
+    /// frame, damaged, preamble, sync_word or unexplained
+    kind: String,
+    /// Frame ID for frame and damaged regions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    frame_id: Option<u64>,
+    /// zero_fill, erased, noise, text or binary for unexplained regions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DamageMapJson {
+    bytes: usize,
+    frame_bytes: usize,
+    damaged_bytes: usize,
+    padding_bytes: usize,
+    unexplained_bytes: usize,
+    regions: Vec<RegionJson>,
+}
+
+fn content_name(c: Content) -> &'static str {
+    match c {
+        Content::ZeroFill => "zero_fill",
+        Content::Erased => "erased",
+        Content::Noise => "noise",
+        Content::Text => "text",
+        Content::Binary => "binary",
+    }
+}
+
+fn write_damage_map(map: &DamageMap, path: &str) -> Result<()> {
+    let summary = map.summary();
+    let json = DamageMapJson {
+        bytes: map.len,
+        frame_bytes: summary.frames,
+        damaged_bytes: summary.damaged,
+        padding_bytes: summary.padding,
+        unexplained_bytes: summary.unexplained,
+        regions: map
+            .regions
+            .iter()
+            .map(|r| {
+                let (kind, frame_id, content) = match r.kind {
+                    RegionKind::Frame { frame_id } => ("frame", Some(frame_id), None),
+                    RegionKind::Damaged { frame_id } => ("damaged", Some(frame_id), None),
+                    RegionKind::Preamble => ("preamble", None, None),
+                    RegionKind::SyncWord => ("sync_word", None, None),
+                    RegionKind::Unexplained(c) => ("unexplained", None, Some(content_name(c))),
+                };
+                RegionJson {
+                    start: r.start,
+                    end: r.end,
+                    kind: kind.to_string(),
+                    frame_id,
+                    content: content.map(str::to_string),
+                }
+            })
+            .collect(),
+    };
+    let text =
+        serde_json::to_string_pretty(&json).with_context(|| "Failed to serialize damage map")?;
+    if path == "-" {
+        println!("{}", text);
+    } else {
+        fs::write(path, text).with_context(|| format!("Failed to write damage map: {}", path))?;
+        info!("Damage map written to: {}", path);
+    }
+    Ok(())
+}
+
+/// Width of the rendered coverage bar in cells
+const MAP_WIDTH: usize = 64;
+
+/// Print a one-line coverage bar and the ranges that are not valid frames
+///
+/// Each cell shows the worst region it touches, so small losses stay visible.
+fn render_damage_map(human: &mut dyn Write, map: &DamageMap) -> io::Result<()> {
+    fn glyph(kind: RegionKind) -> (u8, char) {
+        match kind {
+            RegionKind::Frame { .. } => (0, '#'),
+            RegionKind::Preamble | RegionKind::SyncWord => (1, '-'),
+            RegionKind::Unexplained(Content::ZeroFill) => (2, '0'),
+            RegionKind::Unexplained(Content::Erased) => (2, 'F'),
+            RegionKind::Unexplained(Content::Text) => (2, 't'),
+            RegionKind::Unexplained(Content::Binary) => (2, '?'),
+            RegionKind::Unexplained(Content::Noise) => (2, '~'),
+            RegionKind::Damaged { .. } => (3, 'x'),
+        }
+    }
+
+    writeln!(human, "=== Damage Map ===")?;
+    if map.len == 0 {
+        writeln!(human, "(empty input)")?;
+        writeln!(human)?;
+        return Ok(());
+    }
+    let cells = MAP_WIDTH.min(map.len);
+    let mut bar = vec![(0u8, ' '); cells];
+    for r in &map.regions {
+        let first = r.start * cells / map.len;
+        let last = (r.end - 1) * cells / map.len;
+        let g = glyph(r.kind);
+        for cell in &mut bar[first..=last] {
+            if cell.1 == ' ' || g.0 > cell.0 {
+                *cell = g;
+            }
+        }
+    }
+    writeln!(human, "[{}]", bar.iter().map(|c| c.1).collect::<String>())?;
+    writeln!(
+        human,
+        "# frame  x damaged  - padding  0 zero fill  F erased  ~ noise  t text  ? binary"
+    )?;
+
+    let summary = map.summary();
+    writeln!(
+        human,
+        "Frames {} B, damaged {} B, padding {} B, unexplained {} B",
+        summary.frames, summary.damaged, summary.padding, summary.unexplained
+    )?;
+    for r in &map.regions {
+        let what = match r.kind {
+            RegionKind::Frame { .. } => continue,
+            RegionKind::Damaged { frame_id } => format!("damaged frame {}", frame_id),
+            RegionKind::Preamble => "preamble".to_string(),
+            RegionKind::SyncWord => "sync word".to_string(),
+            RegionKind::Unexplained(c) => content_name(c).replace('_', " "),
+        };
+        writeln!(
+            human,
+            "  {:>10}..{:<10} {:>8} B  {}",
+            r.start,
+            r.end,
+            r.len(),
+            what
+        )?;
+    }
+    writeln!(human)?;
+    Ok(())
+}
+
 fn write_jsonl(mut out: impl Write, record: &ScanRecord) -> Result<()> {
     let line = serde_json::to_string(record)?;
     writeln!(out, "{}", line)?;
@@ -50,9 +218,12 @@ fn write_jsonl(mut out: impl Write, record: &ScanRecord) -> Result<()> {
 
 #[allow(dead_code)]
 pub fn execute(input: &str, output: Option<&str>, stats_only: bool) -> Result<()> {
-    execute_ext(input, output, stats_only, false, None, None)
+    execute_ext(
+        input, output, stats_only, false, None, None, false, false, None,
+    )
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn execute_ext(
     input: &str,
     output: Option<&str>,
@@ -60,8 +231,20 @@ pub fn execute_ext(
     jsonl: bool,
     carve_payloads: Option<&str>,
     min_confidence: Option<f32>,
+    bit_align: bool,
+    allow_inverted: bool,
+    damage_map_out: Option<&str>,
 ) -> Result<()> {
     info!("Scanning: {}", input);
+    if damage_map_out == Some("-") && (output == Some("-") || jsonl && output.is_none()) {
+        bail!("--damage-map - cannot share stdout with the frame output");
+    }
+    // Keep stdout clean when the damage map goes there
+    let mut human: Box<dyn Write> = if damage_map_out == Some("-") {
+        Box::new(io::sink())
+    } else {
+        Box::new(io::stdout())
+    };
 
     // Read input (file or stdin)
     let data = if input == "-" {
@@ -75,16 +258,29 @@ pub fn execute_ext(
     info!("Input size: {} bytes", data.len());
 
     // Scan with statistics
-    let (located_frames, stats) = if jsonl {
+    let (located_frames, damaged, stats) = if bit_align {
+        let options = durapack_core::scanner::BitScanOptions { allow_inverted };
+        let (frames, st) = durapack_core::scanner::scan_stream_bit_aligned(&data, options);
+        (frames, Vec::new(), st)
+    } else if jsonl {
         // zero-copy scan still needs stats: compute using slice path for stats, emit frames from zero-copy path
         let zc = durapack_core::scanner::scan_stream_zero_copy(Bytes::from(data.clone()));
-        let (_, st) = durapack_core::scanner::scan_stream_with_stats(&data);
-        (zc, st)
+        let (_, dmg, st) = durapack_core::scanner::scan_stream_with_damaged(&data);
+        (zc, dmg, st)
     } else {
-        durapack_core::scanner::scan_stream_with_stats(&data)
+        durapack_core::scanner::scan_stream_with_damaged(&data)
     };
 
+    let map = damage_map_out.map(|_| DamageMap::from_scan(&data, &located_frames, &damaged));
+    if let (Some(path), Some(map)) = (damage_map_out, &map) {
+        write_damage_map(map, path)?;
+    }
+
     let min_conf = min_confidence.unwrap_or(0.0);
+    let parity_frames = located_frames
+        .iter()
+        .filter(|lf| parity_descriptor(&lf.frame).is_some())
+        .count();
 
     if jsonl {
         // Prepare writer (stdout or file)
@@ -101,14 +297,17 @@ pub fn execute_ext(
                 markers_found: stats.markers_found,
                 frames_found: stats.frames_found,
                 decode_failures: stats.decode_failures,
+                header_checksum_failures: stats.header_checksum_failures,
+                damaged_frames: stats.damaged_frames,
+                overlaps_rejected: stats.rejected_overlaps.len(),
                 bytes_recovered: stats.bytes_recovered,
                 recovery_rate: stats.recovery_rate(),
+                parity_frames,
             },
         )?;
 
         // Compute gaps via timeline reconstruction
-        let frames_only: Vec<_> = located_frames.iter().map(|lf| lf.frame.clone()).collect();
-        let timeline = link_frames(frames_only);
+        let timeline = link_indexed(&located_frames);
         // Build a map of frame_id -> confidence for gap confidence computation
         let mut conf_map: HashMap<u64, f32> = HashMap::new();
         for lf in &located_frames {
@@ -141,6 +340,9 @@ pub fn execute_ext(
                 size: lf.size,
                 payload: payload_str,
                 confidence: lf.confidence,
+                bit_shift: lf.bit_shift,
+                inverted: lf.inverted,
+                parity: parity_descriptor(&lf.frame).map(ParityJson::from),
             });
             write_jsonl(&mut writer, &rec)?;
         }
@@ -149,7 +351,7 @@ pub fn execute_ext(
         if let Some(pattern) = carve_payloads {
             let stream_id = 0usize; // single-stream file
             for lf in &located_frames {
-                if lf.confidence < min_conf {
+                if lf.confidence < min_conf || parity_descriptor(&lf.frame).is_some() {
                     continue;
                 }
                 let path = pattern
@@ -164,14 +366,42 @@ pub fn execute_ext(
     }
 
     // Non-JSONL: print human-readable summary and optional JSON file
-    println!("\n=== Scan Results ===");
-    println!("Bytes scanned:     {} bytes", stats.bytes_scanned);
-    println!("Markers found:     {}", stats.markers_found);
-    println!("Valid frames:      {}", stats.frames_found);
-    println!("Decode failures:   {}", stats.decode_failures);
-    println!("Bytes recovered:   {} bytes", stats.bytes_recovered);
-    println!("Recovery rate:     {:.2}%", stats.recovery_rate());
-    println!();
+    writeln!(human, "\n=== Scan Results ===")?;
+    writeln!(human, "Bytes scanned:     {} bytes", stats.bytes_scanned)?;
+    writeln!(human, "Markers found:     {}", stats.markers_found)?;
+    writeln!(human, "Valid frames:      {}", stats.frames_found)?;
+    writeln!(human, "Decode failures:   {}", stats.decode_failures)?;
+    if stats.header_checksum_failures > 0 || stats.damaged_frames > 0 {
+        writeln!(
+            human,
+            "Header CRC rejects: {}",
+            stats.header_checksum_failures
+        )?;
+        writeln!(human, "Damaged frames:    {}", stats.damaged_frames)?;
+    }
+    if !stats.rejected_overlaps.is_empty() {
+        writeln!(
+            human,
+            "Overlaps rejected: {}",
+            stats.rejected_overlaps.len()
+        )?;
+    }
+    writeln!(human, "Bytes recovered:   {} bytes", stats.bytes_recovered)?;
+    writeln!(human, "Recovery rate:     {:.2}%", stats.recovery_rate())?;
+    if parity_frames > 0 {
+        let blocks = in_band_blocks(located_frames.iter().map(|lf| &lf.frame));
+        writeln!(
+            human,
+            "Parity frames:     {} ({} blocks)",
+            parity_frames,
+            blocks.len()
+        )?;
+    }
+    writeln!(human)?;
+
+    if let Some(map) = &map {
+        render_damage_map(&mut human, map)?;
+    }
 
     if stats_only {
         return Ok(());
@@ -190,6 +420,9 @@ pub fn execute_ext(
                 size: lf.size,
                 payload: payload_str,
                 confidence: lf.confidence,
+                bit_shift: lf.bit_shift,
+                inverted: lf.inverted,
+                parity: parity_descriptor(&lf.frame).map(ParityJson::from),
             }
         })
         .collect();
@@ -208,12 +441,27 @@ pub fn execute_ext(
         }
     } else {
         // Print to stdout
-        println!("=== Recovered Frames ===");
+        writeln!(human, "=== Recovered Frames ===")?;
         for frame in &recovered {
-            println!(
-                "Frame {} @ offset {}: {} bytes (conf {:.2})",
-                frame.frame_id, frame.offset, frame.size, frame.confidence
-            );
+            let parity = frame
+                .parity
+                .as_ref()
+                .map(|p| {
+                    format!(
+                        " [parity {} {}/{} for {}..={}]",
+                        p.code,
+                        p.index + 1,
+                        p.parity,
+                        p.block_start_id,
+                        p.block_start_id + p.data as u64 - 1
+                    )
+                })
+                .unwrap_or_default();
+            writeln!(
+                human,
+                "Frame {} @ offset {}: {} bytes (conf {:.2}){}",
+                frame.frame_id, frame.offset, frame.size, frame.confidence, parity
+            )?;
         }
     }
 
@@ -221,7 +469,7 @@ pub fn execute_ext(
     if let Some(pattern) = carve_payloads {
         let stream_id = 0usize; // single-stream file
         for lf in &located_frames {
-            if lf.confidence < min_conf {
+            if lf.confidence < min_conf || parity_descriptor(&lf.frame).is_some() {
                 continue;
             }
             let path = pattern
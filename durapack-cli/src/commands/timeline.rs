@@ -1,18 +1,42 @@
 use anyhow::{bail, Context, Result};
-use durapack_core::{
-    linker::{analyze_located_frames, link_frames, report_to_dot, RecoveryRecipe},
-    scanner::scan_stream,
+use durapack_core::html_report::index_report_to_html;
+use durapack_core::linker::{
+    analyze_indexed, index_report_to_dot, link_indexed_with_confidence, RecoveryRecipe,
 };
+use durapack_core::parity::{in_band_blocks, parity_descriptor};
+use durapack_core::types::Frame;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use std::fs;
 use std::io::{self, Read, Write};
 use tracing::info;
 
+use super::{code_label, ParityJson};
+
 #[derive(Serialize, Deserialize)]
 struct TimelineFrame {
     frame_id: u64,
     prev_hash: String,
+    /// Empty for parity frames, whose payload is a FEC shard
     payload: String,
+    /// Block descriptor when this is an in-band parity frame
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parity: Option<ParityJson>,
+}
+
+impl TimelineFrame {
+    fn new(f: &Frame) -> Self {
+        let parity = parity_descriptor(f);
+        Self {
+            frame_id: f.header.frame_id,
+            prev_hash: hex::encode(f.header.prev_hash),
+            payload: match parity {
+                Some(_) => String::new(),
+                None => String::from_utf8_lossy(&f.payload).to_string(),
+            },
+            parity: parity.map(ParityJson::from),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -21,10 +45,27 @@ struct TimelineOutput {
     gaps: Vec<TimelineGap>,
     orphans: Vec<TimelineFrame>,
     stats: TimelineStats,
+    /// Parity blocks described by in-band parity frames
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    parity_blocks: Vec<ParityBlockJson>,
     #[serde(skip_serializing_if = "Option::is_none")]
     analysis: Option<AnalysisExtras>,
 }
 
+#[derive(Serialize, Deserialize)]
+struct ParityBlockJson {
+    code: String,
+    block_start_id: u64,
+    data: usize,
+    parity: usize,
+    /// Parity frame IDs in shard order, including inferred IDs of lost ones
+    parity_frame_ids: Vec<u64>,
+    parity_found: usize,
+    missing_data: Vec<u64>,
+    /// Enough parity survives to rebuild the missing data frames
+    repairable: bool,
+}
+
 #[derive(Serialize, Deserialize)]
 struct TimelineGap {
     before: u64,
@@ -37,6 +78,9 @@ struct TimelineStats {
     gaps: usize,
     orphans: usize,
     continuity: f64,
+    /// Ordered frames that are in-band parity rather than data
+    #[serde(default)]
+    parity_frames: usize,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -52,6 +96,13 @@ struct ConflictJson {
     contenders: Vec<u64>,
 }
 
+#[derive(Serialize, Deserialize)]
+struct EquivocationJson {
+    frame_id: u64,
+    hashes: Vec<String>,
+    offsets: Vec<usize>,
+}
+
 #[derive(Serialize, Deserialize)]
 struct OrphanClusterJson {
     ids: Vec<u64>,
@@ -75,15 +126,30 @@ enum RecipeJson {
 struct AnalysisExtras {
     gap_reasons: Vec<GapReasonJson>,
     conflicts: Vec<ConflictJson>,
+    #[serde(default)]
+    equivocations: Vec<EquivocationJson>,
+    /// IDs of frames whose hash link contradicts their ID
+    #[serde(default)]
+    renumbered: Vec<u64>,
     orphan_clusters: Vec<OrphanClusterJson>,
     recipes: Vec<RecipeJson>,
 }
 
 #[allow(dead_code)]
 pub fn execute(input: &str, output: &str, include_orphans: bool) -> Result<()> {
-    execute_ext(input, output, include_orphans, false, false, None)
+    execute_ext(
+        input,
+        output,
+        include_orphans,
+        false,
+        false,
+        None,
+        false,
+        false,
+    )
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn execute_ext(
     input: &str,
     output: &str,
@@ -91,8 +157,16 @@ pub fn execute_ext(
     dot: bool,
     analyze: bool,
     fec_index_path: Option<&str>,
+    use_index: bool,
+    html: bool,
 ) -> Result<()> {
     info!("Reconstructing timeline from: {}", input);
+    if html && dot {
+        bail!("--html and --dot are mutually exclusive");
+    }
+    if use_index && input == "-" {
+        bail!("--index needs a file input, not stdin");
+    }
 
     // Read input ("-" for stdin)
     let data = if input == "-" {
@@ -104,17 +178,21 @@ pub fn execute_ext(
     };
 
     // Scan for frames
-    let located_frames = scan_stream(&data);
+    let located_frames = super::locate_frames(input, &data, use_index)?;
 
     if located_frames.is_empty() {
         bail!("No valid frames found in input");
     }
 
     info!("Found {} frames", located_frames.len());
+    let parity_blocks = in_band_blocks(located_frames.iter().map(|lf| &lf.frame));
 
     // Optional FEC index
     #[derive(Serialize, Deserialize, Clone)]
     struct FecIndexEntry {
+        /// Absent in sidecars written before RaptorQ support; those are RS
+        #[serde(default)]
+        scheme: Option<String>,
         block_start_id: u64,
         data: usize,
         parity: usize,
@@ -128,9 +206,13 @@ pub fn execute_ext(
         fec_index = Some(entries);
     }
 
-    // Extract frames and link (basic timeline always available)
-    let frames: Vec<_> = located_frames.iter().map(|lf| lf.frame.clone()).collect();
-    let timeline = link_frames(frames);
+    // Link by index into the scanned frames (basic timeline always available);
+    // the HTML report always shows the full analysis
+    let report = (analyze || html).then(|| analyze_indexed(&located_frames));
+    let timeline = match &report {
+        Some(r) => r.index.clone(),
+        None => link_indexed_with_confidence(&located_frames, |i| located_frames[i].confidence),
+    };
 
     info!(
         "Timeline: {} ordered, {} gaps, {} orphans",
@@ -147,25 +229,31 @@ pub fn execute_ext(
             Box::new(fs::File::create(output)?)
         };
 
-        if analyze {
-            let report = analyze_located_frames(located_frames);
-            let dot_str = report_to_dot(&report);
+        if let Some(report) = &report {
+            let dot_str = index_report_to_dot(report, &located_frames);
             write!(&mut out, "{}", dot_str)?;
         } else {
             // Basic DOT (backwards compatible)
             writeln!(&mut out, "digraph timeline {{")?;
             writeln!(&mut out, "  rankdir=LR;")?;
-            for f in &timeline.frames {
-                writeln!(
-                    &mut out,
-                    "  {} [label=\"{}\"];",
-                    f.header.frame_id, f.header.frame_id
-                )?;
+            let ids: Vec<u64> = timeline
+                .ordered(&located_frames)
+                .map(|f| f.header.frame_id)
+                .collect();
+            let parity_ids: BTreeSet<u64> = timeline
+                .ordered(&located_frames)
+                .filter(|f| parity_descriptor(f).is_some())
+                .map(|f| f.header.frame_id)
+                .collect();
+            for id in &ids {
+                if parity_ids.contains(id) {
+                    writeln!(&mut out, "  {} [label=\"{}\", shape=box];", id, id)?;
+                } else {
+                    writeln!(&mut out, "  {} [label=\"{}\"];", id, id)?;
+                }
             }
-            for win in timeline.frames.windows(2) {
-                let a = win[0].header.frame_id;
-                let b = win[1].header.frame_id;
-                writeln!(&mut out, "  {} -> {};", a, b)?;
+            for win in ids.windows(2) {
+                writeln!(&mut out, "  {} -> {};", win[0], win[1])?;
             }
             for g in &timeline.gaps {
                 writeln!(
@@ -179,8 +267,26 @@ pub fn execute_ext(
                 for e in idx {
                     writeln!(
                         &mut out,
-                        "  subgraph cluster_fec_{} {{ label=\"RS {}+{}\"; style=dotted; }}",
-                        e.block_start_id, e.data, e.parity
+                        "  subgraph cluster_fec_{} {{ label=\"{} {}+{}\"; style=dotted; }}",
+                        e.block_start_id,
+                        match e.scheme.as_deref() {
+                            Some("raptorq") => "RaptorQ",
+                            _ => "RS",
+                        },
+                        e.data,
+                        e.parity
+                    )?;
+                }
+            } else if !parity_blocks.is_empty() {
+                writeln!(&mut out, "  // FEC parity annotations (in-band)")?;
+                for b in &parity_blocks {
+                    writeln!(
+                        &mut out,
+                        "  subgraph cluster_fec_{} {{ label=\"{} {}+{}\"; style=dotted; }}",
+                        b.block.block_start_id,
+                        code_label(b.code),
+                        b.block.data,
+                        b.block.parity
                     )?;
                 }
             }
@@ -190,26 +296,32 @@ pub fn execute_ext(
         return Ok(());
     }
 
+    if let (true, Some(report)) = (html, &report) {
+        let title = format!(
+            "Durapack timeline: {}",
+            if input == "-" { "stdin" } else { input }
+        );
+        let page = index_report_to_html(report, &located_frames, &title);
+        if output == "-" {
+            io::stdout().write_all(page.as_bytes())?;
+        } else {
+            fs::write(output, page)
+                .with_context(|| format!("Failed to write HTML report: {}", output))?;
+            info!("HTML report written to: {}", output);
+        }
+        return Ok(());
+    }
+
     // JSON output path
     let frames_output: Vec<TimelineFrame> = timeline
-        .frames
-        .iter()
-        .map(|f| TimelineFrame {
-            frame_id: f.header.frame_id,
-            prev_hash: hex::encode(f.header.prev_hash),
-            payload: String::from_utf8_lossy(&f.payload).to_string(),
-        })
+        .ordered(&located_frames)
+        .map(TimelineFrame::new)
         .collect();
 
     let orphans_output: Vec<TimelineFrame> = if include_orphans {
         timeline
-            .orphans
-            .iter()
-            .map(|f| TimelineFrame {
-                frame_id: f.header.frame_id,
-                prev_hash: hex::encode(f.header.prev_hash),
-                payload: String::from_utf8_lossy(&f.payload).to_string(),
-            })
+            .orphaned(&located_frames)
+            .map(TimelineFrame::new)
             .collect()
     } else {
         Vec::new()
@@ -230,10 +342,28 @@ pub fn execute_ext(
         gaps: stats.gaps,
         orphans: stats.orphans,
         continuity: stats.continuity,
+        parity_frames: frames_output.iter().filter(|f| f.parity.is_some()).count(),
     };
 
-    let mut analysis = if analyze {
-        let report = analyze_located_frames(located_frames);
+    let present: BTreeSet<u64> = located_frames
+        .iter()
+        .map(|lf| lf.frame.header.frame_id)
+        .collect();
+    let parity_blocks_output: Vec<ParityBlockJson> = parity_blocks
+        .iter()
+        .map(|b| ParityBlockJson {
+            code: b.code.name().to_string(),
+            block_start_id: b.block.block_start_id,
+            data: b.block.data,
+            parity: b.block.parity,
+            parity_frame_ids: b.block.parity_frame_ids.clone(),
+            parity_found: b.parity_found.len(),
+            missing_data: b.missing_data(&present),
+            repairable: b.repairable(&present),
+        })
+        .collect();
+
+    let mut analysis = if let Some(report) = report {
         let gap_reasons: Vec<GapReasonJson> = report
             .gap_details
             .iter()
@@ -251,6 +381,21 @@ pub fn execute_ext(
                 contenders: c.contenders.clone(),
             })
             .collect();
+        let equivocations: Vec<EquivocationJson> = report
+            .equivocations
+            .iter()
+            .map(|e| EquivocationJson {
+                frame_id: e.frame_id,
+                hashes: e.hashes.iter().map(hex::encode).collect(),
+                offsets: e.offsets.clone(),
+            })
+            .collect();
+        let renumbered: Vec<u64> = report
+            .index
+            .renumbered
+            .iter()
+            .map(|&i| located_frames[i].frame.header.frame_id)
+            .collect();
         let orphan_clusters: Vec<OrphanClusterJson> = report
             .orphan_clusters
             .iter()
@@ -280,6 +425,8 @@ pub fn execute_ext(
         Some(AnalysisExtras {
             gap_reasons,
             conflicts,
+            equivocations,
+            renumbered,
             orphan_clusters,
             recipes,
         })
@@ -293,6 +440,8 @@ pub fn execute_ext(
             analysis = Some(AnalysisExtras {
                 gap_reasons: Vec::new(),
                 conflicts: Vec::new(),
+                equivocations: Vec::new(),
+                renumbered: Vec::new(),
                 orphan_clusters: Vec::new(),
                 recipes: Vec::new(),
             });
@@ -305,6 +454,7 @@ pub fn execute_ext(
         gaps: gaps_output,
         orphans: orphans_output,
         stats: stats_output,
+        parity_blocks: parity_blocks_output,
         analysis,
     };
 
@@ -322,6 +472,13 @@ pub fn execute_ext(
     println!("Gaps detected:   {}", output_obj.gaps.len());
     println!("Orphaned frames: {}", output_obj.orphans.len());
     println!("Continuity:      {:.2}%", output_obj.stats.continuity);
+    if !output_obj.parity_blocks.is_empty() {
+        println!(
+            "Parity frames:   {} ({} blocks)",
+            output_obj.stats.parity_frames,
+            output_obj.parity_blocks.len()
+        );
+    }
     if output != "-" {
         println!("\nTimeline written to: {}", output);
     }
@@ -1,18 +1,36 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use colored::*;
-#[cfg(feature = "fec-rs")]
-use durapack_core::fec::{RedundancyDecoder, RsDecoder};
+#[cfg(not(feature = "fec-rs"))]
+use durapack_core::recover::RecoveredTimeline;
 use durapack_core::{
     constants::TrailerType,
-    linker::{link_frames, verify_backlinks},
-    scanner::scan_stream,
+    error::FrameError,
+    fec::RS_SHARD_LAYOUT,
+    linker::{
+        analyze_indexed, verify_backlinks_indexed, FlagInconsistency, GapReason, SequenceEnd,
+    },
+    parity::{in_band_blocks, FecCode},
+    recover::ParityBlock,
+    scanner::{scan_stream_with_damaged, DamagedFrame, LocatedFrame},
+    tamper::{self, Cause, FindingKind, Severity},
+    types::Frame,
+};
+#[cfg(feature = "fec-rs")]
+use durapack_core::{
+    encoder::encode_frame_struct_exact,
+    fec::{RsDecoder, RsWholeFrameDecoder},
+    recover::link_with_fec,
 };
 #[cfg(feature = "ed25519-signatures")]
 use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::Serialize;
+use std::collections::BTreeSet;
 use std::fs;
 use std::io::{self, Read, Write};
 use tracing::{info, warn};
 
+use super::code_label;
+
 pub fn export_strip_signatures(input: &str, output: &str) -> Result<()> {
     // Read input bytes
     let data = if input == "-" {
@@ -61,18 +79,66 @@ pub fn export_strip_signatures(input: &str, output: &str) -> Result<()> {
     Ok(())
 }
 
+/// Overall verdict of `verify`, reported through the process exit status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyStatus {
+    /// Every frame is valid and the sequence is provably complete
+    Complete,
+    /// Frames are intact, but the sequence has gaps, is truncated, or lacks first/last markers
+    Incomplete,
+    /// Invalid frames, broken back-links, equivocation, renumbered IDs or inconsistent first/last flags
+    Damaged,
+}
+
+impl VerifyStatus {
+    /// Process exit code: 0 complete, 2 incomplete, 3 damaged (1 is left for errors)
+    pub fn exit_code(self) -> i32 {
+        match self {
+            VerifyStatus::Complete => 0,
+            VerifyStatus::Incomplete => 2,
+            VerifyStatus::Damaged => 3,
+        }
+    }
+}
+
 #[allow(dead_code)]
-pub fn execute(input: &str, report_gaps: bool) -> Result<()> {
-    execute_ext(input, report_gaps, None, false)
+pub fn execute(input: &str, report_gaps: bool) -> Result<VerifyStatus> {
+    execute_ext(input, report_gaps, None, false, false, None, None, None)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn execute_ext(
     input: &str,
     report_gaps: bool,
     fec_index_path: Option<&str>,
     rs_repair: bool,
-) -> Result<()> {
+    use_index: bool,
+    tamper_report_path: Option<&str>,
+    repair_out: Option<&str>,
+    repair_report_path: Option<&str>,
+) -> Result<VerifyStatus> {
     info!("Verifying file: {}", input);
+    if use_index && input == "-" {
+        bail!("--index needs a file input, not stdin");
+    }
+    if rs_repair && input == "-" && repair_out.is_none() {
+        bail!("--rs-repair on stdin needs --repair-out");
+    }
+    let repair_to_stdout = rs_repair && repair_out == Some("-");
+    let report_to_stdout = rs_repair && repair_report_path == Some("-");
+    let tamper_to_stdout = tamper_report_path == Some("-");
+    if repair_to_stdout && report_to_stdout {
+        bail!("--repair-out and --repair-report cannot both write to stdout (\"-\")");
+    }
+    if tamper_to_stdout && (repair_to_stdout || report_to_stdout) {
+        bail!("--tamper-report cannot write to stdout (\"-\") alongside repair output");
+    }
+    // Keep stdout clean when the repaired stream or a JSON report goes there
+    let mut human: Box<dyn Write> = if repair_to_stdout || report_to_stdout || tamper_to_stdout {
+        Box::new(io::sink())
+    } else {
+        Box::new(io::stdout())
+    };
 
     // Read input file or stdin
     let data = if input == "-" {
@@ -83,19 +149,25 @@ pub fn execute_ext(
         fs::read(input).with_context(|| format!("Failed to read input file: {}", input))?
     };
 
-    // Scan for frames
-    let located_frames = scan_stream(&data);
+    // Scan for frames; the tamper report also needs the damaged headers only
+    // a full scan finds, so it takes one scan for both instead of the index
+    let (located_frames, damaged) = if tamper_report_path.is_some() {
+        let (located, damaged, _) = scan_stream_with_damaged(&data);
+        (located, damaged)
+    } else {
+        (super::locate_frames(input, &data, use_index)?, Vec::new())
+    };
 
     if located_frames.is_empty() {
-        println!("{} No valid frames found", "✗".red());
-        return Ok(());
+        writeln!(human, "{} No valid frames found", "✗".red())?;
+        return Ok(VerifyStatus::Damaged);
     }
 
-    println!("\n=== Verification Results ===");
-    println!("Total frames found: {}", located_frames.len());
+    writeln!(human, "\n=== Verification Results ===")?;
+    writeln!(human, "Total frames found: {}", located_frames.len())?;
 
-    // Extract frames
-    let frames: Vec<_> = located_frames.iter().map(|lf| lf.frame.clone()).collect();
+    // Borrow frames; payloads are never copied
+    let frames: Vec<&Frame> = located_frames.iter().map(|lf| &lf.frame).collect();
 
     // Verify each frame individually
     let mut valid_frames = 0;
@@ -111,11 +183,19 @@ pub fn execute_ext(
         }
     }
 
-    println!("Valid frames:       {}", valid_frames.to_string().green());
+    writeln!(
+        human,
+        "Valid frames:       {}",
+        valid_frames.to_string().green()
+    )?;
     if invalid_frames > 0 {
-        println!("Invalid frames:     {}", invalid_frames.to_string().red());
+        writeln!(
+            human,
+            "Invalid frames:     {}",
+            invalid_frames.to_string().red()
+        )?;
     } else {
-        println!("Invalid frames:     {}", invalid_frames);
+        writeln!(human, "Invalid frames:     {}", invalid_frames)?;
     }
 
     // Signature verification (best-effort): if any frame carries Blake3+Sig, try to verify with a provided key env var
@@ -125,7 +205,7 @@ pub fn execute_ext(
             .iter()
             .any(|f| f.header.flags.trailer_type() == TrailerType::Blake3WithEd25519Sig)
         {
-            println!("\n=== Signatures (Ed25519) ===");
+            writeln!(human, "\n=== Signatures (Ed25519) ===")?;
             if let Ok(pk_path) = std::env::var("DURAPACK_VERIFY_PUBKEY") {
                 match fs::read(pk_path) {
                     Ok(bytes) => {
@@ -145,133 +225,739 @@ pub fn execute_ext(
                                 {
                                     continue;
                                 }
-                                // trailer: [32 hash][64 sig]
-                                if let Some(tr) = &f.trailer {
-                                    if tr.len() == 96 {
-                                        let sig_bytes: [u8; 64] = tr[32..96].try_into().unwrap();
-                                        let sig = Signature::from_bytes(&sig_bytes);
-                                        // Build message: marker+header+payload
-                                        let mut msg = Vec::with_capacity(
-                                            durapack_core::constants::MIN_HEADER_SIZE
-                                                + f.payload.len(),
-                                        );
-                                        msg.extend_from_slice(
-                                            durapack_core::constants::FRAME_MARKER,
-                                        );
-                                        msg.push(f.header.version);
-                                        msg.extend_from_slice(&f.header.frame_id.to_be_bytes());
-                                        msg.extend_from_slice(&f.header.prev_hash);
-                                        msg.extend_from_slice(&f.header.payload_len.to_be_bytes());
-                                        msg.push(f.header.flags.as_u8());
-                                        msg.extend_from_slice(&f.payload);
-                                        if vk.verify(&msg, &sig).is_ok() {
-                                            ok += 1;
-                                        } else {
-                                            bad += 1;
-                                        }
-                                    }
+                                if signature_valid(&vk, f) {
+                                    ok += 1;
+                                } else {
+                                    bad += 1;
                                 }
                             }
-                            println!("Verified signatures: {} valid, {} invalid", ok, bad);
+                            writeln!(human, "Verified signatures: {} valid, {} invalid", ok, bad)?;
                         } else {
-                            println!("Public key file invalid (expected 32 bytes)");
+                            writeln!(human, "Public key file invalid (expected 32 bytes)")?;
                         }
                     }
-                    Err(_) => println!(
+                    Err(_) => writeln!(
+                        human,
                         "Public key not found; set DURAPACK_VERIFY_PUBKEY to a 32-byte key file"
-                    ),
+                    )?,
                 }
             } else {
-                println!("Set DURAPACK_VERIFY_PUBKEY to verify signatures (32-byte key)");
+                writeln!(
+                    human,
+                    "Set DURAPACK_VERIFY_PUBKEY to verify signatures (32-byte key)"
+                )?;
             }
         }
     }
 
     // Link frames and check back-links
-    let timeline = link_frames(frames);
+    let report = analyze_indexed(&located_frames);
+    let timeline = &report.index;
 
-    println!("\n=== Timeline Analysis ===");
-    println!("Ordered frames:     {}", timeline.frames.len());
-    println!("Orphaned frames:    {}", timeline.orphans.len());
-    println!("Detected gaps:      {}", timeline.gaps.len());
+    writeln!(human, "\n=== Timeline Analysis ===")?;
+    writeln!(human, "Ordered frames:     {}", timeline.frames.len())?;
+    writeln!(human, "Orphaned frames:    {}", timeline.orphans.len())?;
+    writeln!(human, "Detected gaps:      {}", timeline.gaps.len())?;
 
     let stats = timeline.stats();
-    println!("Continuity:         {:.2}%", stats.continuity);
+    writeln!(human, "Continuity:         {:.2}%", stats.continuity)?;
 
     // Verify back-links
-    let backlink_errors = verify_backlinks(&timeline);
+    let backlink_errors = verify_backlinks_indexed(timeline, &located_frames);
 
-    println!("\n=== Back-link Verification ===");
+    writeln!(human, "\n=== Back-link Verification ===")?;
     if backlink_errors.is_empty() {
-        println!("{} All back-links valid", "✓".green());
+        writeln!(human, "{} All back-links valid", "✓".green())?;
     } else {
-        println!(
+        writeln!(
+            human,
             "{} {} back-link errors found",
             "✗".red(),
             backlink_errors.len()
-        );
+        )?;
         for error in &backlink_errors {
             warn!("{}", error);
         }
     }
 
-    // Optional: Load FEC index and attempt RS repair (report-only)
-    if let (Some(path), true) = (fec_index_path, rs_repair) {
-        #[derive(serde::Deserialize)]
-        struct FecIndexEntry {
-            block_start_id: u64,
-            data: usize,
-            parity: usize,
-            _parity_frame_ids: Vec<u64>,
+    // Same frame ID with different content is a tamper indicator
+    if !report.equivocations.is_empty() {
+        writeln!(human, "\n=== Equivocation (possible tampering) ===")?;
+        for eq in &report.equivocations {
+            let offsets: Vec<String> = eq.offsets.iter().map(|o| o.to_string()).collect();
+            writeln!(
+                human,
+                "{} Frame {} has {} different versions at offsets {}",
+                "✗".red(),
+                eq.frame_id,
+                eq.hashes.len(),
+                offsets.join(", ")
+            )?;
         }
-        let idx_bytes =
-            fs::read(path).with_context(|| format!("Failed to read FEC index: {}", path))?;
-        let _entries: Vec<FecIndexEntry> =
-            serde_json::from_slice(&idx_bytes).with_context(|| "Invalid FEC index JSON")?;
-        println!("\n=== FEC (RS) Repair Simulation ===");
-        #[cfg(feature = "fec-rs")]
-        {
-            let dec = RsDecoder;
-            for entry in _entries {
-                let have = entry.data; // assume we have all data frames here; a real flow would map IDs to present/missing
-                let can = dec.can_reconstruct(have + entry.parity, entry.data);
-                println!(
-                    "Block start id {}: data={}, parity={} => reconstructable: {}",
-                    entry.block_start_id,
-                    entry.data,
-                    entry.parity,
-                    if can { "yes" } else { "no" }
-                );
-            }
+    }
+    if !timeline.renumbered.is_empty() {
+        let ids: Vec<String> = timeline
+            .renumbered
+            .iter()
+            .map(|&i| located_frames[i].frame.header.frame_id.to_string())
+            .collect();
+        writeln!(
+            human,
+            "{} Frame IDs contradict hash links (renumbered): {}",
+            "✗".red(),
+            ids.join(", ")
+        )?;
+    }
+    if !timeline.duplicates.is_empty() {
+        writeln!(
+            human,
+            "Exact duplicate frames skipped: {}",
+            timeline.duplicates.len()
+        )?;
+    }
+
+    // First/last markers: is this the whole sequence, or was it cut short?
+    let completeness = &report.completeness;
+    writeln!(human, "\n=== Sequence Completeness ===")?;
+    match timeline.frames.first() {
+        Some(&i) if completeness.starts_at_first => writeln!(
+            human,
+            "{} Starts at IS_FIRST frame {}",
+            "✓".green(),
+            located_frames[i].frame.header.frame_id
+        )?,
+        Some(&i) => writeln!(
+            human,
+            "{} Starts at frame {} without IS_FIRST: earlier frames may be missing",
+            "!".yellow(),
+            located_frames[i].frame.header.frame_id
+        )?,
+        None => {}
+    }
+    match completeness.end {
+        SequenceEnd::Clean { last } => {
+            writeln!(human, "{} Ends at IS_LAST frame {}", "✓".green(), last)?
         }
-        #[cfg(not(feature = "fec-rs"))]
-        {
-            println!("RS repair requested, but durapack-core was built without `fec-rs` feature.");
+        SequenceEnd::Truncated { last_seen } => writeln!(
+            human,
+            "{} No IS_LAST after frame {}: stream may be truncated",
+            "!".yellow(),
+            last_seen
+        )?,
+        SequenceEnd::Empty => {}
+    }
+    for issue in &completeness.inconsistencies {
+        match issue {
+            FlagInconsistency::FirstWithPrevHash { frame_id } => writeln!(
+                human,
+                "{} Frame {} is flagged IS_FIRST but has a non-zero prev_hash",
+                "✗".red(),
+                frame_id
+            )?,
+            FlagInconsistency::LastWithSuccessor {
+                frame_id,
+                successor,
+            } => writeln!(
+                human,
+                "{} Frame {} is flagged IS_LAST but frame {} links to it",
+                "✗".red(),
+                frame_id,
+                successor
+            )?,
+        }
+    }
+
+    // Parity frames describe the blocks they protect; no sidecar needed
+    let parity_blocks = in_band_blocks(frames.iter().copied());
+    if !parity_blocks.is_empty() {
+        let present: BTreeSet<u64> = frames.iter().map(|f| f.header.frame_id).collect();
+        writeln!(human, "\n=== In-band Parity ===")?;
+        for b in &parity_blocks {
+            let missing = b.missing_data(&present);
+            let status = if missing.is_empty() {
+                format!("{} no data missing", "✓".green())
+            } else if b.repairable(&present) {
+                format!(
+                    "{} {} data frame(s) missing, repairable",
+                    "!".yellow(),
+                    missing.len()
+                )
+            } else {
+                format!(
+                    "{} {} data frame(s) missing, not repairable",
+                    "✗".red(),
+                    missing.len()
+                )
+            };
+            writeln!(
+                human,
+                "{} block {}..={} ({}+{}, {} parity found): {}",
+                code_label(b.code),
+                b.block.block_start_id,
+                b.block.block_start_id + b.block.data as u64 - 1,
+                b.block.data,
+                b.block.parity,
+                b.parity_found.len(),
+                status
+            )?;
         }
     }
 
+    // Optional: rebuild missing frames from RS parity and write a repaired file
+    if rs_repair {
+        let blocks = repair_blocks(fec_index_path, &frames)?;
+        run_rs_repair(
+            input,
+            &located_frames,
+            &blocks,
+            timeline.gaps.len(),
+            repair_out,
+            repair_report_path,
+            &mut human,
+        )?;
+    }
+
     // Report gaps if requested
     if report_gaps && !timeline.gaps.is_empty() {
-        println!("\n=== Detected Gaps ===");
+        writeln!(human, "\n=== Detected Gaps ===")?;
         for gap in &timeline.gaps {
-            println!("Gap between frame {} and frame {}", gap.before, gap.after);
+            writeln!(
+                human,
+                "Gap between frame {} and frame {}",
+                gap.before, gap.after
+            )?;
         }
     }
 
-    // Overall status
-    println!("\n=== Summary ===");
-    if invalid_frames == 0 && backlink_errors.is_empty() && timeline.gaps.is_empty() {
-        println!("{} File is fully valid and complete", "✓".green());
+    // Overall status. A frame missing by ID also breaks the back-link of the
+    // frame after it; that is a gap, not damage.
+    let missing_before = |e: &FrameError| match e {
+        FrameError::BackLinkMismatch(id) => report
+            .gap_details
+            .iter()
+            .any(|g| g.gap.after == *id && g.reason == GapReason::MissingById),
+        _ => false,
+    };
+    writeln!(human, "\n=== Summary ===")?;
+    let status = if !report.equivocations.is_empty() {
+        writeln!(
+            human,
+            "{} File contains conflicting versions of {} frame ID(s): possible tampering",
+            "✗".red(),
+            report.equivocations.len()
+        )?;
+        VerifyStatus::Damaged
     } else if invalid_frames > 0 {
-        println!("{} File contains invalid frames", "✗".red());
-    } else if !backlink_errors.is_empty() {
-        println!("{} File has back-link integrity issues", "✗".red());
-    } else {
-        println!(
+        writeln!(human, "{} File contains invalid frames", "✗".red())?;
+        VerifyStatus::Damaged
+    } else if backlink_errors.iter().any(|e| !missing_before(e)) {
+        writeln!(human, "{} File has back-link integrity issues", "✗".red())?;
+        VerifyStatus::Damaged
+    } else if !timeline.renumbered.is_empty() {
+        writeln!(
+            human,
+            "{} Frame IDs contradict the hash chain: possible renumbering",
+            "✗".red()
+        )?;
+        VerifyStatus::Damaged
+    } else if !completeness.inconsistencies.is_empty() {
+        writeln!(
+            human,
+            "{} First/last flags contradict the back-links",
+            "✗".red()
+        )?;
+        VerifyStatus::Damaged
+    } else if !timeline.gaps.is_empty() {
+        writeln!(
+            human,
             "{} File is valid but has gaps in the sequence",
             "!".yellow()
-        );
+        )?;
+        VerifyStatus::Incomplete
+    } else if completeness.is_truncated() {
+        writeln!(
+            human,
+            "{} File is valid but ends without IS_LAST: possibly truncated",
+            "!".yellow()
+        )?;
+        VerifyStatus::Incomplete
+    } else if !completeness.is_complete() {
+        writeln!(
+            human,
+            "{} File is valid but does not start at an IS_FIRST frame",
+            "!".yellow()
+        )?;
+        VerifyStatus::Incomplete
+    } else {
+        writeln!(human, "{} File is fully valid and complete", "✓".green())?;
+        VerifyStatus::Complete
+    };
+
+    if let Some(path) = tamper_report_path {
+        write_tamper_report(&located_frames, &damaged, data.len(), path)?;
+    }
+
+    Ok(status)
+}
+
+/// A parity block considered by `--rs-repair`
+struct RepairBlock {
+    /// `None` for a sidecar scheme this build does not know
+    code: Option<FecCode>,
+    /// RS shards hold complete encoded frames
+    whole_frames: bool,
+    /// RS data shard layout the parity was computed over
+    shard_layout: u8,
+    block: ParityBlock,
+}
+
+impl RepairBlock {
+    /// Whether `RsDecoder` cannot read this block's shards
+    fn incompatible(&self) -> bool {
+        self.code == Some(FecCode::ReedSolomon)
+            && !self.whole_frames
+            && self.shard_layout != RS_SHARD_LAYOUT
     }
+}
 
+/// Parity blocks from the FEC sidecar, or from in-band parity frames without one
+fn repair_blocks(fec_index_path: Option<&str>, frames: &[&Frame]) -> Result<Vec<RepairBlock>> {
+    let Some(path) = fec_index_path else {
+        return Ok(in_band_blocks(frames.iter().copied())
+            .into_iter()
+            .map(|b| RepairBlock {
+                code: Some(b.code),
+                whole_frames: b.whole_frames(),
+                shard_layout: RS_SHARD_LAYOUT,
+                block: b.block,
+            })
+            .collect());
+    };
+    #[derive(serde::Deserialize)]
+    struct FecIndexEntry {
+        /// Absent in sidecars written before RaptorQ support; those are RS
+        #[serde(default)]
+        scheme: Option<String>,
+        block_start_id: u64,
+        data: usize,
+        parity: usize,
+        parity_frame_ids: Vec<u64>,
+        #[serde(default)]
+        whole_frames: bool,
+        /// Absent in sidecars written before RS shards carried header fields
+        #[serde(default = "legacy_shard_layout")]
+        shard_layout: u8,
+    }
+    fn legacy_shard_layout() -> u8 {
+        1
+    }
+    let idx_bytes =
+        fs::read(path).with_context(|| format!("Failed to read FEC index: {}", path))?;
+    let entries: Vec<FecIndexEntry> =
+        serde_json::from_slice(&idx_bytes).with_context(|| "Invalid FEC index JSON")?;
+    Ok(entries
+        .into_iter()
+        .map(|e| RepairBlock {
+            code: FecCode::from_name(e.scheme.as_deref().unwrap_or("rs")),
+            whole_frames: e.whole_frames,
+            shard_layout: e.shard_layout,
+            block: ParityBlock {
+                block_start_id: e.block_start_id,
+                data: e.data,
+                parity: e.parity,
+                parity_frame_ids: e.parity_frame_ids,
+            },
+        })
+        .collect())
+}
+
+#[derive(Serialize)]
+struct RepairBlockJson {
+    /// rs, raptorq, ldpc, or the sidecar's unknown scheme name
+    code: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    whole_frames: bool,
+    block_start_id: u64,
+    data: usize,
+    parity: usize,
+    parity_present: usize,
+    missing: Vec<u64>,
+    repaired: Vec<u64>,
+    /// intact, repaired, partial, unrepairable, failed, incompatible or unsupported
+    outcome: &'static str,
+}
+
+#[derive(Serialize)]
+struct RepairReportJson {
+    input: String,
+    /// Path of the repaired file; null when this build cannot decode RS
+    output: Option<String>,
+    frames_before: usize,
+    frames_after: usize,
+    gaps_before: usize,
+    gaps_after: usize,
+    repaired_frames: Vec<u64>,
+    /// Rebuilt by the decoder but rejected by the header checks
+    unverified_frames: Vec<u64>,
+    blocks: Vec<RepairBlockJson>,
+}
+
+/// Rebuild missing data frames of RS blocks, write the repaired stream and report per block
+///
+/// The repaired file holds the relinked chain followed by any orphans and
+/// conflicting copies, each frame re-encoded; signatures of surviving frames
+/// are carried over. Frames rebuilt from whole-frame blocks keep theirs too,
+/// while other rebuilt frames of signed streams get a zero signature.
+fn run_rs_repair(
+    input: &str,
+    located: &[LocatedFrame],
+    blocks: &[RepairBlock],
+    gaps_before: usize,
+    repair_out: Option<&str>,
+    repair_report_path: Option<&str>,
+    human: &mut dyn Write,
+) -> Result<()> {
+    writeln!(human, "\n=== FEC (RS) Repair ===")?;
+    if blocks.is_empty() {
+        writeln!(
+            human,
+            "No parity blocks found (no in-band parity frames and no --fec-index)"
+        )?;
+    }
+    let present: BTreeSet<u64> = located.iter().map(|lf| lf.frame.header.frame_id).collect();
+
+    #[cfg(feature = "fec-rs")]
+    let (recovered, output) = {
+        let rs = |whole_frames: bool| -> Vec<ParityBlock> {
+            blocks
+                .iter()
+                .filter(|b| {
+                    b.code == Some(FecCode::ReedSolomon)
+                        && b.whole_frames == whole_frames
+                        && !b.incompatible()
+                })
+                .map(|b| b.block.clone())
+                .collect()
+        };
+        let frames = located.iter().map(|lf| lf.frame.clone()).collect();
+        let mut recovered = link_with_fec(frames, &rs(false), &RsDecoder);
+        let whole = rs(true);
+        if !whole.is_empty() {
+            let frames = recovered
+                .timeline
+                .frames
+                .iter()
+                .chain(&recovered.timeline.orphans)
+                .chain(recovered.timeline.conflicting())
+                .cloned()
+                .collect();
+            let mut pass = link_with_fec(frames, &whole, &RsWholeFrameDecoder);
+            pass.reconstructed.extend(recovered.reconstructed);
+            pass.reconstructed.sort_unstable();
+            pass.unverified.extend(recovered.unverified);
+            pass.unverified.sort_unstable();
+            recovered = pass;
+        }
+
+        let mut out = Vec::new();
+        for f in recovered
+            .timeline
+            .frames
+            .iter()
+            .chain(&recovered.timeline.orphans)
+            .chain(recovered.timeline.conflicting())
+        {
+            out.extend_from_slice(&encode_frame_struct_exact(f)?);
+        }
+        let path = repair_out
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{}.repaired.durp", input));
+        if path == "-" {
+            io::stdout().write_all(&out)?;
+        } else {
+            fs::write(&path, &out)
+                .with_context(|| format!("Failed to write repaired file: {}", path))?;
+        }
+        (Some(recovered), Some(path))
+    };
+    #[cfg(not(feature = "fec-rs"))]
+    let (recovered, output): (Option<RecoveredTimeline>, Option<String>) = {
+        let _ = repair_out;
+        writeln!(
+            human,
+            "RS repair requested, but durapack-core was built without `fec-rs` feature."
+        )?;
+        (None, None)
+    };
+
+    let repaired_all: BTreeSet<u64> = recovered
+        .as_ref()
+        .map(|r| r.reconstructed.iter().copied().collect())
+        .unwrap_or_default();
+    let mut block_reports = Vec::new();
+    for b in blocks {
+        let missing: Vec<u64> = b
+            .block
+            .data_ids()
+            .filter(|id| !present.contains(id))
+            .collect();
+        let parity_present = b
+            .block
+            .parity_frame_ids
+            .iter()
+            .filter(|id| present.contains(id))
+            .count();
+        let repaired: Vec<u64> = missing
+            .iter()
+            .copied()
+            .filter(|id| repaired_all.contains(id))
+            .collect();
+        let outcome = if missing.is_empty() {
+            "intact"
+        } else if b.incompatible() {
+            "incompatible"
+        } else if b.code != Some(FecCode::ReedSolomon) || recovered.is_none() {
+            "unsupported"
+        } else if repaired.len() == missing.len() {
+            "repaired"
+        } else if !repaired.is_empty() {
+            "partial"
+        } else if missing.len() > parity_present {
+            "unrepairable"
+        } else {
+            "failed"
+        };
+        let label = match b.code {
+            Some(code) if b.whole_frames => format!("{} whole-frame", code_label(code)),
+            Some(code) => code_label(code).to_string(),
+            None => "?".to_string(),
+        };
+        let detail = match outcome {
+            "intact" => format!("{} no data missing", "✓".green()),
+            "repaired" => format!(
+                "{} rebuilt {}",
+                "✓".green(),
+                repaired
+                    .iter()
+                    .map(u64::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            "partial" => format!(
+                "{} rebuilt {} of {} missing",
+                "!".yellow(),
+                repaired.len(),
+                missing.len()
+            ),
+            "unrepairable" => format!(
+                "{} {} missing, only {} parity frame(s) present",
+                "✗".red(),
+                missing.len(),
+                parity_present
+            ),
+            "failed" => format!("{} decoding or header checks failed", "✗".red()),
+            "incompatible" => format!(
+                "{} {} missing, but the parity uses RS shard layout {} and this build reads layout {}; regenerate it with `durapack fec`",
+                "✗".red(),
+                missing.len(),
+                b.shard_layout,
+                RS_SHARD_LAYOUT
+            ),
+            _ => format!(
+                "{} {} missing, not an RS block",
+                "!".yellow(),
+                missing.len()
+            ),
+        };
+        writeln!(
+            human,
+            "{} block {}..={} ({}+{}): {}",
+            label,
+            b.block.block_start_id,
+            b.block.block_start_id + b.block.data as u64 - 1,
+            b.block.data,
+            b.block.parity,
+            detail
+        )?;
+        block_reports.push(RepairBlockJson {
+            code: b.code.map_or("unknown", FecCode::name).to_string(),
+            whole_frames: b.whole_frames,
+            block_start_id: b.block.block_start_id,
+            data: b.block.data,
+            parity: b.block.parity,
+            parity_present,
+            missing,
+            repaired,
+            outcome,
+        });
+    }
+
+    let frames_after = recovered.as_ref().map_or(located.len(), |r| {
+        r.timeline.frames.len() + r.timeline.orphans.len() + r.timeline.conflicting().len()
+    });
+    let gaps_after = recovered
+        .as_ref()
+        .map_or(gaps_before, |r| r.timeline.gaps.len());
+    if let Some(path) = &output {
+        writeln!(
+            human,
+            "Rebuilt {} frame(s); gaps {} -> {}",
+            repaired_all.len(),
+            gaps_before,
+            gaps_after
+        )?;
+        if path != "-" {
+            writeln!(human, "Repaired file written to: {}", path)?;
+        }
+    }
+
+    if let Some(path) = repair_report_path {
+        let json = RepairReportJson {
+            input: input.to_string(),
+            output,
+            frames_before: located.len(),
+            frames_after,
+            gaps_before,
+            gaps_after,
+            repaired_frames: repaired_all.into_iter().collect(),
+            unverified_frames: recovered.map(|r| r.unverified).unwrap_or_default(),
+            blocks: block_reports,
+        };
+        let text = serde_json::to_string_pretty(&json)
+            .with_context(|| "Failed to serialize repair report")?;
+        if path == "-" {
+            println!("{}", text);
+        } else {
+            fs::write(path, text)
+                .with_context(|| format!("Failed to write repair report: {}", path))?;
+            info!("Repair report written to: {}", path);
+        }
+    }
     Ok(())
 }
+
+#[derive(Serialize)]
+struct FindingJson {
+    kind: &'static str,
+    cause: &'static str,
+    severity: &'static str,
+    frame_ids: Vec<u64>,
+    offsets: Vec<usize>,
+    detail: String,
+}
+
+#[derive(Serialize)]
+struct TamperReportJson {
+    input_bytes: usize,
+    /// Highest severity found, or null for a clean stream
+    max_severity: Option<&'static str>,
+    deliberate: usize,
+    accidental: usize,
+    ambiguous: usize,
+    /// Whether signed frames were checked against DURAPACK_VERIFY_PUBKEY
+    signatures_checked: bool,
+    findings: Vec<FindingJson>,
+}
+
+fn severity_name(s: Severity) -> &'static str {
+    match s {
+        Severity::Info => "info",
+        Severity::Low => "low",
+        Severity::Medium => "medium",
+        Severity::High => "high",
+        Severity::Critical => "critical",
+    }
+}
+
+/// Classify findings as deliberate or accidental and write them as JSON
+fn write_tamper_report(
+    located: &[LocatedFrame],
+    damaged: &[DamagedFrame],
+    data_len: usize,
+    path: &str,
+) -> Result<()> {
+    #[cfg(feature = "ed25519-signatures")]
+    let key = std::env::var("DURAPACK_VERIFY_PUBKEY")
+        .ok()
+        .and_then(|p| fs::read(p).ok())
+        .and_then(|b| <[u8; 32]>::try_from(b.as_slice()).ok())
+        .and_then(|b| VerifyingKey::from_bytes(&b).ok());
+    #[cfg(feature = "ed25519-signatures")]
+    let check = key.map(|vk| move |f: &Frame| signature_valid(&vk, f));
+    #[cfg(feature = "ed25519-signatures")]
+    let check_ref = check.as_ref().map(|c| c as &dyn Fn(&Frame) -> bool);
+    #[cfg(not(feature = "ed25519-signatures"))]
+    let check_ref: Option<&dyn Fn(&Frame) -> bool> = None;
+
+    let report = tamper::classify_scanned(located, damaged, data_len, check_ref);
+    let count = |c: Cause| report.findings.iter().filter(|f| f.cause == c).count();
+    let json = TamperReportJson {
+        input_bytes: data_len,
+        max_severity: report.max_severity().map(severity_name),
+        deliberate: count(Cause::Deliberate),
+        accidental: count(Cause::Accidental),
+        ambiguous: count(Cause::Ambiguous),
+        signatures_checked: check_ref.is_some(),
+        findings: report
+            .findings
+            .iter()
+            .map(|f| FindingJson {
+                kind: match f.kind {
+                    FindingKind::TrailerFailure => "trailer_failure",
+                    FindingKind::Truncation => "truncation",
+                    FindingKind::MissingFrames => "missing_frames",
+                    FindingKind::BrokenChain => "broken_chain",
+                    FindingKind::ReSigned => "re_signed",
+                    FindingKind::Renumbered => "renumbered",
+                    FindingKind::ConflictingDuplicate => "conflicting_duplicate",
+                    FindingKind::Fork => "fork",
+                    FindingKind::StrippedSignature => "stripped_signature",
+                    FindingKind::FlagInconsistency => "flag_inconsistency",
+                },
+                cause: match f.cause {
+                    Cause::Accidental => "accidental",
+                    Cause::Deliberate => "deliberate",
+                    Cause::Ambiguous => "ambiguous",
+                },
+                severity: severity_name(f.severity),
+                frame_ids: f.frame_ids.clone(),
+                offsets: f.offsets.clone(),
+                detail: f.detail.clone(),
+            })
+            .collect(),
+    };
+    let text =
+        serde_json::to_string_pretty(&json).with_context(|| "Failed to serialize tamper report")?;
+    if path == "-" {
+        println!("{}", text);
+    } else {
+        fs::write(path, text)
+            .with_context(|| format!("Failed to write tamper report: {}", path))?;
+        info!("Tamper report written to: {}", path);
+    }
+    Ok(())
+}
+
+/// Check a frame's Ed25519 signature over marker, header fields and payload
+#[cfg(feature = "ed25519-signatures")]
+fn signature_valid(vk: &VerifyingKey, f: &Frame) -> bool {
+    // trailer: [32 hash][64 sig]
+    let Some(sig_bytes) = f
+        .trailer
+        .as_ref()
+        .filter(|tr| tr.len() == 96)
+        .and_then(|tr| <[u8; 64]>::try_from(&tr[32..96]).ok())
+    else {
+        return false;
+    };
+    let sig = Signature::from_bytes(&sig_bytes);
+    let mut msg = Vec::with_capacity(durapack_core::constants::MIN_HEADER_SIZE + f.payload.len());
+    msg.extend_from_slice(durapack_core::constants::FRAME_MARKER);
+    msg.push(f.header.version);
+    msg.extend_from_slice(&f.header.frame_id.to_be_bytes());
+    msg.extend_from_slice(&f.header.prev_hash);
+    msg.extend_from_slice(&f.header.payload_len.to_be_bytes());
+    msg.push(f.header.flags.as_u8());
+    msg.extend_from_slice(&f.payload);
+    vk.verify(&msg, &sig).is_ok()
+}
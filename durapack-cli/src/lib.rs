@@ -14,6 +14,15 @@ pub enum ChunkStrategy {
     Aggregate,
 }
 
+/// FEC scheme used by pack for parity frames
+#[derive(Copy, Clone, Debug, clap::ValueEnum)]
+pub enum FecScheme {
+    /// Reed–Solomon: exactly `parity` repair frames per block (feature `fec-rs`)
+    Rs,
+    /// RaptorQ fountain code (RFC 6330): any number of repair frames (feature `fec-raptorq`)
+    Raptorq,
+}
+
 // Re-export commonly used items
 pub use crate::commands::pack;
 
@@ -12,6 +12,14 @@ pub enum ChunkStrategy {
     Aggregate,
 }
 
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub enum FecScheme {
+    /// Reed–Solomon: exactly `parity` repair frames per block (feature `fec-rs`)
+    Rs,
+    /// RaptorQ fountain code (RFC 6330): any number of repair frames (feature `fec-raptorq`)
+    Raptorq,
+}
+
 #[derive(Parser)]
 #[command(name = "durapack")]
 #[command(about = "Durapack - Self-locating framing format for hostile media", long_about = None)]
@@ -65,17 +73,32 @@ enum Commands {
         #[arg(long, default_value_t = false)]
         progress: bool,
 
-        /// FEC: number of data frames per block (Reed–Solomon). Requires durapack-core with `fec-rs` feature.
-        #[arg(long, requires = "fec_rs_parity")]
+        /// FEC: number of data frames per block. Requires durapack-core with the scheme's feature.
+        #[arg(long, visible_alias = "fec-data", requires = "fec_rs_parity")]
         fec_rs_data: Option<usize>,
 
-        /// FEC: number of parity frames per block (Reed–Solomon). Requires durapack-core with `fec-rs` feature.
-        #[arg(long, requires = "fec_rs_data")]
+        /// FEC: number of parity frames per block. Requires durapack-core with the scheme's feature.
+        #[arg(long, visible_alias = "fec-parity", requires = "fec_rs_data")]
         fec_rs_parity: Option<usize>,
 
-        /// Path to write FEC sidecar index (JSON). Defaults to <output>.fec.json when FEC is enabled.
+        /// FEC scheme for parity frames (`raptorq` requires the `fec-raptorq` feature)
+        #[arg(long, value_enum, default_value_t = FecScheme::Rs)]
+        fec: FecScheme,
+
+        /// Also write a FEC sidecar index (JSON) here. Without this flag no sidecar is
+        /// written (earlier versions defaulted to <output>.fec.json); parity frames
+        /// describe themselves in-band
         #[arg(long)]
         fec_index_out: Option<String>,
+
+        /// Protect each header with a CRC32C (header version 2) so scanners can reject corrupted lengths
+        #[arg(long, default_value_t = false)]
+        header_crc: bool,
+
+        /// RS only: encode complete frames (header through trailer) into the shards, so
+        /// repair reproduces the original bytes, signatures included
+        #[arg(long, default_value_t = false, requires = "fec_rs_data")]
+        fec_whole_frames: bool,
     },
 
     /// Post-facto parity injection: compute RS parity over existing file and append parity frames
@@ -103,6 +126,10 @@ enum Commands {
         /// Dry-run (compute but do not write frames); still writes sidecar if requested
         #[arg(long, default_value_t = false)]
         dry_run: bool,
+        /// Encode complete frames (header through trailer) into the shards, so repair
+        /// reproduces the original bytes, signatures included
+        #[arg(long, default_value_t = false)]
+        whole_frames: bool,
     },
 
     /// Scan damaged file and recover frames
@@ -130,6 +157,18 @@ enum Commands {
         /// Minimum confidence [0.0-1.0] for reporting/carving frames
         #[arg(long)]
         min_confidence: Option<f32>,
+
+        /// Search for frames at any bit offset (raw serial/SDR captures)
+        #[arg(long)]
+        bit_align: bool,
+
+        /// With --bit-align, also search the bit-inverted stream
+        #[arg(long, requires = "bit_align")]
+        inverted: bool,
+
+        /// Map every input byte (frames, damage, padding, unexplained) and write it as JSON ("-" for stdout, which suppresses the human report)
+        #[arg(long)]
+        damage_map: Option<String>,
     },
 
     /// Verify frame integrity and back-links
@@ -146,9 +185,25 @@ enum Commands {
         #[arg(long)]
         fec_index: Option<String>,
 
-        /// Attempt Reed–Solomon repair using FEC sidecar (report-only)
+        /// Rebuild missing frames from Reed–Solomon parity (in-band, or the --fec-index sidecar) and write a repaired file
         #[arg(long, default_value_t = false)]
         rs_repair: bool,
+
+        /// Path of the repaired file written by --rs-repair ("-" for stdout, which suppresses the human report). Defaults to <input>.repaired.durp.
+        #[arg(long, requires = "rs_repair")]
+        repair_out: Option<String>,
+
+        /// Write per-block --rs-repair outcomes as JSON ("-" for stdout, which suppresses the human report; not with --repair-out -)
+        #[arg(long, requires = "rs_repair")]
+        repair_report: Option<String>,
+
+        /// Use and refresh the `<input>.idx` timeline index to skip rescanning
+        #[arg(long, default_value_t = false)]
+        index: bool,
+
+        /// Write a JSON report classifying findings as tampering or accidental damage ("-" for stdout, which suppresses the human report; scans the whole file; --index is not used)
+        #[arg(long)]
+        tamper_report: Option<String>,
     },
 
     /// Reconstruct timeline from frames
@@ -169,6 +224,10 @@ enum Commands {
         #[arg(long, default_value_t = false)]
         dot: bool,
 
+        /// Emit a self-contained HTML report (always includes the analysis) instead of JSON
+        #[arg(long, default_value_t = false, conflicts_with = "dot")]
+        html: bool,
+
         /// Include detailed analysis (reasons/conflicts/recipes) in JSON or DOT
         #[arg(long, default_value_t = false)]
         analyze: bool,
@@ -176,6 +235,32 @@ enum Commands {
         /// Optional FEC sidecar index (JSON) to annotate parity frames in stats
         #[arg(long)]
         fec_index: Option<String>,
+
+        /// Use and refresh the `<input>.idx` timeline index to skip rescanning
+        #[arg(long, default_value_t = false)]
+        index: bool,
+    },
+
+    /// Merge captures of the same stream, keeping the best copy of each frame
+    ///
+    /// The content found in the most captures wins; repeats within one capture
+    /// count once.
+    ///
+    /// Frames damaged in every capture are rebuilt by byte-wise voting only when
+    /// the stream was packed with --header-crc; damaged frames without a header
+    /// CRC cannot be attributed to a frame ID and stay missing.
+    Merge {
+        /// Input files (at least two; "-" for stdin once)
+        #[arg(short, long, required = true, num_args = 1..)]
+        input: Vec<String>,
+
+        /// Output file for the merged stream ("-" for stdout)
+        #[arg(short, long)]
+        output: String,
+
+        /// Write a JSON report with per-frame provenance ("-" for stdout, which suppresses the summary)
+        #[arg(long)]
+        report: Option<String>,
     },
 
     /// Export tool: strip Ed25519 signatures and downgrade combined trailers to BLAKE3-only
@@ -200,7 +285,7 @@ fn main() -> Result<()> {
     };
 
     tracing_subscriber::registry()
-        .with(fmt::layer())
+        .with(fmt::layer().with_writer(std::io::stderr))
         .with(filter)
         .init();
 
@@ -219,6 +304,9 @@ fn main() -> Result<()> {
             fec_rs_data,
             fec_rs_parity,
             fec_index_out,
+            header_crc,
+            fec,
+            fec_whole_frames,
         } => commands::pack::execute_ext(
             &input,
             &output,
@@ -231,6 +319,9 @@ fn main() -> Result<()> {
             fec_rs_data.zip(fec_rs_parity),
             fec_index_out.as_deref(),
             sign_ed25519.as_deref(),
+            header_crc,
+            fec,
+            fec_whole_frames,
         ),
 
         Commands::Fec {
@@ -240,6 +331,7 @@ fn main() -> Result<()> {
             k_parity,
             fec_index_out,
             dry_run,
+            whole_frames,
         } => commands::fec::inject_parity(
             &input,
             output.as_deref(),
@@ -247,6 +339,7 @@ fn main() -> Result<()> {
             k_parity,
             fec_index_out.as_deref(),
             dry_run,
+            whole_frames,
         ),
 
         Commands::Scan {
@@ -256,6 +349,9 @@ fn main() -> Result<()> {
             jsonl,
             carve_payloads,
             min_confidence,
+            bit_align,
+            inverted,
+            damage_map,
         } => commands::scan::execute_ext(
             &input,
             output.as_deref(),
@@ -263,6 +359,9 @@ fn main() -> Result<()> {
             jsonl,
             carve_payloads.as_deref(),
             min_confidence,
+            bit_align,
+            inverted,
+            damage_map.as_deref(),
         ),
 
         Commands::Verify {
@@ -270,15 +369,36 @@ fn main() -> Result<()> {
             report_gaps,
             fec_index,
             rs_repair,
-        } => commands::verify::execute_ext(&input, report_gaps, fec_index.as_deref(), rs_repair),
+            index,
+            tamper_report,
+            repair_out,
+            repair_report,
+        } => {
+            let status = commands::verify::execute_ext(
+                &input,
+                report_gaps,
+                fec_index.as_deref(),
+                rs_repair,
+                index,
+                tamper_report.as_deref(),
+                repair_out.as_deref(),
+                repair_report.as_deref(),
+            )?;
+            if status != commands::verify::VerifyStatus::Complete {
+                std::process::exit(status.exit_code());
+            }
+            Ok(())
+        }
 
         Commands::Timeline {
             input,
             output,
             include_orphans,
             dot,
+            html,
             analyze,
             fec_index,
+            index,
         } => commands::timeline::execute_ext(
             &input,
             &output,
@@ -286,8 +406,16 @@ fn main() -> Result<()> {
             dot,
             analyze,
             fec_index.as_deref(),
+            index,
+            html,
         ),
 
+        Commands::Merge {
+            input,
+            output,
+            report,
+        } => commands::merge::execute(&input, &output, report.as_deref()),
+
         Commands::Export { input, output } => {
             commands::verify::export_strip_signatures(&input, &output)
         }
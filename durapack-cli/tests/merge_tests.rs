@@ -0,0 +1,175 @@
+use bytes::Bytes;
+use durapack_cli::commands::merge;
+use durapack_core::constants::MAX_HEADER_SIZE;
+use durapack_core::decoder::decode_frame_from_bytes;
+use durapack_core::encoder::FrameBuilder;
+use durapack_core::scanner::scan_stream;
+use std::fs;
+use tempfile::tempdir;
+
+/// Helper: create a header-CRC stream and the offset of each frame
+fn create_stream(count: u64) -> (Vec<u8>, Vec<usize>) {
+    let mut result = Vec::new();
+    let mut offsets = Vec::new();
+    let mut prev = [0u8; 32];
+
+    for id in 1..=count {
+        let mut builder = FrameBuilder::new(id)
+            .payload(Bytes::from(format!("Frame {}", id)))
+            .prev_hash(prev)
+            .with_blake3()
+            .with_header_crc();
+        if id == 1 {
+            builder = builder.mark_first();
+        }
+        let frame_bytes = builder.build().unwrap();
+        prev = decode_frame_from_bytes(&frame_bytes)
+            .unwrap()
+            .compute_hash();
+        offsets.push(result.len());
+        result.extend_from_slice(&frame_bytes);
+    }
+
+    (result, offsets)
+}
+
+#[test]
+fn test_merge_two_damaged_captures() {
+    let td = tempdir().unwrap();
+    let (clean, offsets) = create_stream(6);
+
+    // Each station lost a different frame
+    let mut a = clean.clone();
+    a[offsets[1] + MAX_HEADER_SIZE] ^= 0xFF;
+    let mut b = clean.clone();
+    b[offsets[4] + MAX_HEADER_SIZE] ^= 0xFF;
+
+    let a_path = td.path().join("station_a.durp");
+    let b_path = td.path().join("station_b.durp");
+    let out_path = td.path().join("merged.durp");
+    let report_path = td.path().join("merge.json");
+    fs::write(&a_path, &a).unwrap();
+    fs::write(&b_path, &b).unwrap();
+
+    let inputs = vec![
+        a_path.to_str().unwrap().to_string(),
+        b_path.to_str().unwrap().to_string(),
+    ];
+    merge::execute(
+        &inputs,
+        out_path.to_str().unwrap(),
+        Some(report_path.to_str().unwrap()),
+    )
+    .unwrap();
+
+    assert_eq!(fs::read(&out_path).unwrap(), clean);
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    let frames = report["frames"].as_array().unwrap();
+    assert_eq!(frames.len(), 6);
+    assert_eq!(frames[1]["frame_id"], 2);
+    assert_eq!(frames[1]["origin"], "copy");
+    assert_eq!(frames[1]["file"], inputs[1]);
+    assert_eq!(frames[1]["copies"].as_array().unwrap().len(), 2);
+    assert!(report["gaps"].as_array().unwrap().is_empty());
+    assert!(report["unrecovered"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_merge_votes_frame_damaged_in_every_capture() {
+    let td = tempdir().unwrap();
+    let (clean, offsets) = create_stream(3);
+
+    let mut inputs = Vec::new();
+    for station in 0..3 {
+        let mut copy = clean.clone();
+        copy[offsets[1] + MAX_HEADER_SIZE + station] ^= 0x55;
+        let path = td.path().join(format!("station_{}.durp", station));
+        fs::write(&path, &copy).unwrap();
+        inputs.push(path.to_str().unwrap().to_string());
+    }
+    let out_path = td.path().join("merged.durp");
+    let report_path = td.path().join("merge.json");
+
+    merge::execute(
+        &inputs,
+        out_path.to_str().unwrap(),
+        Some(report_path.to_str().unwrap()),
+    )
+    .unwrap();
+
+    let merged = fs::read(&out_path).unwrap();
+    assert_eq!(scan_stream(&merged).len(), 3);
+    assert_eq!(merged, clean);
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(report["frames"][1]["origin"], "voted");
+    assert_eq!(report["frames"][1]["copies"].as_array().unwrap().len(), 3);
+}
+
+#[test]
+fn test_merge_requires_two_inputs() {
+    let td = tempdir().unwrap();
+    let (clean, _) = create_stream(2);
+    let path = td.path().join("only.durp");
+    fs::write(&path, clean).unwrap();
+
+    let out_path = td.path().join("merged.durp");
+    let result = merge::execute(
+        &[path.to_str().unwrap().to_string()],
+        out_path.to_str().unwrap(),
+        None,
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_merge_rejects_stream_and_report_both_on_stdout() {
+    let td = tempdir().unwrap();
+    let (clean, _) = create_stream(2);
+    let a = td.path().join("a.durp");
+    let b = td.path().join("b.durp");
+    fs::write(&a, &clean).unwrap();
+    fs::write(&b, &clean).unwrap();
+
+    let err = merge::execute(
+        &[
+            a.to_str().unwrap().to_string(),
+            b.to_str().unwrap().to_string(),
+        ],
+        "-",
+        Some("-"),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("stdout"));
+}
+
+#[test]
+fn test_merge_report_on_stdout_is_plain_json() {
+    let td = tempdir().unwrap();
+    let (clean, _) = create_stream(2);
+    let a = td.path().join("a.durp");
+    let b = td.path().join("b.durp");
+    fs::write(&a, &clean).unwrap();
+    fs::write(&b, &clean).unwrap();
+    let out_path = td.path().join("merged.durp");
+
+    let out = std::process::Command::new(env!("CARGO_BIN_EXE_durapack"))
+        .args([
+            "merge",
+            "-i",
+            a.to_str().unwrap(),
+            "-i",
+            b.to_str().unwrap(),
+        ])
+        .args(["-o", out_path.to_str().unwrap(), "--report", "-"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    // The summary stays off stdout
+    let report: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(report["frames"].as_array().unwrap().len(), 2);
+    assert_eq!(fs::read(&out_path).unwrap(), clean);
+}
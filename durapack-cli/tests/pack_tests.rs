@@ -1,7 +1,7 @@
 use std::fs;
 use tempfile::tempdir;
 
-use durapack_cli::{commands::pack, ChunkStrategy};
+use durapack_cli::{commands::pack, ChunkStrategy, FecScheme};
 use durapack_core::constants::TrailerType;
 use durapack_core::scanner::scan_stream;
 
@@ -34,12 +34,18 @@ fn pack_json_array_crc32c_basic() {
         /*fec_rs*/ None,
         /*fec_index_out*/ None,
         /*sign_key_path*/ None,
+        /*header_crc*/ false,
+        FecScheme::Rs,
+        /*fec_whole_frames*/ false,
     )
     .unwrap();
 
     let bytes = fs::read(&out_path).unwrap();
     let frames = scan_stream(&bytes);
     assert_eq!(frames.len(), 3);
+    // Only the final frame closes the sequence
+    let last_flags: Vec<bool> = frames.iter().map(|lf| lf.frame.header.is_last()).collect();
+    assert_eq!(last_flags, vec![false, false, true]);
     for lf in frames {
         assert_eq!(lf.frame.header.flags.trailer_type(), TrailerType::Crc32c);
     }
@@ -66,6 +72,9 @@ fn pack_jsonl_blake3_with_progress() {
         /*fec_rs*/ None,
         /*fec_index_out*/ None,
         /*sign_key_path*/ None,
+        /*header_crc*/ false,
+        FecScheme::Rs,
+        /*fec_whole_frames*/ false,
     )
     .unwrap();
 
@@ -102,6 +111,9 @@ fn pack_with_sign_flag_and_exportable_trailer() {
         /*fec_rs*/ None,
         /*fec_index_out*/ None,
         /*sign_key_path*/ Some(key_path.to_str().unwrap()),
+        /*header_crc*/ false,
+        FecScheme::Rs,
+        /*fec_whole_frames*/ false,
     )
     .unwrap();
 
@@ -139,6 +151,9 @@ fn pack_writes_empty_fec_sidecar_without_feature() {
         /*fec_rs*/ Some((2, 1)),
         /*fec_index_out*/ Some(sidecar.to_str().unwrap()),
         /*sign_key_path*/ None,
+        /*header_crc*/ false,
+        FecScheme::Rs,
+        /*fec_whole_frames*/ false,
     )
     .unwrap();
 
@@ -147,3 +162,185 @@ fn pack_writes_empty_fec_sidecar_without_feature() {
     let v: serde_json::Value = serde_json::from_str(&sc).unwrap();
     assert!(v.is_array());
 }
+
+#[test]
+fn pack_with_header_crc_emits_v2_headers() {
+    let td = tempdir().unwrap();
+    let in_path = td.path().join("in.json");
+    let out_path = td.path().join("out_hcrc.durp");
+
+    write_file(&in_path, "[{\"a\":1},{\"b\":2}]");
+
+    pack::execute_ext(
+        in_path.to_str().unwrap(),
+        out_path.to_str().unwrap(),
+        /*use_blake3*/ false,
+        /*start_id*/ 1,
+        /*jsonl*/ false,
+        ChunkStrategy::Aggregate,
+        /*rate_limit*/ None,
+        /*progress*/ false,
+        /*fec_rs*/ None,
+        /*fec_index_out*/ None,
+        /*sign_key_path*/ None,
+        /*header_crc*/ true,
+        FecScheme::Rs,
+        /*fec_whole_frames*/ false,
+    )
+    .unwrap();
+
+    let bytes = fs::read(&out_path).unwrap();
+    let frames = scan_stream(&bytes);
+    assert_eq!(frames.len(), 2);
+    for lf in frames {
+        assert!(lf.frame.header.has_header_crc());
+    }
+}
+
+#[cfg(not(feature = "fec-raptorq"))]
+#[test]
+fn pack_raptorq_requires_feature() {
+    let td = tempdir().unwrap();
+    let in_path = td.path().join("in.json");
+    let out_path = td.path().join("out_rq.durp");
+
+    write_file(&in_path, "[{\"a\":1},{\"b\":2}]");
+
+    let err = pack::execute_ext(
+        in_path.to_str().unwrap(),
+        out_path.to_str().unwrap(),
+        /*use_blake3*/ false,
+        /*start_id*/ 1,
+        /*jsonl*/ false,
+        ChunkStrategy::Aggregate,
+        /*rate_limit*/ None,
+        /*progress*/ false,
+        /*fec_rs*/ Some((2, 1)),
+        /*fec_index_out*/ None,
+        /*sign_key_path*/ None,
+        /*header_crc*/ false,
+        FecScheme::Raptorq,
+        /*fec_whole_frames*/ false,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("fec-raptorq"));
+}
+
+#[cfg(feature = "fec-raptorq")]
+#[test]
+fn pack_raptorq_parity_rebuilds_dropped_frames() {
+    use durapack_core::fec::{frame_shard, FecBlock, RaptorQDecoder, RedundancyDecoder};
+    use durapack_core::parity::{parity_descriptor, parity_shard, FecCode};
+
+    let td = tempdir().unwrap();
+    let in_path = td.path().join("in.json");
+    let out_path = td.path().join("out_rq.durp");
+    let sidecar = td.path().join("out_rq.durp.fec.json");
+
+    let input: Vec<String> = (0..8).map(|i| format!("{{\"n\":{}}}", i)).collect();
+    write_file(&in_path, &format!("[{}]", input.join(",")));
+
+    pack::execute_ext(
+        in_path.to_str().unwrap(),
+        out_path.to_str().unwrap(),
+        /*use_blake3*/ false,
+        /*start_id*/ 1,
+        /*jsonl*/ false,
+        ChunkStrategy::Aggregate,
+        /*rate_limit*/ None,
+        /*progress*/ false,
+        /*fec_rs*/ Some((4, 3)),
+        /*fec_index_out*/ Some(sidecar.to_str().unwrap()),
+        /*sign_key_path*/ None,
+        /*header_crc*/ false,
+        FecScheme::Raptorq,
+        /*fec_whole_frames*/ false,
+    )
+    .unwrap();
+
+    let sc: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&sidecar).unwrap()).unwrap();
+    let entries = sc.as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["scheme"], "raptorq");
+    assert_eq!(entries[1]["block_start_id"], 8);
+
+    let frames = scan_stream(&fs::read(&out_path).unwrap());
+    assert_eq!(frames.len(), 14);
+    assert!(frames.last().unwrap().frame.header.flags.is_last());
+
+    // Second block: data frames 8..=11, parity frames 12..=14; lose frames 9, 11 and 13
+    let by_id = |id: u64| frames.iter().find(|lf| lf.frame.header.frame_id == id);
+    let parity = by_id(12).unwrap();
+    let desc = parity_descriptor(&parity.frame).unwrap();
+    assert_eq!(desc.code, FecCode::RaptorQ);
+    assert_eq!((desc.block_start_id, desc.data, desc.parity), (8, 4, 3));
+    let shard_len = parity_shard(&parity.frame).len();
+    let avail: Vec<FecBlock> = [(8u64, 0u32), (10, 2), (12, 4), (14, 6)]
+        .iter()
+        .map(|&(id, index)| {
+            let frame = &by_id(id).unwrap().frame;
+            let mut data = match index {
+                0..4 => frame_shard(frame),
+                _ => parity_shard(frame).to_vec(),
+            };
+            data.resize(shard_len, 0);
+            FecBlock {
+                block_id: 8,
+                index,
+                total_blocks: 7,
+                data,
+            }
+        })
+        .collect();
+    let rebuilt = RaptorQDecoder.decode_batch(&avail, 4).unwrap();
+    for (i, id) in [(1, 9), (3, 11)] {
+        let lost = &by_id(id).unwrap().frame;
+        assert_eq!(rebuilt[i].header, lost.header);
+        assert_eq!(rebuilt[i].payload, lost.payload);
+    }
+}
+
+#[cfg(feature = "fec-rs")]
+#[test]
+fn pack_rs_parity_frames_describe_their_block() {
+    use durapack_core::parity::{in_band_blocks, FecCode};
+
+    let td = tempdir().unwrap();
+    let in_path = td.path().join("in.json");
+    let out_path = td.path().join("out_inband.durp");
+
+    let input: Vec<String> = (0..6).map(|i| format!("{{\"n\":{}}}", i)).collect();
+    write_file(&in_path, &format!("[{}]", input.join(",")));
+
+    pack::execute_ext(
+        in_path.to_str().unwrap(),
+        out_path.to_str().unwrap(),
+        /*use_blake3*/ false,
+        /*start_id*/ 1,
+        /*jsonl*/ false,
+        ChunkStrategy::Aggregate,
+        /*rate_limit*/ None,
+        /*progress*/ false,
+        /*fec_rs*/ Some((3, 2)),
+        /*fec_index_out*/ None,
+        /*sign_key_path*/ None,
+        /*header_crc*/ false,
+        FecScheme::Rs,
+        /*fec_whole_frames*/ false,
+    )
+    .unwrap();
+
+    // No sidecar unless asked for
+    assert!(!td.path().join("out_inband.durp.fec.json").exists());
+
+    let frames = scan_stream(&fs::read(&out_path).unwrap());
+    assert_eq!(frames.len(), 10);
+    let blocks = in_band_blocks(frames.iter().map(|lf| &lf.frame));
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[0].code, FecCode::ReedSolomon);
+    assert_eq!(blocks[0].block.block_start_id, 1);
+    assert_eq!(blocks[0].block.parity_frame_ids, vec![4, 5]);
+    assert_eq!(blocks[1].block.block_start_id, 6);
+    assert_eq!(blocks[1].block.parity_frame_ids, vec![9, 10]);
+}
@@ -170,6 +170,9 @@ fn test_scan_jsonl_mode() {
         true, // jsonl
         None,
         None,
+        false, // bit_align
+        false, // inverted
+        None,  // damage_map
     )
     .unwrap();
 
@@ -207,6 +210,9 @@ fn test_scan_with_gaps() {
         true, // jsonl
         None,
         None,
+        false, // bit_align
+        false, // inverted
+        None,  // damage_map
     )
     .unwrap();
 
@@ -258,6 +264,9 @@ fn test_scan_min_confidence_filtering() {
         true, // jsonl
         None,
         Some(0.9), // min_confidence
+        false,     // bit_align
+        false,     // inverted
+        None,      // damage_map
     )
     .unwrap();
 
@@ -300,6 +309,9 @@ fn test_scan_min_confidence_zero() {
         false, // not jsonl
         None,
         Some(0.0), // min_confidence
+        false,     // bit_align
+        false,     // inverted
+        None,      // damage_map
     )
     .unwrap();
 
@@ -335,6 +347,9 @@ fn test_scan_carve_payloads() {
         true, // jsonl
         Some(&carve_pattern),
         None,
+        false, // bit_align
+        false, // inverted
+        None,  // damage_map
     )
     .unwrap();
 
@@ -378,6 +393,9 @@ fn test_scan_carve_with_min_confidence() {
         true, // jsonl
         Some(&carve_pattern),
         Some(0.95), // min_confidence
+        false,      // bit_align
+        false,      // inverted
+        None,       // damage_map
     )
     .unwrap();
 
@@ -407,6 +425,9 @@ fn test_scan_output_to_stdout() {
         true, // jsonl
         None,
         None,
+        false, // bit_align
+        false, // inverted
+        None,  // damage_map
     )
     .unwrap();
 }
@@ -488,6 +509,9 @@ fn test_scan_jsonl_stats_record() {
         true, // jsonl
         None,
         None,
+        false, // bit_align
+        false, // inverted
+        None,  // damage_map
     )
     .unwrap();
 
@@ -569,6 +593,9 @@ fn test_scan_non_jsonl_carve_path() {
         false, // not jsonl
         Some(&carve_pattern),
         None,
+        false, // bit_align
+        false, // inverted
+        None,  // damage_map
     )
     .unwrap();
 
@@ -598,6 +625,9 @@ fn test_jsonl_format_validity() {
         true, // jsonl - this uses write_jsonl internally
         None,
         None,
+        false, // bit_align
+        false, // inverted
+        None,  // damage_map
     )
     .unwrap();
 
@@ -640,6 +670,9 @@ fn test_scan_multiple_gaps() {
         true, // jsonl
         None,
         None,
+        false, // bit_align
+        false, // inverted
+        None,  // damage_map
     )
     .unwrap();
 
@@ -658,3 +691,202 @@ fn test_scan_multiple_gaps() {
 
     assert!(gap_count >= 2); // Should detect multiple gaps
 }
+
+#[test]
+fn test_scan_bit_aligned_capture() {
+    let td = tempdir().unwrap();
+    let input_path = td.path().join("capture.bin");
+    let output_path = td.path().join("capture.jsonl");
+
+    // Shift the whole stream by 2 bits, as after a demodulator slip
+    let frames = create_test_frames(3, true);
+    let mut shifted = vec![0b1100_0000 | (frames[0] >> 2)];
+    for w in frames.windows(2) {
+        shifted.push((w[0] << 6) | (w[1] >> 2));
+    }
+    shifted.push(frames[frames.len() - 1] << 6);
+    fs::write(&input_path, shifted).unwrap();
+
+    scan::execute_ext(
+        input_path.to_str().unwrap(),
+        Some(output_path.to_str().unwrap()),
+        false,
+        true, // jsonl
+        None,
+        None,
+        true,  // bit_align
+        false, // inverted
+        None,  // damage_map
+    )
+    .unwrap();
+
+    let output_text = fs::read_to_string(&output_path).unwrap();
+    let frames: Vec<serde_json::Value> = output_text
+        .lines()
+        .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+        .filter(|r| r["type"] == "frame")
+        .collect();
+    assert_eq!(frames.len(), 3);
+    assert!(frames.iter().all(|f| f["bit_shift"] == 2));
+}
+
+#[test]
+fn test_scan_damage_map_covers_input() {
+    let td = tempdir().unwrap();
+    let input_path = td.path().join("wiped.durp");
+    let map_path = td.path().join("damage.json");
+
+    // Two frames separated by erased flash, followed by zero fill
+    let mut data = create_test_frames(1, true);
+    let first_len = data.len();
+    data.extend_from_slice(&[0xFF; 100]);
+    data.extend_from_slice(
+        &FrameBuilder::new(2)
+            .payload(Bytes::from("Test payload 1"))
+            .with_blake3()
+            .build()
+            .unwrap(),
+    );
+    data.extend_from_slice(&[0u8; 50]);
+    fs::write(&input_path, &data).unwrap();
+
+    scan::execute_ext(
+        input_path.to_str().unwrap(),
+        None,
+        false,
+        false, // jsonl
+        None,
+        None,
+        false, // bit_align
+        false, // inverted
+        Some(map_path.to_str().unwrap()),
+    )
+    .unwrap();
+
+    let map: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&map_path).unwrap()).unwrap();
+    assert_eq!(map["bytes"], data.len());
+    assert_eq!(map["unexplained_bytes"], 150);
+    let regions = map["regions"].as_array().unwrap();
+    let kinds: Vec<(&str, Option<&str>)> = regions
+        .iter()
+        .map(|r| (r["kind"].as_str().unwrap(), r["content"].as_str()))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ("frame", None),
+            ("unexplained", Some("erased")),
+            ("frame", None),
+            ("unexplained", Some("zero_fill")),
+        ]
+    );
+    assert_eq!(regions[1]["start"], first_len);
+    assert_eq!(regions[3]["end"], data.len());
+
+    // The JSON cannot share stdout with JSONL output
+    assert!(scan::execute_ext(
+        input_path.to_str().unwrap(),
+        None,
+        false,
+        true, // jsonl
+        None,
+        None,
+        false, // bit_align
+        false, // inverted
+        Some("-"),
+    )
+    .is_err());
+}
+
+#[test]
+fn test_scan_damage_map_on_stdout_is_plain_json() {
+    let td = tempdir().unwrap();
+    let input_path = td.path().join("frames.durp");
+    let data = create_test_frames(3, true);
+    fs::write(&input_path, &data).unwrap();
+
+    let out = std::process::Command::new(env!("CARGO_BIN_EXE_durapack"))
+        .args([
+            "scan",
+            "-i",
+            input_path.to_str().unwrap(),
+            "--damage-map",
+            "-",
+        ])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    // The human report stays off stdout
+    let map: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(map["bytes"], data.len());
+
+    // Frame JSON and the map cannot both go to stdout
+    assert!(scan::execute_ext(
+        input_path.to_str().unwrap(),
+        Some("-"),
+        false,
+        false, // jsonl
+        None,
+        None,
+        false, // bit_align
+        false, // inverted
+        Some("-"),
+    )
+    .is_err());
+}
+
+#[test]
+fn test_scan_jsonl_marks_in_band_parity_frames() {
+    use durapack_core::parity::{FecCode, ParityDescriptor};
+
+    let td = tempdir().unwrap();
+    let input_path = td.path().join("parity.durp");
+    let output_path = td.path().join("parity.jsonl");
+
+    // Data frames 1..=2 followed by one parity frame describing them
+    let mut data = create_test_frames(2, false);
+    let desc = ParityDescriptor {
+        code: FecCode::ReedSolomon,
+        param: 0,
+        block_start_id: 1,
+        data: 2,
+        parity: 1,
+        index: 0,
+    };
+    data.extend_from_slice(
+        &FrameBuilder::new(3)
+            .as_parity(&desc, b"shard")
+            .build()
+            .unwrap(),
+    );
+    fs::write(&input_path, data).unwrap();
+
+    scan::execute_ext(
+        input_path.to_str().unwrap(),
+        Some(output_path.to_str().unwrap()),
+        false,
+        true, // jsonl
+        None,
+        None,
+        false, // bit_align
+        false, // inverted
+        None,  // damage_map
+    )
+    .unwrap();
+
+    let records: Vec<serde_json::Value> = fs::read_to_string(&output_path)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(records[0]["parity_frames"], 1);
+    let frames: Vec<&serde_json::Value> = records.iter().filter(|r| r["type"] == "frame").collect();
+    assert_eq!(frames.len(), 3);
+    assert!(frames[0].get("parity").is_none());
+    let parity = &frames[2]["parity"];
+    assert_eq!(parity["code"], "rs");
+    assert_eq!(parity["block_start_id"], 1);
+    assert_eq!(parity["data"], 2);
+    assert_eq!(parity["index"], 0);
+}
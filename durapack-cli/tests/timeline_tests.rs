@@ -225,6 +225,8 @@ fn test_timeline_dot_output() {
         true, // dot format
         false,
         None,
+        false, // use_index
+        false, // html
     )
     .unwrap();
 
@@ -255,6 +257,8 @@ fn test_timeline_dot_with_gaps() {
         true, // dot format
         false,
         None,
+        false, // use_index
+        false, // html
     )
     .unwrap();
 
@@ -282,6 +286,8 @@ fn test_timeline_with_analysis() {
         false, // JSON output
         true,  // analyze
         None,
+        false, // use_index
+        false, // html
     )
     .unwrap();
 
@@ -316,6 +322,8 @@ fn test_timeline_dot_with_analysis() {
         true, // dot format
         true, // analyze
         None,
+        false, // use_index
+        false, // html
     )
     .unwrap();
 
@@ -342,6 +350,8 @@ fn test_timeline_output_to_stdout() {
         false,
         false,
         None,
+        false, // use_index
+        false, // html
     )
     .unwrap();
 
@@ -365,6 +375,8 @@ fn test_timeline_dot_to_stdout() {
         true, // dot
         false,
         None,
+        false, // use_index
+        false, // html
     )
     .unwrap();
 
@@ -480,6 +492,8 @@ fn test_timeline_with_fec_index() {
         true, // dot
         false,
         Some(fec_index_path.to_str().unwrap()),
+        false, // use_index
+        false, // html
     )
     .unwrap();
 
@@ -514,6 +528,8 @@ fn test_timeline_fec_index_json_mode() {
         false, // JSON output
         false,
         Some(fec_index_path.to_str().unwrap()),
+        false, // use_index
+        false, // html
     )
     .unwrap();
 
@@ -543,6 +559,8 @@ fn test_timeline_invalid_fec_index() {
         false,
         false,
         Some(fec_index_path.to_str().unwrap()),
+        false, // use_index
+        false, // html
     );
 
     // Should fail with invalid JSON
@@ -604,3 +622,274 @@ fn test_timeline_large_sequence() {
     assert_eq!(output["frames"].as_array().unwrap().len(), 20);
     assert_eq!(output["stats"]["total_frames"].as_u64().unwrap(), 20);
 }
+
+#[test]
+fn test_timeline_analysis_reports_equivocation() {
+    let td = tempdir().unwrap();
+    let input_path = td.path().join("frames_equivocation.durp");
+    let output_path = td.path().join("timeline_equivocation.json");
+
+    // Frame 2 appears twice: once as written, once rewritten; frame 3 is replayed verbatim
+    let mut frames = create_sequential_frames(3);
+    let rewritten = FrameBuilder::new(2)
+        .payload(Bytes::from("Frame 2 rewritten"))
+        .with_blake3()
+        .build()
+        .unwrap();
+    let rewritten_offset = frames.len();
+    frames.extend_from_slice(&rewritten);
+    let replay = create_sequential_frames(3);
+    let third_len = FrameBuilder::new(3)
+        .payload(Bytes::from("Frame 3"))
+        .with_blake3()
+        .build()
+        .unwrap()
+        .len();
+    frames.extend_from_slice(&replay[replay.len() - third_len..]);
+    fs::write(&input_path, frames).unwrap();
+
+    timeline::execute_ext(
+        input_path.to_str().unwrap(),
+        output_path.to_str().unwrap(),
+        false,
+        false, // JSON output
+        true,  // analyze
+        None,
+        false, // use_index
+        false, // html
+    )
+    .unwrap();
+
+    let json = fs::read_to_string(&output_path).unwrap();
+    let output: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let equivocations = output["analysis"]["equivocations"].as_array().unwrap();
+    assert_eq!(equivocations.len(), 1);
+    assert_eq!(equivocations[0]["frame_id"], 2);
+    let offsets = equivocations[0]["offsets"].as_array().unwrap();
+    assert_eq!(offsets.len(), 2);
+    assert_eq!(offsets[1], rewritten_offset);
+    assert_eq!(output["frames"].as_array().unwrap().len(), 3);
+}
+
+#[test]
+fn test_timeline_index_sidecar_reused_and_extended() {
+    use durapack_core::capture_index::CaptureIndex;
+
+    let td = tempdir().unwrap();
+    let input_path = td.path().join("indexed.durp");
+    let output_path = td.path().join("indexed.json");
+    let index_path = td.path().join("indexed.durp.idx");
+    let input = input_path.to_str().unwrap();
+    let output = output_path.to_str().unwrap();
+
+    let run =
+        || timeline::execute_ext(input, output, false, false, false, None, true, false).unwrap();
+
+    fs::write(&input_path, create_sequential_frames(3)).unwrap();
+    run();
+    let first_json = fs::read_to_string(&output_path).unwrap();
+    let first_index = fs::read(&index_path).unwrap();
+    assert_eq!(
+        CaptureIndex::from_bytes(&first_index)
+            .unwrap()
+            .entries
+            .len(),
+        3
+    );
+
+    // Unchanged capture: same timeline, sidecar left as is
+    run();
+    assert_eq!(fs::read_to_string(&output_path).unwrap(), first_json);
+    assert_eq!(fs::read(&index_path).unwrap(), first_index);
+
+    // Appended frames are picked up and the sidecar rewritten
+    let mut data = fs::read(&input_path).unwrap();
+    for id in 4..=5 {
+        let frame = FrameBuilder::new(id)
+            .payload(Bytes::from(format!("Frame {}", id)))
+            .with_blake3()
+            .build()
+            .unwrap();
+        data.extend_from_slice(&frame);
+    }
+    fs::write(&input_path, &data).unwrap();
+    run();
+    let index = CaptureIndex::from_bytes(&fs::read(&index_path).unwrap()).unwrap();
+    assert_eq!(index.entries.len(), 5);
+    assert_eq!(index.file_len, data.len() as u64);
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+    assert_eq!(json["stats"]["total_frames"], 5);
+}
+
+#[test]
+fn test_timeline_index_rejects_stdin() {
+    let td = tempdir().unwrap();
+    let output_path = td.path().join("out.json");
+    let result = timeline::execute_ext(
+        "-",
+        output_path.to_str().unwrap(),
+        false,
+        false,
+        false,
+        None,
+        true,  // use_index
+        false, // html
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_timeline_headless_chain_follows_hash_links() {
+    let td = tempdir().unwrap();
+    let input_path = td.path().join("frames_headless.durp");
+    let output_path = td.path().join("timeline_headless.json");
+
+    // The head was lost and IDs were renumbered; the hash chain is 20 -> 21 -> 9 -> 10.
+    // Written out of order to make sure ID order is not taken as truth.
+    let mut prev = [0x55u8; 32];
+    let mut encoded = Vec::new();
+    for id in [20u64, 21, 9, 10] {
+        let frame = FrameBuilder::new(id)
+            .payload(Bytes::from(format!("Frame {}", id)))
+            .prev_hash(prev)
+            .with_blake3()
+            .build_struct()
+            .unwrap();
+        prev = frame.compute_hash();
+        encoded.push(durapack_core::encoder::encode_frame_struct(&frame).unwrap());
+    }
+    encoded.reverse();
+    fs::write(&input_path, encoded.concat()).unwrap();
+
+    timeline::execute_ext(
+        input_path.to_str().unwrap(),
+        output_path.to_str().unwrap(),
+        false,
+        false, // JSON output
+        true,  // analyze
+        None,
+        false, // use_index
+        false, // html
+    )
+    .unwrap();
+
+    let json = fs::read_to_string(&output_path).unwrap();
+    let output: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let ids: Vec<u64> = output["frames"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["frame_id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![20, 21, 9, 10]);
+    assert_eq!(output["stats"]["gaps"], 0);
+    assert_eq!(output["analysis"]["renumbered"], serde_json::json!([9]));
+}
+
+#[test]
+fn test_timeline_html_report() {
+    let td = tempdir().unwrap();
+    let input_path = td.path().join("gaps.durp");
+    let output_path = td.path().join("timeline.html");
+    fs::write(&input_path, create_frames_with_gaps()).unwrap();
+
+    timeline::execute_ext(
+        input_path.to_str().unwrap(),
+        output_path.to_str().unwrap(),
+        false,
+        false, // dot
+        false, // analyze (implied by html)
+        None,
+        false, // use_index
+        true,  // html
+    )
+    .unwrap();
+
+    let html = fs::read_to_string(&output_path).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<h2>Gaps</h2>"));
+    assert!(html.contains("<div class=\"gap\""));
+    assert!(!html.contains("http://") && !html.contains("https://"));
+
+    // HTML and DOT cannot both be the output format
+    assert!(timeline::execute_ext(
+        input_path.to_str().unwrap(),
+        output_path.to_str().unwrap(),
+        false,
+        true, // dot
+        false,
+        None,
+        false, // use_index
+        true,  // html
+    )
+    .is_err());
+}
+
+#[test]
+fn test_timeline_reports_in_band_parity_without_sidecar() {
+    use durapack_core::parity::{FecCode, ParityDescriptor};
+
+    let td = tempdir().unwrap();
+    let input_path = td.path().join("in_band.durp");
+    let output_path = td.path().join("in_band.json");
+
+    // Data frames 1..=4 with frame 2 lost; parity frames 5..=7 with frame 6 lost
+    let mut data = Vec::new();
+    for id in [1u64, 3, 4] {
+        let mut b = FrameBuilder::new(id).payload(Bytes::from(format!("Frame {}", id)));
+        if id == 1 {
+            b = b.mark_first();
+        }
+        data.extend_from_slice(&b.build().unwrap());
+    }
+    for (id, index) in [(5u64, 0u32), (7, 2)] {
+        let desc = ParityDescriptor {
+            code: FecCode::ReedSolomon,
+            param: 0,
+            block_start_id: 1,
+            data: 4,
+            parity: 3,
+            index,
+        };
+        let b = FrameBuilder::new(id).as_parity(&desc, b"shard");
+        data.extend_from_slice(&b.build().unwrap());
+    }
+    fs::write(&input_path, data).unwrap();
+
+    timeline::execute_ext(
+        input_path.to_str().unwrap(),
+        output_path.to_str().unwrap(),
+        true,  // include_orphans
+        false, // JSON output
+        false,
+        None, // no FEC sidecar
+        false,
+        false,
+    )
+    .unwrap();
+
+    let v: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+    let blocks = v["parity_blocks"].as_array().unwrap();
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0]["code"], "rs");
+    assert_eq!(blocks[0]["parity_frame_ids"], serde_json::json!([5, 6, 7]));
+    assert_eq!(blocks[0]["parity_found"], 2);
+    assert_eq!(blocks[0]["missing_data"], serde_json::json!([2]));
+    assert_eq!(blocks[0]["repairable"], true);
+
+    // Parity frames are marked and carry no data payload
+    let all: Vec<&serde_json::Value> = v["frames"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .chain(v["orphans"].as_array().unwrap())
+        .collect();
+    let parity = all.iter().find(|f| f["frame_id"] == 7).unwrap();
+    assert_eq!(parity["parity"]["index"], 2);
+    assert_eq!(parity["payload"], "");
+    let data_frame = all.iter().find(|f| f["frame_id"] == 3).unwrap();
+    assert!(data_frame.get("parity").is_none());
+    assert_eq!(data_frame["payload"], "Frame 3");
+}
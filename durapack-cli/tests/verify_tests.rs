@@ -1,5 +1,5 @@
 use bytes::Bytes;
-use durapack_cli::commands::verify;
+use durapack_cli::commands::verify::{self, VerifyStatus};
 use durapack_core::encoder::FrameBuilder;
 use std::fs;
 use tempfile::tempdir;
@@ -151,6 +151,10 @@ fn test_verify_execute_ext_basic() {
         false, // report_gaps
         None,  // fec_index_path
         false, // rs_repair
+        false, // use_index
+        None,  // tamper_report
+        None,  // repair_out
+        None,  // repair_report
     );
 
     assert!(result.is_ok());
@@ -172,7 +176,7 @@ fn test_verify_with_fec_index() {
             "block_start_id": 1,
             "data": 2,
             "parity": 1,
-            "_parity_frame_ids": [5]
+            "parity_frame_ids": [5]
         }
     ]"#;
     fs::write(&fec_index_path, fec_index).unwrap();
@@ -183,6 +187,10 @@ fn test_verify_with_fec_index() {
         false,
         Some(fec_index_path.to_str().unwrap()),
         false, // rs_repair=false
+        false, // use_index
+        None,  // tamper_report
+        None,  // repair_out
+        None,  // repair_report
     );
 
     assert!(result.is_ok());
@@ -206,7 +214,11 @@ fn test_verify_invalid_fec_index() {
         input_path.to_str().unwrap(),
         false,
         Some(fec_index_path.to_str().unwrap()),
-        true, // rs_repair must be true to load FEC index
+        true,  // rs_repair must be true to load FEC index
+        false, // use_index
+        None,  // tamper_report
+        None,  // repair_out
+        None,  // repair_report
     );
 
     // Should fail due to invalid JSON
@@ -228,7 +240,11 @@ fn test_verify_missing_fec_index_file() {
         input_path.to_str().unwrap(),
         false,
         Some(fec_index_path.to_str().unwrap()),
-        true, // rs_repair must be true to load FEC index
+        true,  // rs_repair must be true to load FEC index
+        false, // use_index
+        None,  // tamper_report
+        None,  // repair_out
+        None,  // repair_report
     );
 
     // Should fail - file not found
@@ -534,7 +550,7 @@ fn test_verify_execute_ext_all_params() {
     fs::write(&input_path, frames).unwrap();
 
     // Create minimal valid FEC index
-    let fec_index = r#"[{"block_start_id": 1, "data": 2, "parity": 1, "_parity_frame_ids": [4]}]"#;
+    let fec_index = r#"[{"block_start_id": 1, "data": 2, "parity": 1, "parity_frame_ids": [4]}]"#;
     fs::write(&fec_index_path, fec_index).unwrap();
 
     // Execute with all parameters
@@ -543,6 +559,10 @@ fn test_verify_execute_ext_all_params() {
         true, // report_gaps
         Some(fec_index_path.to_str().unwrap()),
         false, // rs_repair (disabled by default, might not have feature)
+        false, // use_index
+        None,  // tamper_report
+        None,  // repair_out
+        None,  // repair_report
     );
 
     assert!(result.is_ok());
@@ -559,8 +579,8 @@ fn test_verify_with_rs_repair_flag() {
 
     // Create valid FEC index
     let fec_index = r#"[
-        {"block_start_id": 1, "data": 3, "parity": 1, "_parity_frame_ids": [5]},
-        {"block_start_id": 5, "data": 2, "parity": 1, "_parity_frame_ids": [8]}
+        {"block_start_id": 1, "data": 3, "parity": 1, "parity_frame_ids": [5]},
+        {"block_start_id": 5, "data": 2, "parity": 1, "parity_frame_ids": [8]}
     ]"#;
     fs::write(&fec_index_path, fec_index).unwrap();
 
@@ -569,7 +589,11 @@ fn test_verify_with_rs_repair_flag() {
         input_path.to_str().unwrap(),
         false,
         Some(fec_index_path.to_str().unwrap()),
-        true, // rs_repair
+        true,  // rs_repair
+        false, // use_index
+        None,  // tamper_report
+        None,  // repair_out
+        None,  // repair_report
     );
 
     // Should succeed (will print RS repair info if feature is enabled)
@@ -1137,8 +1161,8 @@ fn test_verify_with_fec_rs_repair_enabled() {
 
     // Create detailed FEC index
     let fec_index = r#"[
-        {"block_start_id": 1, "data": 4, "parity": 2, "_parity_frame_ids": [7, 8]},
-        {"block_start_id": 9, "data": 3, "parity": 1, "_parity_frame_ids": [13]}
+        {"block_start_id": 1, "data": 4, "parity": 2, "parity_frame_ids": [7, 8]},
+        {"block_start_id": 9, "data": 3, "parity": 1, "parity_frame_ids": [13]}
     ]"#;
     fs::write(&fec_index_path, fec_index).unwrap();
 
@@ -1147,7 +1171,11 @@ fn test_verify_with_fec_rs_repair_enabled() {
         input_path.to_str().unwrap(),
         true, // report_gaps
         Some(fec_index_path.to_str().unwrap()),
-        true, // rs_repair
+        true,  // rs_repair
+        false, // use_index
+        None,  // tamper_report
+        None,  // repair_out
+        None,  // repair_report
     );
 
     assert!(result.is_ok());
@@ -1766,7 +1794,7 @@ fn test_verify_with_fec_and_gaps() {
     fs::write(&input_path, frames_data).unwrap();
 
     // FEC index
-    let fec_index = r#"[{"block_start_id": 1, "data": 3, "parity": 1, "_parity_frame_ids": [6]}]"#;
+    let fec_index = r#"[{"block_start_id": 1, "data": 3, "parity": 1, "parity_frame_ids": [6]}]"#;
     fs::write(&fec_index_path, fec_index).unwrap();
 
     // Verify with FEC and gaps
@@ -1775,6 +1803,10 @@ fn test_verify_with_fec_and_gaps() {
         true, // report_gaps
         Some(fec_index_path.to_str().unwrap()),
         false,
+        false, // use_index
+        None,  // tamper_report
+        None,  // repair_out
+        None,  // repair_report
     );
 
     assert!(result.is_ok());
@@ -1791,9 +1823,9 @@ fn test_verify_fec_with_rs_repair_multiple_blocks() {
 
     // Multiple FEC blocks
     let fec_index = r#"[
-        {"block_start_id": 1, "data": 3, "parity": 1, "_parity_frame_ids": [4]},
-        {"block_start_id": 5, "data": 3, "parity": 1, "_parity_frame_ids": [8]},
-        {"block_start_id": 9, "data": 2, "parity": 1, "_parity_frame_ids": [11]}
+        {"block_start_id": 1, "data": 3, "parity": 1, "parity_frame_ids": [4]},
+        {"block_start_id": 5, "data": 3, "parity": 1, "parity_frame_ids": [8]},
+        {"block_start_id": 9, "data": 2, "parity": 1, "parity_frame_ids": [11]}
     ]"#;
     fs::write(&fec_index_path, fec_index).unwrap();
 
@@ -1802,8 +1834,423 @@ fn test_verify_fec_with_rs_repair_multiple_blocks() {
         input_path.to_str().unwrap(),
         false,
         Some(fec_index_path.to_str().unwrap()),
-        true, // rs_repair
+        true,  // rs_repair
+        false, // use_index
+        None,  // tamper_report
+        None,  // repair_out
+        None,  // repair_report
     );
 
     assert!(result.is_ok());
 }
+
+#[test]
+fn test_verify_status_reflects_completeness() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("status.durp");
+    let status = |data: &[u8]| {
+        fs::write(&path, data).unwrap();
+        verify::execute(path.to_str().unwrap(), false).unwrap()
+    };
+
+    // Chained first through last
+    let mut chain = Vec::new();
+    let mut prev = [0u8; 32];
+    for id in 1..=3u64 {
+        let mut builder = FrameBuilder::new(id)
+            .payload(Bytes::from(format!("Frame {}", id)))
+            .prev_hash(prev)
+            .with_blake3();
+        if id == 1 {
+            builder = builder.mark_first();
+        }
+        if id == 3 {
+            builder = builder.mark_last();
+        }
+        let frame = builder.build_struct().unwrap();
+        prev = frame.compute_hash();
+        chain.extend_from_slice(&durapack_core::encoder::encode_frame_struct(&frame).unwrap());
+    }
+    assert_eq!(status(&chain), VerifyStatus::Complete);
+    assert_eq!(VerifyStatus::Complete.exit_code(), 0);
+
+    // Cut off before the IS_LAST frame
+    let truncated = &chain[..chain.len() - 10];
+    assert_eq!(status(truncated), VerifyStatus::Incomplete);
+
+    assert_eq!(status(&create_frames_with_gaps()), VerifyStatus::Incomplete);
+    assert_eq!(
+        status(&create_broken_backlink_frames()),
+        VerifyStatus::Damaged
+    );
+
+    // IS_FIRST on a frame that links back to something
+    let forged = FrameBuilder::new(1)
+        .payload(Bytes::from("Frame 1"))
+        .mark_first()
+        .prev_hash([7u8; 32])
+        .mark_last()
+        .with_blake3()
+        .build()
+        .unwrap();
+    assert_eq!(status(&forged), VerifyStatus::Damaged);
+    assert_ne!(VerifyStatus::Incomplete.exit_code(), 0);
+    assert_ne!(
+        VerifyStatus::Incomplete.exit_code(),
+        VerifyStatus::Damaged.exit_code()
+    );
+}
+
+#[test]
+fn test_verify_tamper_report_classifies_findings() {
+    let temp_dir = tempdir().unwrap();
+    let input_path = temp_dir.path().join("tampered.durp");
+    let report_path = temp_dir.path().join("tamper.json");
+    fs::write(&input_path, create_broken_backlink_frames()).unwrap();
+
+    let status = verify::execute_ext(
+        input_path.to_str().unwrap(),
+        false, // report_gaps
+        None,  // fec_index_path
+        false, // rs_repair
+        false, // use_index
+        Some(report_path.to_str().unwrap()),
+        None, // repair_out
+        None, // repair_report
+    )
+    .unwrap();
+    assert_eq!(status, VerifyStatus::Damaged);
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    let findings = report["findings"].as_array().unwrap();
+    assert!(findings
+        .iter()
+        .any(|f| f["kind"] == "broken_chain" && f["cause"] == "deliberate"));
+    assert_eq!(report["max_severity"], "high");
+    assert!(report["deliberate"].as_u64().unwrap() >= 1);
+
+    // A clean stream produces an empty report
+    let single = FrameBuilder::new(1)
+        .payload(Bytes::from("Frame 1"))
+        .mark_first()
+        .mark_last()
+        .with_blake3()
+        .build()
+        .unwrap();
+    fs::write(&input_path, single).unwrap();
+    verify::execute_ext(
+        input_path.to_str().unwrap(),
+        false,
+        None,
+        false,
+        false,
+        Some(report_path.to_str().unwrap()),
+        None,
+        None,
+    )
+    .unwrap();
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert!(report["findings"].as_array().unwrap().is_empty());
+    assert!(report["max_severity"].is_null());
+}
+
+#[test]
+fn test_verify_tamper_report_on_stdout_is_plain_json() {
+    let temp_dir = tempdir().unwrap();
+    let input_path = temp_dir.path().join("tampered.durp");
+    fs::write(&input_path, create_broken_backlink_frames()).unwrap();
+
+    let out = std::process::Command::new(env!("CARGO_BIN_EXE_durapack"))
+        .current_dir(temp_dir.path())
+        .args([
+            "verify",
+            "-i",
+            input_path.to_str().unwrap(),
+            "--tamper-report",
+            "-",
+        ])
+        .output()
+        .unwrap();
+    // The human report stays off stdout and no file named "-" appears
+    let report: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(report["max_severity"], "high");
+    assert!(!temp_dir.path().join("-").exists());
+
+    // The tamper report and the repaired stream cannot both go to stdout
+    assert!(verify::execute_ext(
+        input_path.to_str().unwrap(),
+        false,
+        None,
+        true,  // rs_repair
+        false, // use_index
+        Some("-"),
+        Some("-"), // repair_out
+        None,
+    )
+    .is_err());
+}
+
+/// Pack 8 records as two RS(4+2) blocks into `packed.durp` and drop the listed frame IDs
+#[cfg(feature = "fec-rs")]
+fn packed_with_losses(
+    dir: &std::path::Path,
+    drop: &[u64],
+    sidecar: Option<&str>,
+    whole_frames: bool,
+) -> Vec<u8> {
+    use durapack_cli::{commands::pack, ChunkStrategy, FecScheme};
+    use durapack_core::scanner::scan_stream;
+
+    let in_path = dir.join("records.json");
+    let packed = dir.join("packed.durp");
+    let records: Vec<String> = (0..8).map(|i| format!("{{\"reading\":{}}}", i)).collect();
+    fs::write(&in_path, format!("[{}]", records.join(","))).unwrap();
+    pack::execute_ext(
+        in_path.to_str().unwrap(),
+        packed.to_str().unwrap(),
+        /*use_blake3*/ true,
+        /*start_id*/ 1,
+        /*jsonl*/ false,
+        ChunkStrategy::Aggregate,
+        /*rate_limit*/ None,
+        /*progress*/ false,
+        /*fec_rs*/ Some((4, 2)),
+        /*fec_index_out*/ sidecar,
+        /*sign_key_path*/ None,
+        /*header_crc*/ whole_frames,
+        FecScheme::Rs,
+        /*fec_whole_frames*/ whole_frames,
+    )
+    .unwrap();
+
+    let data = fs::read(&packed).unwrap();
+    let mut out = Vec::new();
+    for lf in scan_stream(&data) {
+        if !drop.contains(&lf.frame.header.frame_id) {
+            out.extend_from_slice(&data[lf.offset..lf.offset + lf.size]);
+        }
+    }
+    out
+}
+
+#[cfg(feature = "fec-rs")]
+#[test]
+fn test_verify_rs_repair_writes_repaired_file() {
+    let td = tempdir().unwrap();
+    let input_path = td.path().join("lossy.durp");
+    let repaired_path = td.path().join("repaired.durp");
+    let report_path = td.path().join("repair.json");
+
+    // Frames 1-4 data, 5-6 parity, 7-10 data, 11-12 parity
+    fs::write(
+        &input_path,
+        packed_with_losses(td.path(), &[2, 3, 8], None, false),
+    )
+    .unwrap();
+
+    let status = verify::execute_ext(
+        input_path.to_str().unwrap(),
+        false, // report_gaps
+        None,  // fec_index_path: parity frames are in-band
+        true,  // rs_repair
+        false, // use_index
+        None,  // tamper_report
+        Some(repaired_path.to_str().unwrap()),
+        Some(report_path.to_str().unwrap()),
+    )
+    .unwrap();
+    assert_eq!(status, VerifyStatus::Incomplete);
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(report["repaired_frames"], serde_json::json!([2, 3, 8]));
+    assert_eq!(report["gaps_after"], 0);
+    assert_eq!(report["frames_after"], 12);
+    let blocks = report["blocks"].as_array().unwrap();
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[0]["outcome"], "repaired");
+    assert_eq!(blocks[0]["missing"], serde_json::json!([2, 3]));
+    assert_eq!(blocks[1]["outcome"], "repaired");
+
+    // The repaired file is the complete original chain
+    let status = verify::execute(repaired_path.to_str().unwrap(), false).unwrap();
+    assert_eq!(status, VerifyStatus::Complete);
+}
+
+#[cfg(feature = "fec-rs")]
+#[test]
+fn test_verify_rs_repair_with_sidecar_reports_unrepairable_block() {
+    let td = tempdir().unwrap();
+    let input_path = td.path().join("lossy.durp");
+    let sidecar = td.path().join("packed.durp.fec.json");
+    let report_path = td.path().join("repair.json");
+
+    // Block 2 loses three data frames but has only two parity frames
+    let data = packed_with_losses(
+        td.path(),
+        &[4, 7, 8, 9],
+        Some(sidecar.to_str().unwrap()),
+        false,
+    );
+    fs::write(&input_path, data).unwrap();
+
+    verify::execute_ext(
+        input_path.to_str().unwrap(),
+        false,
+        Some(sidecar.to_str().unwrap()),
+        true,  // rs_repair
+        false, // use_index
+        None,  // tamper_report
+        None,  // repair_out: defaults next to the input
+        Some(report_path.to_str().unwrap()),
+    )
+    .unwrap();
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    let blocks = report["blocks"].as_array().unwrap();
+    assert_eq!(blocks[0]["outcome"], "repaired");
+    assert_eq!(blocks[0]["repaired"], serde_json::json!([4]));
+    assert_eq!(blocks[1]["outcome"], "unrepairable");
+    assert_eq!(blocks[1]["missing"], serde_json::json!([7, 8, 9]));
+    assert_eq!(blocks[1]["parity_present"], 2);
+    assert!(td.path().join("lossy.durp.repaired.durp").exists());
+}
+
+#[test]
+fn test_verify_rejects_repair_stream_and_report_both_on_stdout() {
+    let td = tempdir().unwrap();
+    let input_path = td.path().join("input.durp");
+    fs::write(&input_path, create_valid_frames(3)).unwrap();
+
+    let err = verify::execute_ext(
+        input_path.to_str().unwrap(),
+        false,
+        None,
+        true,      // rs_repair
+        false,     // use_index
+        None,      // tamper_report
+        Some("-"), // repair_out
+        Some("-"), // repair_report
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("stdout"));
+}
+
+#[cfg(feature = "fec-rs")]
+#[test]
+fn test_verify_rs_repair_rejects_legacy_shard_layout() {
+    let td = tempdir().unwrap();
+    let input_path = td.path().join("lossy.durp");
+    let sidecar = td.path().join("packed.durp.fec.json");
+    let report_path = td.path().join("repair.json");
+
+    let data = packed_with_losses(td.path(), &[2], Some(sidecar.to_str().unwrap()), false);
+    fs::write(&input_path, data).unwrap();
+
+    // Sidecars from builds with payload-only shards carry no layout
+    let mut entries: Vec<serde_json::Value> =
+        serde_json::from_slice(&fs::read(&sidecar).unwrap()).unwrap();
+    for e in &mut entries {
+        assert_eq!(e["shard_layout"], 2);
+        e.as_object_mut().unwrap().remove("shard_layout");
+    }
+    fs::write(&sidecar, serde_json::to_vec(&entries).unwrap()).unwrap();
+
+    verify::execute_ext(
+        input_path.to_str().unwrap(),
+        false,
+        Some(sidecar.to_str().unwrap()),
+        true,  // rs_repair
+        false, // use_index
+        None,  // tamper_report
+        None,  // repair_out
+        Some(report_path.to_str().unwrap()),
+    )
+    .unwrap();
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    let blocks = report["blocks"].as_array().unwrap();
+    assert_eq!(blocks[0]["outcome"], "incompatible");
+    assert_eq!(blocks[0]["missing"], serde_json::json!([2]));
+    assert_eq!(report["repaired_frames"], serde_json::json!([]));
+    assert_eq!(blocks[1]["outcome"], "intact");
+}
+
+#[cfg(feature = "fec-rs")]
+#[test]
+fn test_verify_rs_repair_whole_frames_restores_original_bytes() {
+    let td = tempdir().unwrap();
+    let input_path = td.path().join("lossy.durp");
+    let repaired_path = td.path().join("repaired.durp");
+    let report_path = td.path().join("repair.json");
+
+    // Header-CRC frames; the lost ones include a block's first frame
+    fs::write(
+        &input_path,
+        packed_with_losses(td.path(), &[1, 3, 9, 10], None, true),
+    )
+    .unwrap();
+
+    verify::execute_ext(
+        input_path.to_str().unwrap(),
+        false,
+        None,
+        true,  // rs_repair
+        false, // use_index
+        None,  // tamper_report
+        Some(repaired_path.to_str().unwrap()),
+        Some(report_path.to_str().unwrap()),
+    )
+    .unwrap();
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(report["repaired_frames"], serde_json::json!([1, 3, 9, 10]));
+    let blocks = report["blocks"].as_array().unwrap();
+    assert!(blocks.iter().all(|b| b["whole_frames"] == true));
+    assert!(blocks.iter().all(|b| b["outcome"] == "repaired"));
+
+    assert_eq!(
+        fs::read(&repaired_path).unwrap(),
+        fs::read(td.path().join("packed.durp")).unwrap()
+    );
+}
+
+#[cfg(feature = "fec-rs")]
+#[test]
+fn test_verify_pack_then_fec_is_complete() {
+    use durapack_cli::commands::{fec, pack};
+
+    let td = tempdir().unwrap();
+    let in_path = td.path().join("records.json");
+    let packed = td.path().join("packed.durp");
+    let records: Vec<String> = (0..6).map(|i| format!("{{\"reading\":{}}}", i)).collect();
+    fs::write(&in_path, format!("[{}]", records.join(","))).unwrap();
+    pack::execute(
+        in_path.to_str().unwrap(),
+        packed.to_str().unwrap(),
+        false,
+        1,
+    )
+    .unwrap();
+
+    // Parity appended after the frame that already carries IS_LAST
+    fec::inject_parity(
+        packed.to_str().unwrap(),
+        None,
+        /*n_data*/ 3,
+        /*k_parity*/ 1,
+        /*fec_index_out*/ None,
+        /*dry_run*/ false,
+        /*whole_frames*/ false,
+    )
+    .unwrap();
+
+    let status = verify::execute(packed.to_str().unwrap(), false).unwrap();
+    assert_eq!(status, VerifyStatus::Complete);
+    assert_eq!(status.exit_code(), 0);
+}
@@ -2,7 +2,10 @@ use bytes::Bytes;
 use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
 use durapack_core::encoder::FrameBuilder;
 #[cfg(feature = "fec-rs")]
-use durapack_core::fec::{RedundancyEncoder, RsEncoder};
+use durapack_core::fec::{FecBlock, RedundancyDecoder, RedundancyEncoder, RsDecoder, RsEncoder};
+#[cfg(feature = "fec-ldpc")]
+use durapack_core::fec::{LdpcDecoder, LdpcEncoder};
+use durapack_core::types::Frame;
 
 fn bench_rs_encode(c: &mut Criterion) {
     let mut group = c.benchmark_group("fec_rs_encode");
@@ -35,5 +38,88 @@ fn bench_rs_encode(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_rs_encode);
+/// A block of `n` frames with `len`-byte payloads
+fn block(n: usize, len: usize) -> Vec<Frame> {
+    (0..n)
+        .map(|i| {
+            let payload: Vec<u8> = (0..len).map(|j| (i * 31 + j) as u8).collect();
+            FrameBuilder::new(i as u64)
+                .payload(Bytes::from(payload))
+                .build_struct()
+                .unwrap()
+        })
+        .collect()
+}
+
+/// Every shard except each `stride`-th one
+fn survivors(blocks: Vec<FecBlock>, stride: usize) -> Vec<FecBlock> {
+    blocks
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| i % stride != 0)
+        .map(|(_, b)| b)
+        .collect()
+}
+
+/// RS vs LDPC at rate 0.8 as blocks grow; RS over GF(2^8) stops at 255 shards
+fn bench_block_encode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fec_block_encode");
+    group.sample_size(20);
+    let len = 1024usize;
+    for &n in &[32usize, 128, 200, 1000, 4000] {
+        let frames = block(n, len);
+        let k = n / 4;
+        group.throughput(Throughput::Bytes((n * len) as u64));
+        if n + k <= 255 {
+            group.bench_with_input(BenchmarkId::new("rs", n), &frames, |b, frames| {
+                let enc = RsEncoder::new(frames.len(), k);
+                b.iter(|| enc.encode_batch(frames, 0).unwrap())
+            });
+        }
+        #[cfg(feature = "fec-ldpc")]
+        group.bench_with_input(BenchmarkId::new("ldpc", n), &frames, |b, frames| {
+            let enc = LdpcEncoder::with_rate(0.8);
+            b.iter(|| enc.encode_batch(frames, 0).unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// Rebuild a block after losing half its parity budget, spread across the block
+fn bench_block_decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fec_block_decode");
+    group.sample_size(20);
+    let len = 1024usize;
+    for &n in &[32usize, 128, 200, 1000, 4000] {
+        let frames = block(n, len);
+        let k = n / 4;
+        let stride = (n + k) / (k / 2);
+        group.throughput(Throughput::Bytes((n * len) as u64));
+        if n + k <= 255 {
+            let blocks = RsEncoder::new(n, k).encode_batch(&frames, 0).unwrap();
+            let avail = survivors(blocks, stride);
+            group.bench_with_input(BenchmarkId::new("rs", n), &avail, |b, avail| {
+                b.iter(|| RsDecoder.decode_batch(avail, n).unwrap())
+            });
+        }
+        #[cfg(feature = "fec-ldpc")]
+        {
+            let blocks = LdpcEncoder::with_rate(0.8)
+                .encode_batch(&frames, 0)
+                .unwrap();
+            let avail = survivors(blocks, stride);
+            group.bench_with_input(BenchmarkId::new("ldpc", n), &avail, |b, avail| {
+                b.iter(|| LdpcDecoder::default().decode_batch(avail, n).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_rs_encode,
+    bench_block_encode,
+    bench_block_decode
+);
 criterion_main!(benches);
@@ -0,0 +1,78 @@
+use bytes::Bytes;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use durapack_core::{
+    constants::FrameFlags,
+    linker::{analyze_timeline, link_frames, verify_backlinks},
+    types::{Frame, FrameHeader},
+};
+
+/// Build a hash-linked chain of `n` small frames, optionally with every 100th frame missing
+fn make_chain(n: u64, with_gaps: bool) -> Vec<Frame> {
+    let mut frames = Vec::with_capacity(n as usize);
+    let mut prev = [0u8; 32];
+    for id in 1..=n {
+        let flags = if id == 1 {
+            FrameFlags::new(FrameFlags::IS_FIRST)
+        } else {
+            FrameFlags::default()
+        };
+        let frame = Frame::new(
+            FrameHeader::with_flags(id, prev, 8, flags),
+            Bytes::from(id.to_be_bytes().to_vec()),
+        );
+        prev = frame.compute_hash();
+        if !(with_gaps && id % 100 == 0) {
+            frames.push(frame);
+        }
+    }
+    // Reverse so the linker cannot rely on input order
+    frames.reverse();
+    frames
+}
+
+// Sizes grow 10x per step; per-frame time should stay roughly flat (O(n log n))
+const SIZES: [u64; 3] = [1_000, 10_000, 100_000];
+
+fn bench_link(c: &mut Criterion) {
+    let mut group = c.benchmark_group("link_frames");
+    group.sample_size(10);
+
+    for n in SIZES {
+        for (label, with_gaps) in [("chain", false), ("gappy", true)] {
+            let frames = make_chain(n, with_gaps);
+            group.throughput(Throughput::Elements(n));
+            group.bench_with_input(BenchmarkId::new(label, n), &frames, |b, frames| {
+                b.iter(|| link_frames(black_box(frames.clone())));
+            });
+        }
+    }
+
+    group.finish();
+}
+
+fn bench_report(c: &mut Criterion) {
+    let mut group = c.benchmark_group("timeline_report");
+    group.sample_size(10);
+
+    for n in SIZES {
+        let frames = make_chain(n, true);
+        group.throughput(Throughput::Elements(n));
+        group.bench_with_input(BenchmarkId::new("analyze", n), &frames, |b, frames| {
+            b.iter(|| analyze_timeline(black_box(frames.clone())));
+        });
+
+        let timeline = link_frames(frames);
+        group.bench_with_input(
+            BenchmarkId::new("verify_backlinks", n),
+            &timeline,
+            |b, timeline| {
+                b.iter(|| verify_backlinks(black_box(timeline)));
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_link, bench_report);
+criterion_main!(benches);
@@ -30,6 +30,73 @@ fn make_stream(num_frames: usize, payload_len: usize) -> Vec<u8> {
     stream
 }
 
+/// Pseudo-random noise with no valid markers (xorshift keeps the bench dependency-free)
+fn make_noise(len: usize, seed: u64) -> Vec<u8> {
+    let mut x = seed | 1;
+    (0..len)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            (x >> 24) as u8
+        })
+        .collect()
+}
+
+/// Noise densely seeded with near-miss markers (2 bits off) and short preamble/sync fragments,
+/// which forces every candidate source to fire without ever producing a decodable frame
+fn make_adversarial_noise(len: usize) -> Vec<u8> {
+    let mut data = make_noise(len, 0x5eed);
+    let mut i = 0;
+    while i + 16 <= len {
+        data[i..i + 4].copy_from_slice(b"DURP");
+        data[i + 1] ^= 0x03;
+        data[i + 6..i + 12].copy_from_slice(&[0x55, 0xAA, 0x55, 0xAA, 0x55, 0xAA]);
+        i += 16;
+    }
+    data
+}
+
+fn bench_scanner_garbage(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scanner_garbage");
+
+    // Throughput should stay flat as the input grows; a quadratic search would
+    // slow down roughly 4x per step here.
+    for &len in &[64 * 1024usize, 256 * 1024, 1024 * 1024, 4 * 1024 * 1024] {
+        let noise = make_noise(len, len as u64);
+        group.throughput(Throughput::Bytes(len as u64));
+        group.bench_with_input(BenchmarkId::new("noise", len), &noise, |b, data| {
+            b.iter(|| criterion::black_box(scan_stream_with_stats(data)));
+        });
+
+        let adversarial = make_adversarial_noise(len);
+        group.bench_with_input(
+            BenchmarkId::new("near_miss_markers", len),
+            &adversarial,
+            |b, data| {
+                b.iter(|| criterion::black_box(scan_stream_with_stats(data)));
+            },
+        );
+
+        // Real frames separated by long stretches of noise
+        let mut mixed = make_noise(len, 42);
+        for k in 0..8usize {
+            let frame = FrameBuilder::new(k as u64 + 1)
+                .with_crc32c()
+                .payload(Bytes::from(vec![b'x'; 256]))
+                .build()
+                .unwrap();
+            let at = k * (len / 8);
+            mixed[at..at + frame.len()].copy_from_slice(&frame);
+        }
+        group.bench_with_input(BenchmarkId::new("sparse_frames", len), &mixed, |b, data| {
+            b.iter(|| criterion::black_box(scan_stream(data)));
+        });
+    }
+
+    group.finish();
+}
+
 fn bench_scanner(c: &mut Criterion) {
     let mut group = c.benchmark_group("scanner");
 
@@ -63,5 +130,5 @@ fn bench_scanner(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_scanner);
+criterion_group!(benches, bench_scanner, bench_scanner_garbage);
 criterion_main!(benches);
@@ -0,0 +1,450 @@
+//! Persisted timeline index for fast reopen of captures
+//!
+//! Scanning and linking a large capture is the expensive part of every
+//! `timeline`/`verify` run. A [`CaptureIndex`] records where each frame sits
+//! (frame ID, offset, size, hash, confidence) and the gaps found when linking,
+//! together with the capture's length and a BLAKE3 hash of its contents. On
+//! reopen, [`CaptureIndex::refresh`] decodes frames straight from the recorded
+//! offsets when the capture is unchanged, scans only the appended tail when the
+//! capture has grown, and rescans everything otherwise.
+//!
+//! Binary layout (big-endian):
+//!
+//! ```text
+//! magic "DPIX" | version u8 | file_len u64 | content_hash [32]
+//! entry_count u64 | gap_count u64
+//! entries: frame_id u64 | offset u64 | size u32 | hash [32] | confidence f32
+//! gaps:    before u64 | after u64 | has_expected u8 | expected_hash [32]?
+//! crc32c u32 over everything above
+//! ```
+
+use crate::constants::{BLAKE3_HASH_SIZE, FRAME_MARKER};
+use crate::decoder::decode_frame_from_bytes;
+use crate::error::FrameError;
+use crate::linker::{link_indexed, SequenceGap};
+use crate::scanner::{scan_stream, LocatedFrame};
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// Magic bytes at the start of an index file
+pub const INDEX_MAGIC: &[u8; 4] = b"DPIX";
+
+/// Current index format version
+pub const INDEX_VERSION: u8 = 1;
+
+/// Encoded size of one [`IndexEntry`]
+const ENTRY_SIZE: usize = 8 + 8 + 4 + BLAKE3_HASH_SIZE + 4;
+
+/// Encoded size of the fixed part before the entries
+const PREAMBLE_SIZE: usize = 4 + 1 + 8 + BLAKE3_HASH_SIZE + 8 + 8;
+
+/// Where one frame sits in the capture
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IndexEntry {
+    /// Frame ID
+    pub frame_id: u64,
+    /// Byte offset of the frame marker
+    pub offset: u64,
+    /// Total frame size in bytes
+    pub size: u32,
+    /// Frame hash (as used for back-links)
+    pub hash: [u8; BLAKE3_HASH_SIZE],
+    /// Scanner confidence
+    pub confidence: f32,
+}
+
+/// How an index relates to the capture it is opened against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexStatus {
+    /// Same length and content as when indexed
+    Fresh,
+    /// The indexed content is unchanged and more bytes follow it
+    Appended,
+    /// The capture was modified or truncated
+    Stale,
+}
+
+/// What [`CaptureIndex::refresh`] had to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Refresh {
+    /// The stored index matched; nothing was scanned
+    Reused,
+    /// Only the bytes after the last indexed frame were scanned
+    Extended {
+        /// Frames found in the scanned tail
+        tail_frames: usize,
+    },
+    /// No usable index: the whole capture was scanned
+    Rebuilt,
+}
+
+/// Persisted scan and link results for one capture
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptureIndex {
+    /// Capture length when indexed
+    pub file_len: u64,
+    /// BLAKE3 hash of the first `file_len` bytes of the capture
+    pub content_hash: [u8; BLAKE3_HASH_SIZE],
+    /// Frames in scan (offset) order
+    pub entries: Vec<IndexEntry>,
+    /// Gaps found when linking the frames
+    pub gaps: Vec<SequenceGap>,
+}
+
+impl CaptureIndex {
+    /// Scan and link a capture, returning its index and the located frames
+    pub fn build(data: &[u8]) -> (Self, Vec<LocatedFrame>) {
+        let frames = scan_stream(data);
+        (Self::from_frames(data, &frames), frames)
+    }
+
+    /// Index frames already located in `data`
+    pub fn from_frames(data: &[u8], frames: &[LocatedFrame]) -> Self {
+        Self {
+            file_len: data.len() as u64,
+            content_hash: *blake3::hash(data).as_bytes(),
+            entries: frames
+                .iter()
+                .map(|lf| IndexEntry {
+                    frame_id: lf.frame.header.frame_id,
+                    offset: lf.offset as u64,
+                    size: lf.size as u32,
+                    hash: lf.frame.compute_hash(),
+                    confidence: lf.confidence,
+                })
+                .collect(),
+            gaps: link_indexed(frames).gaps,
+        }
+    }
+
+    /// Compare the index with the current contents of its capture
+    pub fn status(&self, data: &[u8]) -> IndexStatus {
+        let Ok(len) = usize::try_from(self.file_len) else {
+            return IndexStatus::Stale;
+        };
+        if data.len() < len || blake3::hash(&data[..len]).as_bytes() != &self.content_hash {
+            IndexStatus::Stale
+        } else if data.len() == len {
+            IndexStatus::Fresh
+        } else {
+            IndexStatus::Appended
+        }
+    }
+
+    /// Decode every indexed frame directly at its offset, without scanning
+    ///
+    /// Returns `None` if any entry no longer decodes to the recorded frame.
+    pub fn located_frames(&self, data: &[u8]) -> Option<Vec<LocatedFrame>> {
+        self.entries
+            .iter()
+            .map(|e| {
+                let offset = usize::try_from(e.offset).ok()?;
+                let raw = data.get(offset..offset.checked_add(e.size as usize)?)?;
+                let frame = if raw.starts_with(FRAME_MARKER) {
+                    decode_frame_from_bytes(raw)
+                } else {
+                    // Indexed through a damaged marker; the trailer vouched for it
+                    let mut repaired = raw.to_vec();
+                    repaired[..FRAME_MARKER.len()].copy_from_slice(FRAME_MARKER);
+                    decode_frame_from_bytes(&repaired)
+                }
+                .ok()?;
+                (frame.compute_hash() == e.hash).then_some(LocatedFrame {
+                    offset,
+                    frame,
+                    size: e.size as usize,
+                    confidence: e.confidence,
+                    bit_shift: 0,
+                    inverted: false,
+                    repair: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Open a capture with a previously stored index, scanning as little as possible
+    ///
+    /// `stored` is the serialized index, if one exists. It is reused when the
+    /// capture is unchanged, extended by scanning only the bytes after the
+    /// last indexed frame when the capture has grown, and rebuilt from a full
+    /// scan when it is missing, corrupt or stale. Callers should persist the
+    /// returned index unless the result is [`Refresh::Reused`].
+    pub fn refresh(data: &[u8], stored: Option<&[u8]>) -> (Self, Vec<LocatedFrame>, Refresh) {
+        if let Some(index) = stored.and_then(|bytes| Self::from_bytes(bytes).ok()) {
+            let status = index.status(data);
+            if status != IndexStatus::Stale {
+                if let Some(mut frames) = index.located_frames(data) {
+                    if status == IndexStatus::Fresh {
+                        return (index, frames, Refresh::Reused);
+                    }
+
+                    // Resume after the last complete frame: the old end of file
+                    // may have cut a frame in half
+                    let resume = frames
+                        .iter()
+                        .map(|lf| lf.offset + lf.size)
+                        .max()
+                        .unwrap_or(0);
+                    let tail: Vec<LocatedFrame> = scan_stream(&data[resume..])
+                        .into_iter()
+                        .map(|mut lf| {
+                            lf.offset += resume;
+                            lf
+                        })
+                        .collect();
+
+                    #[cfg(feature = "logging")]
+                    tracing::debug!(
+                        "Index extended: {} new frames after offset {}",
+                        tail.len(),
+                        resume
+                    );
+
+                    let tail_frames = tail.len();
+                    frames.extend(tail);
+                    let index = Self::from_frames(data, &frames);
+                    return (index, frames, Refresh::Extended { tail_frames });
+                }
+            }
+
+            #[cfg(feature = "logging")]
+            tracing::debug!("Stored index does not match the capture; rescanning");
+        }
+
+        let (index, frames) = Self::build(data);
+        (index, frames, Refresh::Rebuilt)
+    }
+
+    /// Serialize the index
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(
+            PREAMBLE_SIZE + self.entries.len() * ENTRY_SIZE + self.gaps.len() * 49 + 4,
+        );
+        out.extend_from_slice(INDEX_MAGIC);
+        out.push(INDEX_VERSION);
+        out.extend_from_slice(&self.file_len.to_be_bytes());
+        out.extend_from_slice(&self.content_hash);
+        out.extend_from_slice(&(self.entries.len() as u64).to_be_bytes());
+        out.extend_from_slice(&(self.gaps.len() as u64).to_be_bytes());
+        for e in &self.entries {
+            out.extend_from_slice(&e.frame_id.to_be_bytes());
+            out.extend_from_slice(&e.offset.to_be_bytes());
+            out.extend_from_slice(&e.size.to_be_bytes());
+            out.extend_from_slice(&e.hash);
+            out.extend_from_slice(&e.confidence.to_bits().to_be_bytes());
+        }
+        for g in &self.gaps {
+            out.extend_from_slice(&g.before.to_be_bytes());
+            out.extend_from_slice(&g.after.to_be_bytes());
+            match g.expected_hash {
+                Some(hash) => {
+                    out.push(1);
+                    out.extend_from_slice(&hash);
+                }
+                None => out.push(0),
+            }
+        }
+        let crc = crc32c::crc32c(&out);
+        out.extend_from_slice(&crc.to_be_bytes());
+        out
+    }
+
+    /// Parse an index written by [`CaptureIndex::to_bytes`]
+    pub fn from_bytes(buf: &[u8]) -> Result<Self, FrameError> {
+        let invalid = |what: &str| FrameError::InvalidStructure(format!("capture index: {}", what));
+
+        if buf.len() < PREAMBLE_SIZE + 4 {
+            return Err(invalid("truncated"));
+        }
+        let (body, crc) = buf.split_at(buf.len() - 4);
+        let expected = u32::from_be_bytes([crc[0], crc[1], crc[2], crc[3]]);
+        let actual = crc32c::crc32c(body);
+        if expected != actual {
+            return Err(FrameError::ChecksumMismatch { expected, actual });
+        }
+        if &body[..4] != INDEX_MAGIC {
+            return Err(invalid("bad magic"));
+        }
+        if body[4] != INDEX_VERSION {
+            return Err(FrameError::UnsupportedVersion(body[4]));
+        }
+
+        let mut r = Reader { buf: body, pos: 5 };
+        let file_len = r.u64()?;
+        let content_hash = r.hash()?;
+        let entry_count = r.u64()?;
+        let gap_count = r.u64()?;
+        if entry_count > (body.len() / ENTRY_SIZE) as u64 {
+            return Err(invalid("entry count exceeds index size"));
+        }
+
+        let mut entries = Vec::with_capacity(entry_count as usize);
+        for _ in 0..entry_count {
+            entries.push(IndexEntry {
+                frame_id: r.u64()?,
+                offset: r.u64()?,
+                size: r.u32()?,
+                hash: r.hash()?,
+                confidence: f32::from_bits(r.u32()?),
+            });
+        }
+        let mut gaps = Vec::new();
+        for _ in 0..gap_count {
+            let before = r.u64()?;
+            let after = r.u64()?;
+            let expected_hash = match r.take(1)?[0] {
+                0 => None,
+                1 => Some(r.hash()?),
+                _ => return Err(invalid("bad gap record")),
+            };
+            gaps.push(SequenceGap {
+                before,
+                after,
+                expected_hash,
+            });
+        }
+        if r.pos != body.len() {
+            return Err(invalid("trailing bytes"));
+        }
+
+        Ok(Self {
+            file_len,
+            content_hash,
+            entries,
+            gaps,
+        })
+    }
+}
+
+/// Bounds-checked big-endian reader
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], FrameError> {
+        let out = self
+            .buf
+            .get(self.pos..self.pos + n)
+            .ok_or_else(|| FrameError::InvalidStructure("capture index: truncated".to_string()))?;
+        self.pos += n;
+        Ok(out)
+    }
+
+    fn u32(&mut self) -> Result<u32, FrameError> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn u64(&mut self) -> Result<u64, FrameError> {
+        let mut v = [0u8; 8];
+        v.copy_from_slice(self.take(8)?);
+        Ok(u64::from_be_bytes(v))
+    }
+
+    fn hash(&mut self) -> Result<[u8; BLAKE3_HASH_SIZE], FrameError> {
+        let mut v = [0u8; BLAKE3_HASH_SIZE];
+        v.copy_from_slice(self.take(BLAKE3_HASH_SIZE)?);
+        Ok(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoder::FrameBuilder;
+    use crate::test_util::{chain_from, encode_all};
+
+    /// CRC32C frames, frame 1 opening the capture
+    fn sample(id: u64, builder: FrameBuilder) -> FrameBuilder {
+        let builder = builder.with_crc32c();
+        if id == 1 {
+            builder.mark_first()
+        } else {
+            builder
+        }
+    }
+
+    #[test]
+    fn test_round_trip_bytes() {
+        let mut prev = [0u8; 32];
+        let mut data = encode_all(&chain_from(1..=3, &mut prev, sample)).0;
+        data.extend(encode_all(&chain_from(5..=6, &mut [7u8; 32], sample)).0);
+        let (index, frames) = CaptureIndex::build(&data);
+        assert_eq!(index.entries.len(), 5);
+        assert_eq!(index.gaps.len(), 1);
+        assert_eq!(frames.len(), 5);
+
+        let bytes = index.to_bytes();
+        assert_eq!(CaptureIndex::from_bytes(&bytes).unwrap(), index);
+
+        let mut corrupt = bytes.clone();
+        corrupt[10] ^= 1;
+        assert!(CaptureIndex::from_bytes(&corrupt).is_err());
+        assert!(CaptureIndex::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_refresh_reuses_fresh_index() {
+        let data = encode_all(&chain_from(1..=4, &mut [0u8; 32], sample)).0;
+        let (index, scanned) = CaptureIndex::build(&data);
+        let stored = index.to_bytes();
+
+        let (reopened, frames, how) = CaptureIndex::refresh(&data, Some(&stored));
+        assert_eq!(how, Refresh::Reused);
+        assert_eq!(reopened, index);
+        assert_eq!(frames.len(), scanned.len());
+        for (a, b) in frames.iter().zip(&scanned) {
+            assert_eq!((a.offset, a.size), (b.offset, b.size));
+            assert_eq!(a.frame.compute_hash(), b.frame.compute_hash());
+        }
+    }
+
+    #[test]
+    fn test_refresh_scans_only_appended_tail() {
+        let mut prev = [0u8; 32];
+        let mut data = encode_all(&chain_from(1..=3, &mut prev, sample)).0;
+        let (index, _) = CaptureIndex::build(&data);
+        let stored = index.to_bytes();
+
+        // A frame was cut in half at the old end of file, then the writer carried on
+        let tail = encode_all(&chain_from(4..=6, &mut prev, sample)).0;
+        let cut = data.len();
+        data.extend_from_slice(&tail[..10]);
+        let (partial, _) = CaptureIndex::build(&data);
+        let stored_partial = partial.to_bytes();
+        data.truncate(cut);
+        data.extend_from_slice(&tail);
+
+        let (extended, frames, how) = CaptureIndex::refresh(&data, Some(&stored));
+        assert_eq!(how, Refresh::Extended { tail_frames: 3 });
+        assert_eq!(frames.len(), 6);
+        assert!(extended.gaps.is_empty());
+        assert_eq!(extended.status(&data), IndexStatus::Fresh);
+
+        // The old end of file fell inside frame 4; resuming after frame 3 picks it up
+        let (_, frames, how) = CaptureIndex::refresh(&data, Some(&stored_partial));
+        assert_eq!(how, Refresh::Extended { tail_frames: 3 });
+        assert_eq!(frames.len(), 6);
+    }
+
+    #[test]
+    fn test_refresh_rebuilds_when_capture_changed() {
+        let mut data = encode_all(&chain_from(1..=3, &mut [0u8; 32], sample)).0;
+        let stored = CaptureIndex::build(&data).0.to_bytes();
+
+        let last = data.len() - 1;
+        data[last] ^= 0xFF;
+        let (index, frames, how) = CaptureIndex::refresh(&data, Some(&stored));
+        assert_eq!(how, Refresh::Rebuilt);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(index.status(&data), IndexStatus::Fresh);
+
+        let (_, _, how) = CaptureIndex::refresh(&data, Some(b"not an index"));
+        assert_eq!(how, Refresh::Rebuilt);
+        let (_, _, how) = CaptureIndex::refresh(&data[..10], None);
+        assert_eq!(how, Refresh::Rebuilt);
+    }
+}
@@ -23,6 +23,16 @@ pub const MAX_MARKER_HAMMING: u32 = 1;
 /// Current protocol version
 pub const PROTOCOL_VERSION: u8 = 1;
 
+/// Protocol version whose header is followed by a CRC32C over marker + header fields
+///
+/// The header CRC lets scanners reject a corrupted `payload_len`/flags before trusting
+/// them, and lets recovery tooling trust the frame ID and prev_hash of a frame whose
+/// payload is damaged.
+pub const HEADER_CRC_VERSION: u8 = 2;
+
+/// Size of the header CRC32C carried by `HEADER_CRC_VERSION` frames
+pub const HEADER_CRC_SIZE: usize = 4;
+
 /// Maximum frame size (16 MB)
 pub const MAX_FRAME_SIZE: u32 = 16 * 1024 * 1024;
 
@@ -39,6 +49,23 @@ pub const CRC32C_SIZE: usize = 4;
 /// 4 (marker) + 1 (version) + 8 (frame_id) + 32 (prev_hash) + 4 (payload_len) + 1 (flags) = 50 bytes
 pub const MIN_HEADER_SIZE: usize = 50;
 
+/// Largest marker + header size across supported versions (v2 adds the header CRC)
+pub const MAX_HEADER_SIZE: usize = MIN_HEADER_SIZE + HEADER_CRC_SIZE;
+
+/// Check whether a protocol version is understood by this implementation
+pub const fn is_supported_version(version: u8) -> bool {
+    version == PROTOCOL_VERSION || version == HEADER_CRC_VERSION
+}
+
+/// Size of marker + header (including the header CRC, if any) for a protocol version
+pub const fn header_size(version: u8) -> usize {
+    if version == HEADER_CRC_VERSION {
+        MAX_HEADER_SIZE
+    } else {
+        MIN_HEADER_SIZE
+    }
+}
+
 /// Trailer types
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TrailerType {
@@ -91,6 +118,11 @@ impl FrameFlags {
     pub const HAS_SYNC_PREFIX: u8 = 0b0010_0000;
 
     /// Frame encoded as a superframe (contains an index in payload)
+    ///
+    /// In-band parity frames set this bit too; their payload starts with a
+    /// `DPAR` descriptor (see [`crate::parity`] and [`Frame::is_index_superframe`]).
+    ///
+    /// [`Frame::is_index_superframe`]: crate::types::Frame::is_index_superframe
     pub const IS_SUPERFRAME: u8 = 0b0100_0000;
 
     /// Frame payload carries optional logarithmic skip-list backlinks
@@ -136,7 +168,7 @@ impl FrameFlags {
         (self.0 & Self::HAS_SYNC_PREFIX) != 0
     }
 
-    /// Check if this frame is a superframe
+    /// Check if the superframe bit is set (index superframe or in-band parity)
     pub const fn is_superframe(&self) -> bool {
         (self.0 & Self::IS_SUPERFRAME) != 0
     }
@@ -0,0 +1,442 @@
+//! Byte-level coverage map of a scanned capture
+//!
+//! The scanner reports what it recovered; [`DamageMap`] accounts for every
+//! byte of the input. Each byte belongs to exactly one [`Region`]: a valid
+//! frame, a frame whose header parsed but whose body did not, preamble or
+//! sync-word padding, or an unexplained range classified by its [`Content`]
+//! so a reader can tell wiped media (zero fill, erased flash) from noise or
+//! foreign data.
+
+use crate::constants::{MIN_PREAMBLE_LEN, PREAMBLE_PATTERN, ROBUST_SYNC_WORD};
+use crate::scanner::{scan_stream_with_damaged, DamagedFrame, LocatedFrame};
+use alloc::vec::Vec;
+
+/// Shortest run of 0x00 or 0xFF split out of an unexplained range as fill
+const MIN_FILL_RUN: usize = 16;
+
+/// Shortest printable run split out of an unexplained range as text
+const MIN_TEXT_RUN: usize = 16;
+
+/// Shortest range whose byte histogram is trusted to tell noise from binary data
+const MIN_NOISE_LEN: usize = 64;
+
+/// Chi-squared bound (255 degrees of freedom) below which a range counts as uniform noise
+const NOISE_CHI_SQUARED: f64 = 400.0;
+
+/// Share of printable ASCII needed to call a range text
+const TEXT_RATIO: f64 = 0.95;
+
+/// What an unexplained range looks like
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Content {
+    /// All 0x00: zeroed or never-written media
+    ZeroFill,
+    /// All 0xFF: erased flash
+    Erased,
+    /// Near-uniform byte distribution: noise, encrypted or compressed data
+    Noise,
+    /// Printable ASCII
+    Text,
+    /// Anything else
+    Binary,
+}
+
+/// What a range of the input holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionKind {
+    /// A frame that decoded and verified
+    Frame {
+        /// Frame ID from the header
+        frame_id: u64,
+    },
+    /// A frame whose header parsed but whose payload or trailer did not verify ([`DamagedFrame`])
+    Damaged {
+        /// Frame ID from the header
+        frame_id: u64,
+    },
+    /// Alternating 0x55/0xAA preamble
+    Preamble,
+    /// Robust sync word
+    SyncWord,
+    /// Bytes no frame or padding accounts for
+    Unexplained(Content),
+}
+
+impl RegionKind {
+    /// True for bytes that are not part of a valid frame or padding
+    pub fn is_loss(&self) -> bool {
+        matches!(
+            self,
+            RegionKind::Damaged { .. } | RegionKind::Unexplained(_)
+        )
+    }
+}
+
+/// A half-open byte range `start..end` of the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    /// First byte of the range
+    pub start: usize,
+    /// One past the last byte
+    pub end: usize,
+    /// What the range holds
+    pub kind: RegionKind,
+}
+
+impl Region {
+    /// Number of bytes in the range
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// True for a zero-length range (never produced by [`DamageMap`])
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// Byte totals per region class
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CoverageSummary {
+    /// Bytes in valid frames
+    pub frames: usize,
+    /// Bytes in damaged frames
+    pub damaged: usize,
+    /// Bytes of preamble and sync words
+    pub padding: usize,
+    /// Bytes nothing accounts for
+    pub unexplained: usize,
+}
+
+/// Ordered, gap-free regions covering the whole input
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DamageMap {
+    /// Length of the input in bytes
+    pub len: usize,
+    /// Regions in offset order; together they cover `0..len` exactly once
+    pub regions: Vec<Region>,
+}
+
+impl DamageMap {
+    /// Scan `data` and map every byte
+    pub fn build(data: &[u8]) -> Self {
+        let (located, damaged, _) = scan_stream_with_damaged(data);
+        Self::from_scan(data, &located, &damaged)
+    }
+
+    /// Map every byte of `data` given frames already scanned from it
+    ///
+    /// Where frames overlap, the earlier one keeps the shared bytes. A
+    /// damaged frame ends where the next valid frame starts: without a header
+    /// CRC its declared size may itself be damaged.
+    pub fn from_scan(data: &[u8], located: &[LocatedFrame], damaged: &[DamagedFrame]) -> Self {
+        let mut starts: Vec<usize> = located.iter().map(|lf| lf.offset).collect();
+        starts.sort_unstable();
+        let next_frame = |at: usize| {
+            let i = starts.partition_point(|&s| s <= at);
+            starts.get(i).copied().unwrap_or(usize::MAX)
+        };
+        let mut claimed: Vec<Region> = located
+            .iter()
+            .map(|lf| Region {
+                start: lf.offset,
+                // A bit-shifted frame spills into one more byte
+                end: lf.offset + lf.size + usize::from(lf.bit_shift != 0),
+                kind: RegionKind::Frame {
+                    frame_id: lf.frame.header.frame_id,
+                },
+            })
+            .chain(damaged.iter().map(|d| Region {
+                start: d.offset,
+                end: d.offset.saturating_add(d.size).min(next_frame(d.offset)),
+                kind: RegionKind::Damaged {
+                    frame_id: d.header.frame_id,
+                },
+            }))
+            .collect();
+        claimed.sort_by_key(|r| r.start);
+
+        let mut regions = Vec::new();
+        let mut pos = 0;
+        for mut r in claimed {
+            r.start = r.start.max(pos);
+            r.end = r.end.min(data.len());
+            if r.start >= r.end {
+                continue;
+            }
+            classify_range(data, pos, r.start, &mut regions);
+            regions.push(r);
+            pos = r.end;
+        }
+        classify_range(data, pos, data.len(), &mut regions);
+
+        DamageMap {
+            len: data.len(),
+            regions,
+        }
+    }
+
+    /// Regions that hold neither a valid frame nor padding
+    pub fn losses(&self) -> impl Iterator<Item = &Region> {
+        self.regions.iter().filter(|r| r.kind.is_loss())
+    }
+
+    /// Byte totals per region class
+    pub fn summary(&self) -> CoverageSummary {
+        let mut s = CoverageSummary::default();
+        for r in &self.regions {
+            let bucket = match r.kind {
+                RegionKind::Frame { .. } => &mut s.frames,
+                RegionKind::Damaged { .. } => &mut s.damaged,
+                RegionKind::Preamble | RegionKind::SyncWord => &mut s.padding,
+                RegionKind::Unexplained(_) => &mut s.unexplained,
+            };
+            *bucket += r.len();
+        }
+        s
+    }
+}
+
+/// Split `data[start..end]` into padding, fill runs and classified leftovers
+fn classify_range(data: &[u8], start: usize, end: usize, out: &mut Vec<Region>) {
+    let mut rest = start;
+    let mut i = start;
+    while i < end {
+        let found = if data[i..end].starts_with(ROBUST_SYNC_WORD) {
+            Some((ROBUST_SYNC_WORD.len(), RegionKind::SyncWord))
+        } else {
+            let preamble = preamble_len(&data[i..end]);
+            let fill = match data[i] {
+                0x00 | 0xFF => data[i..end].iter().take_while(|&&b| b == data[i]).count(),
+                _ => 0,
+            };
+            // 0x55 is printable, so stop text where a preamble begins
+            let text = (i..end)
+                .take_while(|&j| {
+                    is_printable(data[j])
+                        && (j == i || preamble_len(&data[j..end]) < MIN_PREAMBLE_LEN)
+                })
+                .count();
+            if preamble >= MIN_PREAMBLE_LEN {
+                Some((preamble, RegionKind::Preamble))
+            } else if fill >= MIN_FILL_RUN {
+                Some((
+                    fill,
+                    RegionKind::Unexplained(classify_content(&data[i..i + fill])),
+                ))
+            } else if text >= MIN_TEXT_RUN {
+                Some((text, RegionKind::Unexplained(Content::Text)))
+            } else {
+                None
+            }
+        };
+        match found {
+            Some((len, kind)) => {
+                push_unexplained(data, rest, i, out);
+                out.push(Region {
+                    start: i,
+                    end: i + len,
+                    kind,
+                });
+                i += len;
+                rest = i;
+            }
+            None => i += 1,
+        }
+    }
+    push_unexplained(data, rest, end, out);
+}
+
+/// Length of the alternating 0x55/0xAA run at the start of `bytes`, in either phase
+fn preamble_len(bytes: &[u8]) -> usize {
+    let Some(&first) = bytes.first() else {
+        return 0;
+    };
+    let phase = usize::from(first != PREAMBLE_PATTERN[0]);
+    bytes
+        .iter()
+        .enumerate()
+        .take_while(|&(k, &b)| b == PREAMBLE_PATTERN[(k + phase) % 2])
+        .count()
+}
+
+fn push_unexplained(data: &[u8], start: usize, end: usize, out: &mut Vec<Region>) {
+    if start < end {
+        out.push(Region {
+            start,
+            end,
+            kind: RegionKind::Unexplained(classify_content(&data[start..end])),
+        });
+    }
+}
+
+fn is_printable(b: u8) -> bool {
+    matches!(b, 0x20..=0x7E | b'\t' | b'\n' | b'\r')
+}
+
+/// Classify bytes by their value distribution
+pub fn classify_content(bytes: &[u8]) -> Content {
+    if bytes.iter().all(|&b| b == 0x00) {
+        return Content::ZeroFill;
+    }
+    if bytes.iter().all(|&b| b == 0xFF) {
+        return Content::Erased;
+    }
+    let printable = bytes.iter().filter(|&&b| is_printable(b)).count();
+    if printable as f64 >= bytes.len() as f64 * TEXT_RATIO {
+        return Content::Text;
+    }
+    if bytes.len() >= MIN_NOISE_LEN {
+        let mut counts = [0u32; 256];
+        for &b in bytes {
+            counts[b as usize] += 1;
+        }
+        let expected = bytes.len() as f64 / 256.0;
+        let chi_squared: f64 = counts
+            .iter()
+            .map(|&c| {
+                let d = c as f64 - expected;
+                d * d / expected
+            })
+            .sum();
+        if chi_squared < NOISE_CHI_SQUARED {
+            return Content::Noise;
+        }
+    }
+    Content::Binary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{MAX_HEADER_SIZE, MIN_HEADER_SIZE};
+    use crate::encoder::FrameBuilder;
+    use alloc::{format, vec};
+    use bytes::Bytes;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn frame(id: u64) -> Bytes {
+        FrameBuilder::new(id)
+            .payload(Bytes::from(format!("sample {}", id)))
+            .with_header_crc()
+            .with_blake3()
+            .build()
+            .unwrap()
+    }
+
+    fn assert_contiguous(map: &DamageMap) {
+        let mut pos = 0;
+        for r in &map.regions {
+            assert_eq!(r.start, pos, "{:?}", map.regions);
+            assert!(!r.is_empty());
+            pos = r.end;
+        }
+        assert_eq!(pos, map.len);
+    }
+
+    #[test]
+    fn test_map_accounts_for_every_byte() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut noise = [0u8; 512];
+        rng.fill(&mut noise[..]);
+
+        let mut data = Vec::new();
+        let f1 = frame(1);
+        data.extend_from_slice(&f1);
+        data.extend_from_slice(&[0u8; 40]);
+        data.extend_from_slice(&[0xFFu8; 40]);
+        data.extend_from_slice(&noise);
+        data.extend_from_slice(b"log: sensor offline, retrying in 5s\n");
+        let pad_at = data.len();
+        for k in 0..MIN_PREAMBLE_LEN {
+            data.push(PREAMBLE_PATTERN[k % 2]);
+        }
+        data.extend_from_slice(ROBUST_SYNC_WORD);
+        let f2_at = data.len();
+        data.extend_from_slice(&frame(2));
+        let f3_at = data.len();
+        data.extend_from_slice(&frame(3));
+        // Damage frame 3's payload; its verified header still identifies it
+        data[f3_at + MAX_HEADER_SIZE + 1] ^= 0x01;
+
+        let map = DamageMap::build(&data);
+        assert_contiguous(&map);
+        let kinds: Vec<RegionKind> = map.regions.iter().map(|r| r.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                RegionKind::Frame { frame_id: 1 },
+                RegionKind::Unexplained(Content::ZeroFill),
+                RegionKind::Unexplained(Content::Erased),
+                RegionKind::Unexplained(Content::Noise),
+                RegionKind::Unexplained(Content::Text),
+                RegionKind::Preamble,
+                RegionKind::SyncWord,
+                RegionKind::Frame { frame_id: 2 },
+                RegionKind::Damaged { frame_id: 3 },
+            ]
+        );
+        assert_eq!(map.regions[5].start, pad_at);
+        assert_eq!(map.regions[7].start, f2_at);
+
+        let s = map.summary();
+        assert_eq!(s.padding, MIN_PREAMBLE_LEN + ROBUST_SYNC_WORD.len());
+        assert_eq!(s.damaged, data.len() - f3_at);
+        assert_eq!(s.frames + s.damaged + s.padding + s.unexplained, data.len());
+        assert_eq!(map.losses().count(), 5);
+    }
+
+    #[test]
+    fn test_version_1_damaged_frame_is_identified() {
+        let v1 = |id: u64| {
+            FrameBuilder::new(id)
+                .payload(Bytes::from(format!("sample {}", id)))
+                .with_crc32c()
+                .build()
+                .unwrap()
+        };
+        let mut data = v1(1).to_vec();
+        let f2_at = data.len();
+        data.extend_from_slice(&v1(2));
+        let f3_at = data.len();
+        data.extend_from_slice(&v1(3));
+        // Without a header CRC, the failed trailer is what identifies frame 2
+        data[f2_at + MIN_HEADER_SIZE + 1] ^= 0x01;
+
+        let map = DamageMap::build(&data);
+        assert_contiguous(&map);
+        let kinds: Vec<RegionKind> = map.regions.iter().map(|r| r.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                RegionKind::Frame { frame_id: 1 },
+                RegionKind::Damaged { frame_id: 2 },
+                RegionKind::Frame { frame_id: 3 },
+            ]
+        );
+        assert_eq!(map.summary().damaged, f3_at - f2_at);
+        assert_eq!(map.summary().unexplained, 0);
+    }
+
+    #[test]
+    fn test_truncated_and_empty_inputs() {
+        assert_eq!(DamageMap::build(&[]).regions, vec![]);
+
+        let f = frame(1);
+        let cut = &f[..f.len() - 4];
+        let map = DamageMap::build(cut);
+        assert_contiguous(&map);
+        assert_eq!(map.regions.len(), 1);
+        assert_eq!(map.regions[0].kind, RegionKind::Damaged { frame_id: 1 });
+    }
+
+    #[test]
+    fn test_classify_content() {
+        assert_eq!(classify_content(&[0; 3]), Content::ZeroFill);
+        assert_eq!(classify_content(&[0xFF; 3]), Content::Erased);
+        assert_eq!(classify_content(b"plain words"), Content::Text);
+        // Structured binary is skewed far from uniform
+        let table: Vec<u8> = (0..256u32).flat_map(|i| [0, 0, 1, i as u8]).collect();
+        assert_eq!(classify_content(&table), Content::Binary);
+    }
+}
@@ -1,7 +1,9 @@
 //! Frame decoding (strict mode)
 
+#[cfg(feature = "std")]
+use crate::constants::{header_size, MAX_HEADER_SIZE};
 use crate::constants::{
-    FrameFlags, TrailerType, FRAME_MARKER, MAX_FRAME_SIZE, MIN_HEADER_SIZE, PROTOCOL_VERSION,
+    is_supported_version, FrameFlags, TrailerType, FRAME_MARKER, MAX_FRAME_SIZE, MIN_HEADER_SIZE,
 };
 use crate::error::FrameError;
 use crate::types::{Frame, FrameHeader};
@@ -9,11 +11,91 @@ use bytes::Bytes;
 #[cfg(feature = "std")]
 use std::io::{ErrorKind, Read};
 
+/// Decode and check a frame header (marker + header fields + optional header CRC)
+///
+/// `buf` must start at the marker. Only the header is examined; the payload and
+/// trailer are not required to be present. For `HEADER_CRC_VERSION` headers the
+/// CRC is verified, so a successful return means the frame ID, prev_hash,
+/// payload length and flags are trustworthy even if the payload is damaged.
+pub fn decode_header(buf: &[u8]) -> Result<FrameHeader, FrameError> {
+    if buf.len() < MIN_HEADER_SIZE {
+        return Err(FrameError::IncompleteFrame {
+            expected: MIN_HEADER_SIZE,
+            actual: buf.len(),
+        });
+    }
+
+    // Validate marker
+    if &buf[0..4] != FRAME_MARKER {
+        let mut bad = [0u8; 4];
+        bad.copy_from_slice(&buf[0..4]);
+        return Err(FrameError::BadMarker(bad));
+    }
+
+    // Header view (excluding marker)
+    let header_bytes = &buf[4..MIN_HEADER_SIZE];
+
+    let version = header_bytes[0];
+    if !is_supported_version(version) {
+        return Err(FrameError::UnsupportedVersion(version));
+    }
+
+    let frame_id = u64::from_be_bytes([
+        header_bytes[1],
+        header_bytes[2],
+        header_bytes[3],
+        header_bytes[4],
+        header_bytes[5],
+        header_bytes[6],
+        header_bytes[7],
+        header_bytes[8],
+    ]);
+
+    let mut prev_hash = [0u8; 32];
+    prev_hash.copy_from_slice(&header_bytes[9..41]);
+
+    let payload_len = u32::from_be_bytes([
+        header_bytes[41],
+        header_bytes[42],
+        header_bytes[43],
+        header_bytes[44],
+    ]);
+
+    let flags = FrameFlags::new(header_bytes[45]);
+
+    let mut header = FrameHeader::with_flags(frame_id, prev_hash, payload_len, flags);
+    header.version = version;
+
+    // Verify the header CRC before anyone trusts payload_len/flags
+    if header.has_header_crc() {
+        let size = header.encoded_len();
+        if buf.len() < size {
+            return Err(FrameError::IncompleteFrame {
+                expected: size,
+                actual: buf.len(),
+            });
+        }
+        let expected = u32::from_be_bytes([
+            buf[MIN_HEADER_SIZE],
+            buf[MIN_HEADER_SIZE + 1],
+            buf[MIN_HEADER_SIZE + 2],
+            buf[MIN_HEADER_SIZE + 3],
+        ]);
+        let actual = crc32c::crc32c(&buf[..MIN_HEADER_SIZE]);
+        if actual != expected {
+            return Err(FrameError::HeaderChecksumMismatch { expected, actual });
+        }
+    }
+
+    Ok(header)
+}
+
 /// Decode a frame from a reader
 ///
 /// This function performs strict validation:
 /// - Validates marker
 /// - Validates version
+/// - Validates the header CRC if present
 /// - Validates length
 /// - Validates checksum/hash if present
 ///
@@ -21,60 +103,52 @@ use std::io::{ErrorKind, Read};
 #[cfg(feature = "std")]
 pub fn decode_frame<R: Read>(reader: &mut R) -> Result<Frame, FrameError> {
     // Read and validate marker
-    let mut marker = [0u8; 4];
-    reader.read_exact(&mut marker)?;
+    let mut head = [0u8; MAX_HEADER_SIZE];
+    reader.read_exact(&mut head[..4])?;
 
-    if &marker != FRAME_MARKER {
+    if &head[..4] != FRAME_MARKER {
+        let mut marker = [0u8; 4];
+        marker.copy_from_slice(&head[..4]);
         return Err(FrameError::BadMarker(marker));
     }
 
-    // Read header
-    let mut header_buf = [0u8; MIN_HEADER_SIZE - 4]; // Minus marker
-    reader.read_exact(&mut header_buf)?;
-
-    let version = header_buf[0];
-    if version != PROTOCOL_VERSION {
+    // Read header (plus header CRC for versions that carry one)
+    reader.read_exact(&mut head[4..MIN_HEADER_SIZE])?;
+    let version = head[4];
+    if !is_supported_version(version) {
         return Err(FrameError::UnsupportedVersion(version));
     }
+    let head_len = header_size(version);
+    reader.read_exact(&mut head[MIN_HEADER_SIZE..head_len])?;
 
-    let frame_id = u64::from_be_bytes([
-        header_buf[1],
-        header_buf[2],
-        header_buf[3],
-        header_buf[4],
-        header_buf[5],
-        header_buf[6],
-        header_buf[7],
-        header_buf[8],
-    ]);
-
-    let mut prev_hash = [0u8; 32];
-    prev_hash.copy_from_slice(&header_buf[9..41]);
-
-    let payload_len = u32::from_be_bytes([
-        header_buf[41],
-        header_buf[42],
-        header_buf[43],
-        header_buf[44],
-    ]);
-
-    let flags = FrameFlags::new(header_buf[45]);
+    let header = decode_header(&head[..head_len])?;
+    let payload_len = header.payload_len;
+    let flags = header.flags;
 
     // Validate payload length
     let total_frame_size =
-        MIN_HEADER_SIZE as u32 + payload_len + flags.trailer_type().size() as u32;
-    if total_frame_size > MAX_FRAME_SIZE {
-        return Err(FrameError::FrameTooLarge(total_frame_size, MAX_FRAME_SIZE));
+        head_len as u64 + payload_len as u64 + flags.trailer_type().size() as u64;
+    if total_frame_size > MAX_FRAME_SIZE as u64 {
+        return Err(FrameError::FrameTooLarge(
+            total_frame_size.min(u32::MAX as u64) as u32,
+            MAX_FRAME_SIZE,
+        ));
     }
 
-    // Create header
-    let header = FrameHeader::with_flags(frame_id, prev_hash, payload_len, flags);
     header.validate()?;
 
     // Read payload
     let mut payload = vec![0u8; payload_len as usize];
     reader.read_exact(&mut payload)?;
 
+    // Trailers cover marker + header (+ header CRC) + payload
+    let covered = || {
+        let mut data = Vec::with_capacity(head_len + payload.len());
+        data.extend_from_slice(&head[..head_len]);
+        data.extend_from_slice(&payload);
+        data
+    };
+
     // Read and validate trailer if present
     let trailer_type = flags.trailer_type();
     let trailer = match trailer_type {
@@ -84,13 +158,7 @@ pub fn decode_frame<R: Read>(reader: &mut R) -> Result<Frame, FrameError> {
             reader.read_exact(&mut expected_checksum)?;
             let expected = u32::from_be_bytes(expected_checksum);
 
-            // Compute checksum over marker + header + payload
-            let mut data = Vec::with_capacity(MIN_HEADER_SIZE + payload.len());
-            data.extend_from_slice(FRAME_MARKER);
-            data.extend_from_slice(&header_buf);
-            data.extend_from_slice(&payload);
-
-            let actual = crc32c::crc32c(&data);
+            let actual = crc32c::crc32c(&covered());
 
             if actual != expected {
                 return Err(FrameError::ChecksumMismatch { expected, actual });
@@ -102,13 +170,7 @@ pub fn decode_frame<R: Read>(reader: &mut R) -> Result<Frame, FrameError> {
             let mut expected_hash = [0u8; 32];
             reader.read_exact(&mut expected_hash)?;
 
-            // Compute hash over marker + header + payload
-            let mut data = Vec::with_capacity(MIN_HEADER_SIZE + payload.len());
-            data.extend_from_slice(FRAME_MARKER);
-            data.extend_from_slice(&header_buf);
-            data.extend_from_slice(&payload);
-
-            let actual_hash = blake3::hash(&data);
+            let actual_hash = blake3::hash(&covered());
 
             if actual_hash.as_bytes() != &expected_hash {
                 return Err(FrameError::HashMismatch);
@@ -123,12 +185,7 @@ pub fn decode_frame<R: Read>(reader: &mut R) -> Result<Frame, FrameError> {
             let mut sig_bytes = [0u8; 64];
             reader.read_exact(&mut sig_bytes)?;
 
-            // Compute hash over marker + header + payload
-            let mut data = Vec::with_capacity(MIN_HEADER_SIZE + payload.len());
-            data.extend_from_slice(FRAME_MARKER);
-            data.extend_from_slice(&header_buf);
-            data.extend_from_slice(&payload);
-            let actual_hash = blake3::hash(&data);
+            let actual_hash = blake3::hash(&covered());
 
             if actual_hash.as_bytes() != &expected_hash {
                 return Err(FrameError::HashMismatch);
@@ -168,55 +225,14 @@ pub fn decode_frame_from_bytes(data: &[u8]) -> Result<Frame, FrameError> {
 /// (marker + header + payload + optional trailer). The returned
 /// `Frame` will borrow slices from `buf` for payload/trailer.
 pub fn decode_frame_from_bytes_zero_copy(buf: Bytes) -> Result<Frame, FrameError> {
-    // Sanity: minimum header
-    if buf.len() < MIN_HEADER_SIZE {
-        return Err(FrameError::IncompleteFrame {
-            expected: MIN_HEADER_SIZE,
-            actual: buf.len(),
-        });
-    }
-
-    // Validate marker
-    if &buf[0..4] != FRAME_MARKER {
-        let mut bad = [0u8; 4];
-        bad.copy_from_slice(&buf[0..4]);
-        return Err(FrameError::BadMarker(bad));
-    }
-
-    // Header view (excluding marker)
-    let header_bytes = &buf[4..MIN_HEADER_SIZE];
-
-    let version = header_bytes[0];
-    if version != PROTOCOL_VERSION {
-        return Err(FrameError::UnsupportedVersion(version));
-    }
-
-    let frame_id = u64::from_be_bytes([
-        header_bytes[1],
-        header_bytes[2],
-        header_bytes[3],
-        header_bytes[4],
-        header_bytes[5],
-        header_bytes[6],
-        header_bytes[7],
-        header_bytes[8],
-    ]);
-
-    let mut prev_hash = [0u8; 32];
-    prev_hash.copy_from_slice(&header_bytes[9..41]);
-
-    let payload_len = u32::from_be_bytes([
-        header_bytes[41],
-        header_bytes[42],
-        header_bytes[43],
-        header_bytes[44],
-    ]);
-
-    let flags = FrameFlags::new(header_bytes[45]);
+    let header = decode_header(&buf)?;
+    let payload_len = header.payload_len;
+    let flags = header.flags;
+    let head_len = header.encoded_len();
 
     // Validate lengths and compute total size
     let trailer_size = flags.trailer_type().size();
-    let total_frame_size = MIN_HEADER_SIZE + payload_len as usize + trailer_size;
+    let total_frame_size = head_len + payload_len as usize + trailer_size;
     if total_frame_size > MAX_FRAME_SIZE as usize {
         return Err(FrameError::FrameTooLarge(
             total_frame_size as u32,
@@ -230,12 +246,10 @@ pub fn decode_frame_from_bytes_zero_copy(buf: Bytes) -> Result<Frame, FrameError
         });
     }
 
-    // Build header and validate
-    let header = FrameHeader::with_flags(frame_id, prev_hash, payload_len, flags);
     header.validate()?;
 
     // Slice payload and trailer
-    let payload_start = MIN_HEADER_SIZE;
+    let payload_start = head_len;
     let payload_end = payload_start + payload_len as usize;
     let trailer_start = payload_end;
     let trailer_end = trailer_start + trailer_size;
@@ -317,8 +331,8 @@ pub fn try_decode_frame<R: Read>(reader: &mut R) -> Result<(Frame, usize), Frame
     let mut bytes_read = 0;
 
     // Read marker
-    let mut marker = [0u8; 4];
-    match reader.read_exact(&mut marker) {
+    let mut head = [0u8; MAX_HEADER_SIZE];
+    match reader.read_exact(&mut head[..4]) {
         Ok(_) => bytes_read += 4,
         Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
             return Err(FrameError::IncompleteFrame {
@@ -329,24 +343,22 @@ pub fn try_decode_frame<R: Read>(reader: &mut R) -> Result<(Frame, usize), Frame
         Err(e) => return Err(e.into()),
     }
 
-    if &marker != FRAME_MARKER {
+    if &head[..4] != FRAME_MARKER {
+        let mut marker = [0u8; 4];
+        marker.copy_from_slice(&head[..4]);
         return Err(FrameError::BadMarker(marker));
     }
 
-    // Read header
-    let mut header_buf = [0u8; MIN_HEADER_SIZE - 4];
-    reader.read_exact(&mut header_buf)?;
-    bytes_read += MIN_HEADER_SIZE - 4;
-
-    let payload_len = u32::from_be_bytes([
-        header_buf[41],
-        header_buf[42],
-        header_buf[43],
-        header_buf[44],
-    ]);
+    // Read header (plus header CRC for versions that carry one)
+    reader.read_exact(&mut head[4..MIN_HEADER_SIZE])?;
+    let head_len = header_size(head[4]);
+    reader.read_exact(&mut head[MIN_HEADER_SIZE..head_len])?;
+    bytes_read += head_len - 4;
 
-    let flags = FrameFlags::new(header_buf[45]);
-    let trailer_size = flags.trailer_type().size();
+    // Check the header before trusting payload_len
+    let header = decode_header(&head[..head_len])?;
+    let payload_len = header.payload_len;
+    let trailer_size = header.flags.trailer_type().size();
 
     // Read payload
     let mut payload = vec![0u8; payload_len as usize];
@@ -362,8 +374,7 @@ pub fn try_decode_frame<R: Read>(reader: &mut R) -> Result<(Frame, usize), Frame
 
     // Now decode the complete frame
     let mut all_data = Vec::with_capacity(bytes_read);
-    all_data.extend_from_slice(&marker);
-    all_data.extend_from_slice(&header_buf);
+    all_data.extend_from_slice(&head[..head_len]);
     all_data.extend_from_slice(&payload);
     all_data.extend_from_slice(&trailer);
 
@@ -432,4 +443,53 @@ mod tests {
         assert_eq!(decoded.header.prev_hash, [1u8; 32]);
         assert_eq!(decoded.payload.as_ref(), payload);
     }
+
+    #[test]
+    fn test_header_crc_round_trip() {
+        use crate::constants::{HEADER_CRC_VERSION, MAX_HEADER_SIZE};
+        let payload = b"header protected";
+        let mut header = FrameHeader::with_flags(
+            9,
+            [7u8; 32],
+            payload.len() as u32,
+            FrameFlags::new(FrameFlags::HAS_CRC32C),
+        );
+        header.version = HEADER_CRC_VERSION;
+
+        let encoded = encode_frame(&header, payload).unwrap();
+        assert_eq!(encoded.len(), MAX_HEADER_SIZE + payload.len() + 4);
+
+        let decoded = decode_frame_from_bytes(&encoded).unwrap();
+        assert_eq!(decoded.header, header);
+        assert_eq!(decoded.total_size(), encoded.len());
+
+        let zc = decode_frame_from_bytes_zero_copy(encoded.clone()).unwrap();
+        assert_eq!(zc.payload.as_ref(), payload);
+
+        #[cfg(feature = "std")]
+        {
+            let (_, consumed) = try_decode_frame(&mut std::io::Cursor::new(&encoded[..])).unwrap();
+            assert_eq!(consumed, encoded.len());
+        }
+    }
+
+    #[test]
+    fn test_header_crc_rejects_corrupted_length() {
+        use crate::constants::HEADER_CRC_VERSION;
+        let payload = b"abc";
+        let mut header = FrameHeader::new(1, [0u8; 32], payload.len() as u32);
+        header.version = HEADER_CRC_VERSION;
+        let mut encoded = encode_frame(&header, payload).unwrap().to_vec();
+
+        // Flip a bit in payload_len
+        encoded[45] ^= 0x10;
+        assert!(matches!(
+            decode_header(&encoded),
+            Err(FrameError::HeaderChecksumMismatch { .. })
+        ));
+        assert!(matches!(
+            decode_frame_from_bytes(&encoded),
+            Err(FrameError::HeaderChecksumMismatch { .. })
+        ));
+    }
 }
@@ -1,6 +1,8 @@
 //! Frame encoding
 
-use crate::constants::{FrameFlags, TrailerType, FRAME_MARKER, MIN_HEADER_SIZE};
+use crate::constants::{
+    FrameFlags, TrailerType, FRAME_MARKER, HEADER_CRC_VERSION, PROTOCOL_VERSION,
+};
 use crate::error::FrameError;
 use crate::types::{Frame, FrameHeader};
 use alloc::format;
@@ -16,6 +18,7 @@ use bytes::{BufMut, Bytes, BytesMut};
 ///    - Previous hash (32 bytes)
 ///    - Payload length (4 bytes, big-endian)
 ///    - Flags (1 byte)
+///    - Header CRC32C (4 bytes, big-endian; only for `HEADER_CRC_VERSION` headers)
 /// 3. Payload (variable length)
 /// 4. Trailer (optional, CRC32C or BLAKE3)
 pub fn encode_frame(header: &FrameHeader, payload: &[u8]) -> Result<Bytes, FrameError> {
@@ -42,7 +45,7 @@ pub fn encode_frame(header: &FrameHeader, payload: &[u8]) -> Result<Bytes, Frame
         prefix_len += crate::constants::MIN_PREAMBLE_LEN;
     }
 
-    let total_size = prefix_len + MIN_HEADER_SIZE + payload.len() + trailer_size;
+    let total_size = prefix_len + header.encoded_len() + payload.len() + trailer_size;
 
     let mut buf = BytesMut::with_capacity(total_size);
 
@@ -68,23 +71,26 @@ pub fn encode_frame(header: &FrameHeader, payload: &[u8]) -> Result<Bytes, Frame
     buf.put_slice(&header.prev_hash);
     buf.put_u32(header.payload_len);
     buf.put_u8(header.flags.as_u8());
+    if header.has_header_crc() {
+        buf.put_u32(header.compute_header_crc());
+    }
 
     // Write payload
     buf.put_slice(payload);
 
-    // Write trailer if needed
+    // Write trailer if needed (covers marker + header + payload, not the sync/preamble prefix)
     match trailer_type {
         TrailerType::None => {}
         TrailerType::Crc32c => {
-            let checksum = compute_crc32c(&buf);
+            let checksum = compute_crc32c(&buf[prefix_len..]);
             buf.put_u32(checksum);
         }
         TrailerType::Blake3 => {
-            let hash = compute_blake3(&buf);
+            let hash = compute_blake3(&buf[prefix_len..]);
             buf.put_slice(&hash);
         }
         TrailerType::Blake3WithEd25519Sig => {
-            let hash = compute_blake3(&buf);
+            let hash = compute_blake3(&buf[prefix_len..]);
             buf.put_slice(&hash);
             #[cfg(feature = "ed25519-signatures")]
             {
@@ -108,6 +114,30 @@ pub fn encode_frame_struct(frame: &Frame) -> Result<Bytes, FrameError> {
     encode_frame(&frame.header, &frame.payload)
 }
 
+/// Encode a frame as it was read from a stream, keeping its stored trailer
+///
+/// [`encode_frame_struct`] recomputes the trailer, which zeroes an Ed25519
+/// signature; here a decoded frame's trailer is written back verbatim, so the
+/// result matches the original bytes. Frames without a stored trailer encode
+/// as with [`encode_frame_struct`].
+pub fn encode_frame_struct_exact(frame: &Frame) -> Result<Bytes, FrameError> {
+    let encoded = encode_frame(&frame.header, &frame.payload)?;
+    let size = frame.header.flags.trailer_type().size();
+    match frame
+        .trailer
+        .as_ref()
+        .filter(|t| size > 0 && t.len() == size)
+    {
+        Some(tr) => {
+            let mut v = encoded.to_vec();
+            let at = v.len() - size;
+            v[at..].copy_from_slice(tr);
+            Ok(Bytes::from(v))
+        }
+        None => Ok(encoded),
+    }
+}
+
 /// Encode a frame into bytes with Ed25519 signature when combined trailer is requested
 #[cfg(feature = "ed25519-signatures")]
 pub fn encode_frame_signed(
@@ -119,7 +149,7 @@ pub fn encode_frame_signed(
     if header.flags.trailer_type() == TrailerType::Blake3WithEd25519Sig {
         use ed25519_dalek::Signer;
         // Compute signature over marker+header+payload (without trailer)
-        let mut msg = BytesMut::with_capacity(MIN_HEADER_SIZE + payload.len());
+        let mut msg = BytesMut::with_capacity(header.encoded_len() + payload.len());
         msg.extend_from_slice(FRAME_MARKER);
         msg.extend_from_slice(&[header.version]);
         msg.extend_from_slice(&header.frame_id.to_be_bytes());
@@ -182,7 +212,7 @@ pub fn compute_chain_hash(frame: &Frame, prev_trailer: Option<&[u8]>) -> [u8; 32
 #[cfg(feature = "ed25519-signatures")]
 pub fn ed25519_sign_header_payload(frame: &Frame, sk: &ed25519_dalek::SigningKey) -> [u8; 64] {
     use ed25519_dalek::Signer;
-    let mut buf = BytesMut::with_capacity(MIN_HEADER_SIZE + frame.payload.len());
+    let mut buf = BytesMut::with_capacity(frame.header.encoded_len() + frame.payload.len());
     buf.extend_from_slice(FRAME_MARKER);
     buf.extend_from_slice(&[frame.header.version]);
     buf.extend_from_slice(&frame.header.frame_id.to_be_bytes());
@@ -202,6 +232,7 @@ pub struct FrameBuilder {
     prev_hash: [u8; 32],
     payload: Bytes,
     flags: u8,
+    version: u8,
 }
 
 impl FrameBuilder {
@@ -212,6 +243,7 @@ impl FrameBuilder {
             prev_hash: [0u8; 32],
             payload: Bytes::new(),
             flags: FrameFlags::NONE,
+            version: PROTOCOL_VERSION,
         }
     }
 
@@ -239,6 +271,12 @@ impl FrameBuilder {
         self
     }
 
+    /// Protect the header with a CRC32C (emits a `HEADER_CRC_VERSION` header)
+    pub fn with_header_crc(mut self) -> Self {
+        self.version = HEADER_CRC_VERSION;
+        self
+    }
+
     /// Mark as first frame
     pub fn mark_first(mut self) -> Self {
         self.flags |= FrameFlags::IS_FIRST;
@@ -253,11 +291,25 @@ impl FrameBuilder {
     }
 
     /// Mark this frame as a superframe (payload should carry an index)
+    ///
+    /// The payload must not start with a valid parity descriptor, or readers
+    /// take the frame for in-band parity ([`Frame::is_index_superframe`]).
     pub fn as_superframe(mut self) -> Self {
         self.flags |= FrameFlags::IS_SUPERFRAME;
         self
     }
 
+    /// Make this an in-band parity frame carrying `shard` under `desc`
+    ///
+    /// Sets `IS_SUPERFRAME` and replaces the payload with the descriptor
+    /// followed by the shard (see [`crate::parity`]); the descriptor is what
+    /// tells the frame apart from an index superframe.
+    pub fn as_parity(mut self, desc: &crate::parity::ParityDescriptor, shard: &[u8]) -> Self {
+        self.flags |= FrameFlags::IS_SUPERFRAME;
+        self.payload = desc.payload(shard);
+        self
+    }
+
     /// Indicate that payload includes skip-list backlinks
     pub fn with_skiplist(mut self) -> Self {
         self.flags |= FrameFlags::HAS_SKIPLIST;
@@ -281,24 +333,26 @@ impl FrameBuilder {
 
     /// Build and encode the frame
     pub fn build(self) -> Result<Bytes, FrameError> {
-        let header = FrameHeader::with_flags(
+        let mut header = FrameHeader::with_flags(
             self.frame_id,
             self.prev_hash,
             self.payload.len() as u32,
             FrameFlags::new(self.flags),
         );
+        header.version = self.version;
 
         encode_frame(&header, &self.payload)
     }
 
     /// Build the frame struct without encoding
     pub fn build_struct(self) -> Result<Frame, FrameError> {
-        let header = FrameHeader::with_flags(
+        let mut header = FrameHeader::with_flags(
             self.frame_id,
             self.prev_hash,
             self.payload.len() as u32,
             FrameFlags::new(self.flags),
         );
+        header.version = self.version;
 
         header.validate()?;
 
@@ -309,6 +363,7 @@ impl FrameBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::constants::MIN_HEADER_SIZE;
 
     #[test]
     fn test_encode_simple_frame() {
@@ -49,6 +49,18 @@ pub enum FrameError {
         actual: u32,
     },
 
+    /// Header CRC mismatch (header fields cannot be trusted)
+    #[cfg_attr(
+        feature = "std",
+        error("Header checksum mismatch: expected {expected:x}, got {actual:x}")
+    )]
+    HeaderChecksumMismatch {
+        /// The header CRC stored in the frame.
+        expected: u32,
+        /// The header CRC calculated over marker and header fields.
+        actual: u32,
+    },
+
     /// Hash mismatch
     #[cfg_attr(feature = "std", error("Hash mismatch"))]
     HashMismatch,
@@ -0,0 +1,564 @@
+//! Self-contained HTML rendering of timeline reports
+//!
+//! [`crate::linker::report_to_dot`] needs an external renderer and stops being
+//! readable after a few hundred frames. The pages produced here are a single
+//! file with inline styles and script and no network assets: a zoomable
+//! timeline strip with gaps, conflicts, equivocations and orphans
+//! highlighted, followed by tables of frames (with capture offset and
+//! confidence where known), gaps, conflicts and recovery recipes.
+
+use crate::linker::{
+    ChainConflict, Completeness, Equivocation, FlagInconsistency, GapDetail, GapReason,
+    OrphanCluster, RecoveryRecipe, SequenceEnd, TimelineIndexReport, TimelineReport, TimelineStats,
+};
+use crate::scanner::LocatedFrame;
+use crate::types::Frame;
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write as _;
+
+/// Widest slot a single gap takes on the strip, in frame widths
+const MAX_GAP_SLOTS: u64 = 8;
+
+/// One frame as shown in the report
+struct Row<'a> {
+    frame: &'a Frame,
+    /// Capture offset, size and confidence, when the frame was scanned
+    located: Option<(usize, usize, f32)>,
+}
+
+/// Everything the page shows, borrowed from either report type
+struct Page<'a> {
+    title: &'a str,
+    chain: Vec<Row<'a>>,
+    orphans: Vec<Row<'a>>,
+    stats: TimelineStats,
+    gap_details: &'a [GapDetail],
+    conflicts: &'a [ChainConflict],
+    equivocations: &'a [Equivocation],
+    renumbered: BTreeSet<u64>,
+    orphan_clusters: &'a [OrphanCluster],
+    recipes: &'a [RecoveryRecipe],
+    completeness: &'a Completeness,
+}
+
+/// Render a TimelineReport as a standalone HTML page
+pub fn report_to_html(report: &TimelineReport, title: &str) -> String {
+    let row = |frame| Row {
+        frame,
+        located: None,
+    };
+    render_html(&Page {
+        title,
+        chain: report.timeline.frames.iter().map(row).collect(),
+        orphans: report.timeline.orphans.iter().map(row).collect(),
+        stats: report.timeline.stats(),
+        gap_details: &report.gap_details,
+        conflicts: &report.conflicts,
+        equivocations: &report.equivocations,
+        renumbered: BTreeSet::new(),
+        orphan_clusters: &report.orphan_clusters,
+        recipes: &report.recipes,
+        completeness: &report.completeness,
+    })
+}
+
+/// Render a TimelineIndexReport over scanned frames as a standalone HTML page
+///
+/// Unlike [`report_to_html`], each frame shows where it was found in the
+/// capture and the scanner's confidence in it.
+pub fn index_report_to_html(
+    report: &TimelineIndexReport,
+    source: &[LocatedFrame],
+    title: &str,
+) -> String {
+    let row = |&i: &usize| {
+        let lf = &source[i];
+        Row {
+            frame: &lf.frame,
+            located: Some((lf.offset, lf.size, lf.confidence)),
+        }
+    };
+    render_html(&Page {
+        title,
+        chain: report.index.frames.iter().map(row).collect(),
+        orphans: report.index.orphans.iter().map(row).collect(),
+        stats: report.index.stats(),
+        gap_details: &report.gap_details,
+        conflicts: &report.conflicts,
+        equivocations: &report.equivocations,
+        renumbered: report
+            .index
+            .renumbered
+            .iter()
+            .map(|&i| source[i].frame.header.frame_id)
+            .collect(),
+        orphan_clusters: &report.orphan_clusters,
+        recipes: &report.recipes,
+        completeness: &report.completeness,
+    })
+}
+
+/// Escape text for HTML element content and quoted attributes
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn hex_prefix(bytes: &[u8]) -> String {
+    let mut s = String::new();
+    for b in bytes.iter().take(6) {
+        let _ = write!(&mut s, "{:02x}", b);
+    }
+    s
+}
+
+/// Fill colour for a frame cell: red through green by confidence
+fn confidence_color(confidence: Option<f32>) -> String {
+    match confidence {
+        Some(c) => alloc::format!("hsl({:.0},65%,42%)", c.clamp(0.0, 1.0) * 120.0),
+        None => String::from("#3f6fb5"),
+    }
+}
+
+/// Strip slots a gap occupies: one per missing ID, capped
+fn gap_slots(gd: &GapDetail) -> u64 {
+    match gd.reason {
+        GapReason::MissingById => gd
+            .gap
+            .after
+            .saturating_sub(gd.gap.before)
+            .saturating_sub(1)
+            .clamp(1, MAX_GAP_SLOTS),
+        GapReason::MissingByHash => 1,
+    }
+}
+
+fn render_html(page: &Page) -> String {
+    let conflict_at: BTreeSet<u64> = page.conflicts.iter().map(|c| c.at).collect();
+    let contenders: BTreeSet<u64> = page
+        .conflicts
+        .iter()
+        .flat_map(|c| c.contenders.iter().copied())
+        .collect();
+    let equivocated: BTreeSet<u64> = page.equivocations.iter().map(|e| e.frame_id).collect();
+    let gap_after = |id: u64| page.gap_details.iter().find(|g| g.gap.after == id);
+    let tags = |f: &Frame| {
+        let id = f.header.frame_id;
+        let mut t = Vec::new();
+        if f.header.flags.is_first() {
+            t.push("first");
+        }
+        if f.header.flags.is_last() {
+            t.push("last");
+        }
+        if conflict_at.contains(&id) || contenders.contains(&id) {
+            t.push("conflict");
+        }
+        if equivocated.contains(&id) {
+            t.push("equivocation");
+        }
+        if page.renumbered.contains(&id) {
+            t.push("renumbered");
+        }
+        t
+    };
+
+    let mut s = String::new();
+    let _ = write!(
+        &mut s,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        escape(page.title),
+        STYLE,
+        escape(page.title)
+    );
+
+    // Summary
+    let c = page.completeness;
+    let start = if c.starts_at_first {
+        "starts at IS_FIRST"
+    } else {
+        "no IS_FIRST frame: head may be missing"
+    };
+    let end = match c.end {
+        SequenceEnd::Clean { .. } => String::from("ends at IS_LAST"),
+        SequenceEnd::Truncated { last_seen } => {
+            alloc::format!("no IS_LAST after frame {}: possibly truncated", last_seen)
+        }
+        SequenceEnd::Empty => String::from("empty"),
+    };
+    let _ = write!(
+        &mut s,
+        "<section class=\"summary\">\n\
+         <div><b>{}</b>ordered frames</div><div><b>{}</b>gaps</div>\
+         <div><b>{}</b>orphans</div><div><b>{}</b>conflicts</div>\
+         <div><b>{}</b>equivocations</div><div><b>{:.2}%</b>continuity</div>\n\
+         <p class=\"{}\">{}; {}{}</p>\n</section>\n",
+        page.stats.total_frames,
+        page.stats.gaps,
+        page.stats.orphans,
+        page.conflicts.len(),
+        page.equivocations.len(),
+        page.stats.continuity,
+        if c.is_complete() { "ok" } else { "warn" },
+        start,
+        escape(&end),
+        if c.is_complete() {
+            "; sequence complete"
+        } else {
+            ""
+        }
+    );
+    for issue in &c.inconsistencies {
+        let text = match issue {
+            FlagInconsistency::FirstWithPrevHash { frame_id } => alloc::format!(
+                "frame {} is flagged IS_FIRST but has a non-zero prev_hash",
+                frame_id
+            ),
+            FlagInconsistency::LastWithSuccessor {
+                frame_id,
+                successor,
+            } => alloc::format!(
+                "frame {} is flagged IS_LAST but frame {} links to it",
+                frame_id,
+                successor
+            ),
+        };
+        let _ = writeln!(&mut s, "<p class=\"bad\">{}</p>", escape(&text));
+    }
+
+    // Timeline strip: chain frames with gap slots, orphans on a second lane
+    let slots: u64 = page.chain.len() as u64
+        + page
+            .chain
+            .iter()
+            .filter_map(|r| gap_after(r.frame.header.frame_id))
+            .map(gap_slots)
+            .sum::<u64>();
+    let slots = slots.max(page.orphans.len() as u64).max(1);
+    let pct = |slot: u64| slot as f64 * 100.0 / slots as f64;
+    let _ = write!(
+        &mut s,
+        "<section>\n<h2>Timeline</h2>\n<div class=\"controls\">\
+         <label>Zoom <input id=\"zoom\" type=\"range\" min=\"1\" max=\"400\" value=\"1\"></label> \
+         <span class=\"hint\">Ctrl+wheel to zoom, scroll to pan, click a frame for details</span>\
+         </div>\n<div id=\"viewport\">\n<div id=\"track\" style=\"--slots:{}\">\n",
+        slots
+    );
+    let mut slot = 0u64;
+    for (n, r) in page.chain.iter().enumerate() {
+        let id = r.frame.header.frame_id;
+        if let Some(gd) = gap_after(id) {
+            let w = gap_slots(gd);
+            let label = match gd.reason {
+                GapReason::MissingById => "missing by id",
+                GapReason::MissingByHash => "missing by hash",
+            };
+            let _ = writeln!(
+                &mut s,
+                "<div class=\"gap\" style=\"left:{:.4}%;width:{:.4}%\" \
+                 title=\"gap {} &#8594; {} ({})\"></div>",
+                pct(slot),
+                pct(w),
+                gd.gap.before,
+                gd.gap.after,
+                label
+            );
+            slot += w;
+        }
+        let t = tags(r.frame);
+        let _ =
+            writeln!(
+            &mut s,
+            "<a class=\"f{}\" href=\"#row-{}\" style=\"left:{:.4}%;width:{:.4}%;background:{}\" \
+             title=\"frame {}{}\"></a>",
+            t.iter().map(|t| alloc::format!(" {}", t)).collect::<String>(),
+            n,
+            pct(slot),
+            pct(1),
+            confidence_color(r.located.map(|l| l.2)),
+            id,
+            r.located
+                .map(|(off, _, conf)| alloc::format!(" @ {} (conf {:.2})", off, conf))
+                .unwrap_or_default()
+        );
+        slot += 1;
+    }
+    for (n, r) in page.orphans.iter().enumerate() {
+        let _ = writeln!(
+            &mut s,
+            "<a class=\"f orphan\" href=\"#orphan-{}\" style=\"left:{:.4}%;width:{:.4}%\" \
+             title=\"orphan frame {}\"></a>",
+            n,
+            pct(n as u64),
+            pct(1),
+            r.frame.header.frame_id
+        );
+    }
+    s.push_str(
+        "</div>\n</div>\n<p class=\"legend\">\
+         <span class=\"sw\" style=\"background:hsl(120,65%,42%)\"></span>high confidence \
+         <span class=\"sw\" style=\"background:hsl(0,65%,42%)\"></span>low confidence \
+         <span class=\"sw gapsw\"></span>gap \
+         <span class=\"sw conflict\"></span>conflict \
+         <span class=\"sw equivocation\"></span>equivocation \
+         <span class=\"sw renumbered\"></span>renumbered \
+         <span class=\"sw orphansw\"></span>orphan (lower lane)</p>\n</section>\n",
+    );
+
+    // Frame tables
+    let frame_table = |s: &mut String, heading: &str, anchor: &str, rows: &[Row]| {
+        let _ = write!(
+            s,
+            "<section>\n<h2>{} ({})</h2>\n<table>\n<thead><tr><th>#</th><th>Frame ID</th>\
+             <th>Offset</th><th>Size</th><th>Confidence</th><th>Payload</th>\
+             <th>Prev hash</th><th>Notes</th></tr></thead>\n<tbody>\n",
+            heading,
+            rows.len()
+        );
+        for (n, r) in rows.iter().enumerate() {
+            let (offset, size, conf) = match r.located {
+                Some((o, z, c)) => (
+                    alloc::format!("{}", o),
+                    alloc::format!("{}", z),
+                    alloc::format!("{:.2}", c),
+                ),
+                None => (String::from("-"), String::from("-"), String::from("-")),
+            };
+            let _ = writeln!(
+                s,
+                "<tr id=\"{}-{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td>\
+                 <td>{} B</td><td><code>{}&#8230;</code></td><td>{}</td></tr>",
+                anchor,
+                n,
+                n,
+                r.frame.header.frame_id,
+                offset,
+                size,
+                conf,
+                r.frame.payload.len(),
+                hex_prefix(&r.frame.header.prev_hash),
+                tags(r.frame).join(", ")
+            );
+        }
+        s.push_str("</tbody>\n</table>\n</section>\n");
+    };
+    frame_table(&mut s, "Frames", "row", &page.chain);
+
+    if !page.gap_details.is_empty() {
+        s.push_str(
+            "<section>\n<h2>Gaps</h2>\n<table>\n<thead><tr><th>Before</th><th>After</th>\
+             <th>Reason</th></tr></thead>\n<tbody>\n",
+        );
+        for gd in page.gap_details {
+            let _ = writeln!(
+                &mut s,
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                gd.gap.before,
+                gd.gap.after,
+                match gd.reason {
+                    GapReason::MissingById => "missing by id",
+                    GapReason::MissingByHash => "missing by hash",
+                }
+            );
+        }
+        s.push_str("</tbody>\n</table>\n</section>\n");
+    }
+
+    if !page.conflicts.is_empty() || !page.equivocations.is_empty() {
+        s.push_str("<section>\n<h2>Conflicts</h2>\n<ul>\n");
+        for c in page.conflicts {
+            let ids: Vec<String> = c
+                .contenders
+                .iter()
+                .map(|i| alloc::format!("{}", i))
+                .collect();
+            let _ = writeln!(
+                &mut s,
+                "<li class=\"conflict\">frames {} all link back to frame {}</li>",
+                ids.join(", "),
+                c.at
+            );
+        }
+        for e in page.equivocations {
+            let offsets: Vec<String> = e.offsets.iter().map(|o| alloc::format!("{}", o)).collect();
+            let _ = writeln!(
+                &mut s,
+                "<li class=\"equivocation\">frame {} has {} different versions at offsets {}</li>",
+                e.frame_id,
+                e.hashes.len(),
+                offsets.join(", ")
+            );
+        }
+        s.push_str("</ul>\n</section>\n");
+    }
+
+    if !page.recipes.is_empty() {
+        s.push_str("<section>\n<h2>Recovery recipes</h2>\n<ol>\n");
+        for r in page.recipes {
+            let text = match r {
+                RecoveryRecipe::InsertParityFrame { between, reason } => alloc::format!(
+                    "Insert parity between frames {} and {} ({})",
+                    between.0,
+                    between.1,
+                    reason
+                ),
+                RecoveryRecipe::RewindOffset {
+                    near_frame,
+                    by_bytes,
+                    reason,
+                } => alloc::format!(
+                    "Move the read offset near frame {} by {} bytes ({})",
+                    near_frame,
+                    by_bytes,
+                    reason
+                ),
+            };
+            let _ = writeln!(&mut s, "<li>{}</li>", escape(&text));
+        }
+        s.push_str("</ol>\n</section>\n");
+    }
+
+    if !page.orphans.is_empty() {
+        frame_table(&mut s, "Orphans", "orphan", &page.orphans);
+        if !page.orphan_clusters.is_empty() {
+            s.push_str("<section>\n<h2>Orphan clusters</h2>\n<ul>\n");
+            for cluster in page.orphan_clusters {
+                let ids: Vec<String> = cluster
+                    .ids
+                    .iter()
+                    .map(|i| alloc::format!("{}", i))
+                    .collect();
+                let _ = writeln!(&mut s, "<li>{}</li>", ids.join(", "));
+            }
+            s.push_str("</ul>\n</section>\n");
+        }
+    }
+
+    let _ = write!(&mut s, "<script>{}</script>\n</body>\n</html>\n", SCRIPT);
+    s
+}
+
+const STYLE: &str = "
+body{font-family:system-ui,sans-serif;margin:1.5em;color:#222}
+h1{font-size:1.4em}h2{font-size:1.1em;margin-top:1.5em}
+.summary div{display:inline-block;margin:0 1.5em .5em 0}.summary b{display:block;font-size:1.4em}
+.ok{color:#1d7a33}.warn{color:#a06000}.bad{color:#b00020}
+#viewport{overflow-x:auto;border:1px solid #ccc;background:#fafafa}
+#track{position:relative;height:64px;min-width:100%;width:calc(var(--zoom,1)*max(100%,calc(var(--slots)*2px)))}
+#track .f,#track .gap{position:absolute;box-sizing:border-box;display:block}
+#track .f{top:4px;height:32px;border:1px solid rgba(0,0,0,.15)}
+#track .gap{top:0;height:40px;background:repeating-linear-gradient(45deg,#e53935 0 4px,#ffcdd2 4px 8px)}
+#track .f.orphan{top:44px;height:16px;background:#9e9e9e}
+#track .f:hover,#track .f:focus{outline:2px solid #000;z-index:2}
+.f.conflict,.sw.conflict{box-shadow:inset 0 0 0 2px #fb8c00}
+.f.equivocation,.sw.equivocation{box-shadow:inset 0 0 0 2px #8e24aa}
+.f.renumbered,.sw.renumbered{border:2px dashed #000!important}
+li.conflict{color:#b35900}li.equivocation{color:#6a1b9a}
+.controls{margin:.5em 0}.hint{color:#777;font-size:.85em}
+.legend{font-size:.85em;color:#555}
+.sw{display:inline-block;width:1em;height:1em;vertical-align:middle;margin:0 .3em 0 1em;border:1px solid #999}
+.gapsw{background:repeating-linear-gradient(45deg,#e53935 0 3px,#ffcdd2 3px 6px)}.orphansw{background:#9e9e9e}
+table{border-collapse:collapse;font-size:.85em}th,td{border:1px solid #ddd;padding:2px 8px;text-align:right}
+th{background:#f0f0f0}tr:target{background:#fff59d}
+";
+
+const SCRIPT: &str = "
+(function(){
+var track=document.getElementById('track'),view=document.getElementById('viewport'),zoom=document.getElementById('zoom');
+function set(z,anchor){
+  var before=(view.scrollLeft+anchor)/track.offsetWidth;
+  track.style.setProperty('--zoom',z);zoom.value=z;
+  view.scrollLeft=before*track.offsetWidth-anchor;
+}
+zoom.addEventListener('input',function(){set(+zoom.value,view.clientWidth/2);});
+view.addEventListener('wheel',function(e){
+  if(!e.ctrlKey)return;e.preventDefault();
+  var z=Math.min(400,Math.max(1,+zoom.value*(e.deltaY<0?1.25:0.8)));
+  set(z,e.clientX-view.getBoundingClientRect().left);
+},{passive:false});
+})();
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoder::{encode_frame_struct, FrameBuilder};
+    use crate::linker::{analyze_indexed, analyze_timeline};
+    use crate::scanner::scan_stream;
+    use crate::test_util::chain;
+    use alloc::vec;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_html_report_is_self_contained() {
+        // 1, 2, then 5 after losing 3 and 4
+        let mut frames = chain(1..=5, |_, b| b.with_blake3());
+        frames.drain(2..4);
+        let data: Vec<u8> = frames
+            .iter()
+            .flat_map(|f| encode_frame_struct(f).unwrap())
+            .collect();
+        let located = scan_stream(&data);
+        let report = analyze_indexed(&located);
+        let html = index_report_to_html(&report, &located, "capture <A&B>");
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>capture &lt;A&amp;B&gt;</title>"));
+        assert!(!html.contains("http://") && !html.contains("https://"));
+        assert!(!html.contains("<script src") && !html.contains("<link"));
+        // One strip cell and one table row per frame, plus the gap slot
+        assert_eq!(html.matches("<a class=\"f").count(), 3);
+        assert_eq!(html.matches("<tr id=\"row-").count(), 3);
+        assert_eq!(html.matches("<div class=\"gap\"").count(), 1);
+        assert!(html.contains("title=\"gap 2 &#8594; 5 (missing by id)\""));
+        assert!(html.contains(&alloc::format!("<td>{}</td>", located[2].offset)));
+        assert!(html.contains("possibly truncated"));
+    }
+
+    #[test]
+    fn test_html_report_highlights_orphans_and_conflicts() {
+        let mut frames = chain(1..=3, |_, b| b.with_blake3());
+        // A second successor of frame 1
+        let fork = FrameBuilder::new(7)
+            .payload(Bytes::from("fork"))
+            .prev_hash(frames[0].compute_hash())
+            .with_blake3()
+            .build_struct()
+            .unwrap();
+        frames.push(fork);
+
+        let mut report = analyze_timeline(frames);
+        assert_eq!(report.conflicts.len(), 1);
+        // The linker bridges stray frames with gaps; place one among the orphans directly
+        let stray = FrameBuilder::new(40)
+            .payload(Bytes::from("stray"))
+            .with_blake3()
+            .build_struct()
+            .unwrap();
+        report.timeline.orphans.push(stray);
+        report.orphan_clusters.push(OrphanCluster { ids: vec![40] });
+
+        let html = report_to_html(&report, "owned");
+        assert!(html.contains("<h2>Conflicts</h2>"));
+        assert!(html.contains("all link back to frame 1"));
+        assert!(html.contains("class=\"f conflict\""));
+        assert_eq!(html.matches("class=\"f orphan\"").count(), 1);
+        assert!(html.contains("<h2>Orphans (1)</h2>"));
+        assert!(html.contains("<h2>Orphan clusters</h2>"));
+        // Owned reports have no capture positions
+        assert!(html.contains("<td>-</td>"));
+    }
+}
@@ -0,0 +1,558 @@
+//! Online timeline building for live downlinks
+//!
+//! [`IncrementalLinker`] accepts frames one at a time, in any order, and keeps
+//! only the frames that cannot be finalized yet. Frames leave the linker in
+//! chain order through [`LinkEvent::Retired`] as soon as every frame before
+//! them is known, so memory stays bounded by the reordering window rather than
+//! by the length of the stream.
+
+use crate::constants::BLAKE3_HASH_SIZE;
+use crate::linker::{ChainConflict, Equivocation, SequenceGap};
+use crate::types::Frame;
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+
+type Hash = [u8; BLAKE3_HASH_SIZE];
+
+/// Default number of frames held back waiting for late arrivals
+pub const DEFAULT_MAX_PENDING: usize = 4096;
+
+/// Something the linker learned from a pushed frame
+#[derive(Debug, Clone)]
+pub enum LinkEvent {
+    /// The frame was accepted into the pending chain
+    Appended {
+        /// ID of the accepted frame
+        frame_id: u64,
+    },
+    /// The pending chain now has a hole between two frames
+    GapOpened(SequenceGap),
+    /// A late arrival landed inside this gap; any narrower gap that remains
+    /// is reported by a following [`LinkEvent::GapOpened`]
+    GapClosed(SequenceGap),
+    /// The gap was finalized while still open; frames that would have filled
+    /// it will be reported as orphans
+    GapAbandoned(SequenceGap),
+    /// The frame is final: everything before it has been retired. Frames are
+    /// retired in chain order and the linker keeps no reference to them
+    Retired(Frame),
+    /// The frame cannot join the chain: it arrived after its slot was
+    /// finalized, or precedes the first frame of the stream
+    Orphaned(Frame),
+    /// An exact copy of a frame the linker has already seen
+    Duplicate {
+        /// ID of the replayed frame
+        frame_id: u64,
+    },
+    /// A frame ID was reused for different content; the first version is kept
+    Equivocation(Equivocation),
+    /// Two frames link back to the same predecessor
+    Conflict(ChainConflict),
+}
+
+/// Counters maintained by an [`IncrementalLinker`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IncrementalStats {
+    /// Frames accepted into the chain
+    pub appended: usize,
+    /// Frames finalized and handed back
+    pub retired: usize,
+    /// Frames that could not join the chain
+    pub orphans: usize,
+    /// Exact copies that were ignored
+    pub duplicates: usize,
+    /// Frame IDs seen with different content
+    pub equivocations: usize,
+    /// Predecessors claimed by more than one frame
+    pub conflicts: usize,
+    /// Gaps opened
+    pub gaps_opened: usize,
+    /// Gaps closed by late arrivals
+    pub gaps_closed: usize,
+    /// Gaps finalized while still open
+    pub gaps_abandoned: usize,
+}
+
+/// A frame waiting for its predecessors
+#[derive(Debug)]
+struct Pending {
+    frame: Frame,
+    hash: Hash,
+}
+
+/// What is remembered about a retired frame
+#[derive(Debug, Clone, Copy)]
+struct Seen {
+    hash: Hash,
+    prev_hash: Hash,
+}
+
+/// Builds a timeline from frames arriving in any order
+///
+/// Frames are ordered by `frame_id` and checked against their predecessor's
+/// hash like [`link_indexed`](crate::linker::link_indexed) does in batch. The
+/// linker holds:
+/// - pending frames: accepted but not yet final because an earlier frame is
+///   missing (at most `max_pending` of them)
+/// - the open gaps between pending frames
+/// - the IDs and hashes of the last `max_pending` retired frames, so that
+///   retransmissions are recognized as duplicates or equivocations
+///
+/// A frame is retired once it links to the last retired frame, or when the
+/// gap before it can no longer be filled (the next ID is taken by a frame
+/// with a mismatched back-link). Until a frame with `IS_FIRST` arrives the
+/// start of the stream is unknown, so nothing is retired before that unless
+/// the window overflows. When more than `max_pending` frames are waiting, the
+/// oldest gap is abandoned and the frames after it are retired.
+#[derive(Debug)]
+pub struct IncrementalLinker {
+    max_pending: usize,
+    pending: BTreeMap<u64, Pending>,
+    /// Open gaps keyed by the frame ID before them
+    gaps: BTreeMap<u64, SequenceGap>,
+    /// Last retired frame
+    anchor: Option<(u64, Hash)>,
+    history: BTreeMap<u64, Seen>,
+    /// Frame ID by content hash, for pending and remembered frames
+    by_hash: BTreeMap<Hash, u64>,
+    /// First frame ID claiming each predecessor hash
+    by_prev: BTreeMap<Hash, u64>,
+    stats: IncrementalStats,
+}
+
+impl Default for IncrementalLinker {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_PENDING)
+    }
+}
+
+impl IncrementalLinker {
+    /// Create a linker that holds back at most `max_pending` frames
+    ///
+    /// `max_pending = 0` retires every frame immediately, so late arrivals
+    /// are always orphaned.
+    pub fn new(max_pending: usize) -> Self {
+        Self {
+            max_pending,
+            pending: BTreeMap::new(),
+            gaps: BTreeMap::new(),
+            anchor: None,
+            history: BTreeMap::new(),
+            by_hash: BTreeMap::new(),
+            by_prev: BTreeMap::new(),
+            stats: IncrementalStats::default(),
+        }
+    }
+
+    /// Add a frame and return what changed, in the order it happened
+    pub fn push(&mut self, frame: Frame) -> Vec<LinkEvent> {
+        let id = frame.header.frame_id;
+        let hash = frame.compute_hash();
+        let mut events = Vec::new();
+
+        // Replays and rewrites of frames we still remember
+        let known = self
+            .pending
+            .get(&id)
+            .map(|p| p.hash)
+            .or_else(|| self.history.get(&id).map(|s| s.hash));
+        if let Some(kept) = known {
+            if kept == hash {
+                self.stats.duplicates += 1;
+                events.push(LinkEvent::Duplicate { frame_id: id });
+            } else {
+                #[cfg(feature = "logging")]
+                tracing::warn!("Frame ID {} seen again with different content", id);
+
+                self.stats.equivocations += 1;
+                events.push(LinkEvent::Equivocation(Equivocation {
+                    frame_id: id,
+                    hashes: vec![kept, hash],
+                    offsets: Vec::new(),
+                }));
+            }
+            return events;
+        }
+
+        if self.anchor.is_some_and(|(last, _)| id <= last) {
+            self.stats.orphans += 1;
+            events.push(LinkEvent::Orphaned(frame));
+            return events;
+        }
+
+        // Two frames claiming the same (known) predecessor
+        let prev_hash = frame.header.prev_hash;
+        if let (Some(&at), Some(&other)) =
+            (self.by_hash.get(&prev_hash), self.by_prev.get(&prev_hash))
+        {
+            self.stats.conflicts += 1;
+            events.push(LinkEvent::Conflict(ChainConflict {
+                at,
+                contenders: vec![other.min(id), other.max(id)],
+            }));
+        }
+
+        // The first frame of the stream: anything pending below it is stray
+        if frame.header.is_first() && self.anchor.is_none() {
+            while let Some((&stray, _)) = self.pending.first_key_value() {
+                if stray > id {
+                    break;
+                }
+                if let Some(gap) = self.gaps.remove(&stray) {
+                    self.stats.gaps_abandoned += 1;
+                    events.push(LinkEvent::GapAbandoned(gap));
+                }
+                let stray = self.pending.remove(&stray).expect("key just observed");
+                self.forget(
+                    stray.frame.header.frame_id,
+                    stray.hash,
+                    stray.frame.header.prev_hash,
+                );
+                self.stats.orphans += 1;
+                events.push(LinkEvent::Orphaned(stray.frame));
+            }
+        }
+
+        // Find neighbours; a gap between them (if any) is the one this frame lands in
+        let pred = self
+            .pending
+            .range(..id)
+            .next_back()
+            .map(|(&pid, p)| (pid, p.hash))
+            .or(self.anchor);
+        let succ = self.pending.range(id..).next().map(|(&sid, _)| sid);
+
+        self.stats.appended += 1;
+        events.push(LinkEvent::Appended { frame_id: id });
+
+        if let (Some((before, _)), Some(_)) = (pred, succ) {
+            if let Some(gap) = self.gaps.remove(&before) {
+                self.stats.gaps_closed += 1;
+                events.push(LinkEvent::GapClosed(gap));
+            }
+        }
+        if let Some(before) = pred {
+            if let Some(gap) = link_gap(before, &frame) {
+                self.open_gap(gap, &mut events);
+            }
+        }
+        if let Some(after) = succ {
+            if let Some(gap) = link_gap((id, hash), &self.pending[&after].frame) {
+                self.open_gap(gap, &mut events);
+            }
+        }
+
+        self.by_hash.entry(hash).or_insert(id);
+        self.by_prev.entry(prev_hash).or_insert(id);
+        self.pending.insert(id, Pending { frame, hash });
+
+        self.retire_ready(&mut events);
+        events
+    }
+
+    /// Retire every pending frame, abandoning any open gap
+    ///
+    /// Call at the end of a pass or stream. The linker can keep receiving
+    /// frames afterwards; anything at or below the last retired ID is orphaned.
+    pub fn flush(&mut self) -> Vec<LinkEvent> {
+        let mut events = Vec::new();
+        while let Some((&id, _)) = self.pending.first_key_value() {
+            if let Some(gap) = self.anchor.and_then(|(last, _)| self.gaps.remove(&last)) {
+                self.stats.gaps_abandoned += 1;
+                events.push(LinkEvent::GapAbandoned(gap));
+            }
+            self.retire(id, &mut events);
+        }
+        events
+    }
+
+    /// Frames waiting for a predecessor, in frame ID order
+    pub fn pending(&self) -> impl Iterator<Item = &Frame> {
+        self.pending.values().map(|p| &p.frame)
+    }
+
+    /// Gaps that a late arrival could still fill, in frame ID order
+    pub fn open_gaps(&self) -> impl Iterator<Item = &SequenceGap> {
+        self.gaps.values()
+    }
+
+    /// ID of the last retired frame
+    pub fn last_retired(&self) -> Option<u64> {
+        self.anchor.map(|(id, _)| id)
+    }
+
+    /// Counters accumulated so far
+    pub fn stats(&self) -> &IncrementalStats {
+        &self.stats
+    }
+
+    fn open_gap(&mut self, gap: SequenceGap, events: &mut Vec<LinkEvent>) {
+        #[cfg(feature = "logging")]
+        tracing::debug!("Gap opened between frames {} and {}", gap.before, gap.after);
+
+        self.stats.gaps_opened += 1;
+        self.gaps.insert(gap.before, gap.clone());
+        events.push(LinkEvent::GapOpened(gap));
+    }
+
+    /// Retire the head of the pending chain while it is final, then shrink
+    /// the window back to `max_pending` by abandoning the oldest gaps
+    fn retire_ready(&mut self, events: &mut Vec<LinkEvent>) {
+        while let Some((&id, head)) = self.pending.first_key_value() {
+            let ready = match self.anchor {
+                // Linked, or the slot after the anchor is taken so nothing can close the gap
+                Some((last, _)) => !self.gaps.contains_key(&last) || id == last + 1,
+                None => head.frame.header.is_first(),
+            };
+            if !ready && self.pending.len() <= self.max_pending {
+                break;
+            }
+            #[cfg(feature = "logging")]
+            if !ready {
+                tracing::warn!(
+                    "Reordering window full ({} frames), finalizing past frame {}",
+                    self.max_pending,
+                    id
+                );
+            }
+            if let Some(gap) = self.anchor.and_then(|(last, _)| self.gaps.remove(&last)) {
+                self.stats.gaps_abandoned += 1;
+                events.push(LinkEvent::GapAbandoned(gap));
+            }
+            self.retire(id, events);
+        }
+    }
+
+    fn retire(&mut self, id: u64, events: &mut Vec<LinkEvent>) {
+        let Pending { frame, hash } = self.pending.remove(&id).expect("retiring a pending frame");
+        self.anchor = Some((id, hash));
+        self.history.insert(
+            id,
+            Seen {
+                hash,
+                prev_hash: frame.header.prev_hash,
+            },
+        );
+        while self.history.len() > self.max_pending.max(1) {
+            let (old, seen) = self.history.pop_first().expect("history is not empty");
+            self.forget(old, seen.hash, seen.prev_hash);
+        }
+        self.stats.retired += 1;
+        events.push(LinkEvent::Retired(frame));
+    }
+
+    fn forget(&mut self, id: u64, hash: Hash, prev_hash: Hash) {
+        if self.by_hash.get(&hash) == Some(&id) {
+            self.by_hash.remove(&hash);
+        }
+        if self.by_prev.get(&prev_hash) == Some(&id) {
+            self.by_prev.remove(&prev_hash);
+        }
+    }
+}
+
+/// The gap between a frame and the next one present, if they do not link
+fn link_gap((before, before_hash): (u64, Hash), next: &Frame) -> Option<SequenceGap> {
+    let linked = next.header.frame_id == before + 1 && next.header.prev_hash == before_hash;
+    (!linked).then_some(SequenceGap {
+        before,
+        after: next.header.frame_id,
+        expected_hash: Some(next.header.prev_hash),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{chain, plain};
+    use crate::types::FrameHeader;
+    use bytes::Bytes;
+
+    fn retired(events: &[LinkEvent]) -> Vec<u64> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                LinkEvent::Retired(f) => Some(f.header.frame_id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn push_all(
+        linker: &mut IncrementalLinker,
+        frames: &[Frame],
+        order: &[usize],
+    ) -> Vec<LinkEvent> {
+        order
+            .iter()
+            .flat_map(|&i| linker.push(frames[i].clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_in_order_stream_retires_immediately() {
+        let frames = chain(1..=5, plain);
+        let mut linker = IncrementalLinker::new(8);
+        let events = push_all(&mut linker, &frames, &[0, 1, 2, 3, 4]);
+
+        assert_eq!(retired(&events), vec![1, 2, 3, 4, 5]);
+        assert!(!events.iter().any(|e| matches!(e, LinkEvent::GapOpened(_))));
+        assert_eq!(linker.pending().count(), 0);
+        assert_eq!(linker.last_retired(), Some(5));
+    }
+
+    #[test]
+    fn test_late_arrival_closes_gap() {
+        let frames = chain(1..=5, plain);
+        let mut linker = IncrementalLinker::new(8);
+
+        let events = push_all(&mut linker, &frames, &[0, 1, 4]);
+        assert_eq!(retired(&events), vec![1, 2]);
+        assert_eq!(linker.open_gaps().count(), 1);
+        assert_eq!(
+            linker.open_gaps().next().map(|g| (g.before, g.after)),
+            Some((2, 5))
+        );
+
+        // Frame 4 narrows the gap to 2..4; frame 3 closes it and releases 3, 4, 5
+        let events = linker.push(frames[3].clone());
+        assert!(matches!(&events[1], LinkEvent::GapClosed(g) if (g.before, g.after) == (2, 5)));
+        assert!(matches!(&events[2], LinkEvent::GapOpened(g) if (g.before, g.after) == (2, 4)));
+        assert!(retired(&events).is_empty());
+
+        let events = linker.push(frames[2].clone());
+        assert!(matches!(&events[1], LinkEvent::GapClosed(g) if (g.before, g.after) == (2, 4)));
+        assert_eq!(retired(&events), vec![3, 4, 5]);
+        assert_eq!(linker.open_gaps().count(), 0);
+        assert_eq!(linker.stats().gaps_opened, linker.stats().gaps_closed);
+    }
+
+    #[test]
+    fn test_waits_for_first_frame() {
+        let frames = chain(1..=3, plain);
+        let mut linker = IncrementalLinker::new(8);
+
+        let events = push_all(&mut linker, &frames, &[2, 1]);
+        assert!(retired(&events).is_empty());
+        assert_eq!(linker.pending().count(), 2);
+
+        let events = linker.push(frames[0].clone());
+        assert_eq!(retired(&events), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_window_overflow_abandons_gap_and_orphans_late_frame() {
+        let frames = chain(1..=6, plain);
+        let mut linker = IncrementalLinker::new(2);
+
+        let events = push_all(&mut linker, &frames, &[0, 2, 3]);
+        assert_eq!(retired(&events), vec![1]);
+
+        // A third waiting frame overflows the window: the 1..3 gap is given up
+        let events = linker.push(frames[4].clone());
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, LinkEvent::GapAbandoned(g) if (g.before, g.after) == (1, 3))));
+        assert_eq!(retired(&events), vec![3, 4, 5]);
+        assert!(linker.pending().count() <= 2);
+
+        let events = linker.push(frames[1].clone());
+        assert!(matches!(&events[..], [LinkEvent::Orphaned(f)] if f.header.frame_id == 2));
+        assert_eq!(linker.stats().orphans, 1);
+    }
+
+    #[test]
+    fn test_hash_mismatch_is_final() {
+        let frames = chain(1..=3, plain);
+        let forged = Frame::new(
+            FrameHeader::new(2, [7u8; BLAKE3_HASH_SIZE], 6),
+            Bytes::from("forged"),
+        );
+        let mut linker = IncrementalLinker::new(8);
+        linker.push(frames[0].clone());
+
+        // Nothing can fill the slot between 1 and 2, so the break is retired at once
+        let events = linker.push(forged);
+        assert!(events.iter().any(|e| matches!(e, LinkEvent::GapOpened(_))));
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, LinkEvent::GapAbandoned(_))));
+        assert_eq!(retired(&events), vec![2]);
+    }
+
+    #[test]
+    fn test_duplicates_equivocations_and_conflicts() {
+        let frames = chain(1..=3, plain);
+        let mut linker = IncrementalLinker::new(8);
+        push_all(&mut linker, &frames, &[0, 1, 2]);
+
+        let events = linker.push(frames[1].clone());
+        assert!(matches!(
+            &events[..],
+            [LinkEvent::Duplicate { frame_id: 2 }]
+        ));
+
+        let rewritten = Frame::new(
+            FrameHeader::new(2, frames[0].compute_hash(), 9),
+            Bytes::from("rewritten"),
+        );
+        let events = linker.push(rewritten.clone());
+        match &events[..] {
+            [LinkEvent::Equivocation(e)] => {
+                assert_eq!(e.frame_id, 2);
+                assert_eq!(
+                    e.hashes,
+                    vec![frames[1].compute_hash(), rewritten.compute_hash()]
+                );
+            }
+            other => panic!("unexpected events {:?}", other),
+        }
+
+        // A new frame 4 linking back to frame 2 competes with frame 3
+        let branch = Frame::new(
+            FrameHeader::new(4, frames[1].compute_hash(), 6),
+            Bytes::from("branch"),
+        );
+        let events = linker.push(branch);
+        assert!(events.iter().any(|e| matches!(
+            e,
+            LinkEvent::Conflict(c) if c.at == 2 && c.contenders == vec![3, 4]
+        )));
+        assert_eq!(linker.stats().duplicates, 1);
+        assert_eq!(linker.stats().equivocations, 1);
+        assert_eq!(linker.stats().conflicts, 1);
+    }
+
+    #[test]
+    fn test_shuffled_stream_matches_batch_linker() {
+        let frames = chain(1..=200, plain);
+        let mut order: Vec<usize> = (0..frames.len()).collect();
+        // Deterministic local shuffle: swap within blocks of 7
+        for block in order.chunks_mut(7) {
+            block.reverse();
+        }
+
+        let mut linker = IncrementalLinker::new(16);
+        let mut events = push_all(&mut linker, &frames, &order);
+        events.extend(linker.flush());
+
+        let batch = crate::linker::link_frames(frames.clone());
+        let batch_ids: Vec<u64> = batch.frames.iter().map(|f| f.header.frame_id).collect();
+        assert_eq!(retired(&events), batch_ids);
+        assert_eq!(linker.stats().orphans, 0);
+        assert_eq!(linker.stats().gaps_abandoned, 0);
+    }
+
+    #[test]
+    fn test_flush_releases_pending_frames() {
+        let frames = chain(1..=4, plain);
+        let mut linker = IncrementalLinker::new(8);
+        push_all(&mut linker, &frames, &[1, 3]);
+
+        let events = linker.flush();
+        assert_eq!(retired(&events), vec![2, 4]);
+        assert_eq!(linker.stats().gaps_abandoned, 1);
+        assert_eq!(linker.pending().count(), 0);
+    }
+}
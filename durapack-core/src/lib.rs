@@ -10,21 +10,39 @@
 //! - `decoder`: Strict frame decoding
 //! - `scanner`: Damaged stream scanning and recovery
 //! - `linker`: Bidirectional timeline reconstruction
-//! - `fec`: Forward Error Correction traits (interface only)
+//! - `incremental`: Online timeline building for frames arriving out of order
+//! - `merge`: Consensus merge of several damaged captures of one stream
+//! - `recover`: Gap repair from FEC parity blocks during linking
+//! - `capture_index`: Persisted timeline index for fast reopen of captures
+//! - `tamper`: Tamper classification separating deliberate modification from damage
+//! - `damage_map`: Byte-level coverage map of a capture
+//! - `html_report`: Self-contained HTML rendering of timeline reports
+//! - `fec`: Forward Error Correction traits and Reed–Solomon, RaptorQ and LDPC backends
+//! - `parity`: Self-describing in-band parity frames
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 
 extern crate alloc;
 
+pub mod capture_index;
 pub mod constants;
+pub mod damage_map;
 pub mod decoder;
 pub mod encoder;
 pub mod error;
 pub mod fec;
+pub mod html_report;
+pub mod incremental;
 pub mod interleave;
 pub mod linker;
+pub mod merge;
+pub mod parity;
+pub mod recover;
 pub mod scanner;
+pub mod tamper;
+#[cfg(test)]
+pub(crate) mod test_util;
 pub mod types;
 
 // Re-export commonly used types
@@ -2,10 +2,13 @@
 
 use crate::constants::BLAKE3_HASH_SIZE;
 use crate::error::FrameError;
+use crate::parity::parity_descriptor;
 use crate::scanner::LocatedFrame;
 use crate::types::Frame;
 use alloc::collections::BTreeMap;
 use alloc::collections::BTreeSet;
+use alloc::collections::VecDeque;
+use alloc::format;
 use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
@@ -21,6 +24,9 @@ pub struct Timeline {
 
     /// Frames that couldn't be linked (orphans)
     pub orphans: Vec<Frame>,
+
+    /// Frames skipped for an equivocating copy; see [`Timeline::conflicting`]
+    pub(crate) conflicting: Vec<Frame>,
 }
 
 /// Represents a gap in the frame sequence
@@ -63,6 +69,139 @@ pub struct ChainConflict {
     pub contenders: Vec<u64>,
 }
 
+/// Distinct frames carrying the same frame ID
+///
+/// Honest writers never reuse a frame ID for different content, so this is
+/// either a rewritten frame or tampering. Exact copies are not reported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Equivocation {
+    /// The contested frame ID
+    pub frame_id: u64,
+    /// Content hash of each distinct version; the first is the one that was linked
+    pub hashes: Vec<[u8; BLAKE3_HASH_SIZE]>,
+    /// Stream offset of each version, parallel to `hashes` (empty when offsets are unknown)
+    pub offsets: Vec<usize>,
+}
+
+/// How a linked sequence ends (spec §5.4, §10.3)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceEnd {
+    /// No frames were linked
+    Empty,
+    /// The last linked frame carries IS_LAST, or only parity frames follow one that does
+    Clean {
+        /// Frame ID of the frame flagged IS_LAST
+        last: u64,
+    },
+    /// The last linked frame lacks IS_LAST, so later frames may have been cut off
+    Truncated {
+        /// Frame ID of the last frame seen
+        last_seen: u64,
+    },
+}
+
+/// A first/last flag that contradicts the frame's back-links
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagInconsistency {
+    /// IS_FIRST is set but prev_hash is not all zeros
+    FirstWithPrevHash {
+        /// The flagged frame
+        frame_id: u64,
+    },
+    /// IS_LAST is set but another frame links back to it
+    LastWithSuccessor {
+        /// The flagged frame
+        frame_id: u64,
+        /// The frame whose prev_hash references it
+        successor: u64,
+    },
+}
+
+/// Whether a timeline provably holds a whole sequence, first through last
+///
+/// A sequence is complete when its chain starts at a frame flagged IS_FIRST
+/// (with an all-zero prev_hash), ends at a frame flagged IS_LAST, has no gaps,
+/// and no first/last flag contradicts the back-links. In-band parity frames
+/// (see [`crate::parity`]) may follow the IS_LAST frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completeness {
+    /// The chain starts at a frame flagged IS_FIRST with an all-zero prev_hash
+    pub starts_at_first: bool,
+    /// How the chain ends
+    pub end: SequenceEnd,
+    /// Number of gaps in the chain
+    pub gaps: usize,
+    /// Flags contradicting the back-links
+    pub inconsistencies: Vec<FlagInconsistency>,
+}
+
+impl Completeness {
+    /// True when the sequence is provably whole
+    pub fn is_complete(&self) -> bool {
+        self.starts_at_first
+            && matches!(self.end, SequenceEnd::Clean { .. })
+            && self.gaps == 0
+            && self.inconsistencies.is_empty()
+    }
+
+    /// True when the chain ends without an IS_LAST frame
+    pub fn is_truncated(&self) -> bool {
+        matches!(self.end, SequenceEnd::Truncated { .. })
+    }
+}
+
+fn completeness<'a>(
+    chain: &[&'a Frame],
+    orphans: impl Iterator<Item = &'a Frame>,
+    gaps: usize,
+) -> Completeness {
+    let mut inconsistencies = Vec::new();
+    for f in chain.iter().copied().chain(orphans) {
+        if f.header.flags.is_first() && f.header.prev_hash != [0u8; BLAKE3_HASH_SIZE] {
+            inconsistencies.push(FlagInconsistency::FirstWithPrevHash {
+                frame_id: f.header.frame_id,
+            });
+        }
+    }
+    // Parity injected after the fact (`durapack fec`) links onto the IS_LAST frame
+    for pair in chain.windows(2) {
+        if pair[0].header.is_last()
+            && pair[1].header.prev_hash == pair[0].compute_hash()
+            && parity_descriptor(pair[1]).is_none()
+        {
+            inconsistencies.push(FlagInconsistency::LastWithSuccessor {
+                frame_id: pair[0].header.frame_id,
+                successor: pair[1].header.frame_id,
+            });
+        }
+    }
+
+    let last_data = chain.iter().rev().find(|f| parity_descriptor(f).is_none());
+    let end = match chain.last() {
+        None => SequenceEnd::Empty,
+        Some(f) if f.header.is_last() => SequenceEnd::Clean {
+            last: f.header.frame_id,
+        },
+        Some(f) => match last_data {
+            Some(d) if d.header.is_last() => SequenceEnd::Clean {
+                last: d.header.frame_id,
+            },
+            _ => SequenceEnd::Truncated {
+                last_seen: f.header.frame_id,
+            },
+        },
+    };
+
+    Completeness {
+        starts_at_first: chain.first().is_some_and(|f| {
+            f.header.flags.is_first() && f.header.prev_hash == [0u8; BLAKE3_HASH_SIZE]
+        }),
+        end,
+        gaps,
+        inconsistencies,
+    }
+}
+
 /// Connected set of orphan frames (by hash linkage among orphans)
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct OrphanCluster {
@@ -100,173 +239,474 @@ pub struct TimelineReport {
     pub gap_details: Vec<GapDetail>,
     /// Conflicts detected where multiple successors reference the same predecessor
     pub conflicts: Vec<ChainConflict>,
+    /// Same frame ID seen with different content
+    pub equivocations: Vec<Equivocation>,
     /// Orphan clusters (connected components among orphans)
     pub orphan_clusters: Vec<OrphanCluster>,
     /// Recovery suggestions
     pub recipes: Vec<RecoveryRecipe>,
+    /// Whether the timeline holds the whole sequence
+    pub completeness: Completeness,
 }
 
-/// Link frames into a timeline using their IDs and back-links
+/// A timeline expressed as indices into a caller-owned frame list
 ///
-/// This function:
-/// 1. Builds a hash map of frame_id -> frame
-/// 2. Verifies back-link consistency (prev_hash matches actual previous frame)
-/// 3. Orders frames chronologically
-/// 4. Detects gaps in the sequence
-pub fn link_frames(frames: Vec<Frame>) -> Timeline {
-    #[cfg(feature = "logging")]
-    tracing::debug!("Linking {} frames into timeline", frames.len());
+/// Built by [`link_indexed`] without cloning any frame, so its memory cost is
+/// proportional to the number of frames rather than to payload bytes. Indices
+/// refer to the slice the index was built from; use [`TimelineIndex::to_timeline`]
+/// when an owned [`Timeline`] is more convenient.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimelineIndex {
+    /// Source indices in chronological (chain) order
+    pub frames: Vec<usize>,
 
-    if frames.is_empty() {
-        return Timeline {
-            frames: Vec::new(),
-            gaps: Vec::new(),
-            orphans: Vec::new(),
-        };
+    /// Detected gaps in the sequence
+    pub gaps: Vec<SequenceGap>,
+
+    /// Source indices of frames that couldn't be linked (orphans)
+    pub orphans: Vec<usize>,
+
+    /// Source indices of exact copies of an earlier frame, which were skipped
+    pub duplicates: Vec<usize>,
+
+    /// Source indices of frames skipped because an earlier frame with the same
+    /// ID but different content was linked (see [`Equivocation`])
+    pub conflicting: Vec<usize>,
+
+    /// Source indices of frames hash-linked to a predecessor whose ID is not
+    /// lower than theirs (renumbered or reused IDs); forward jumps, as at a
+    /// fork or after lost frames, are not listed
+    pub renumbered: Vec<usize>,
+}
+
+impl TimelineIndex {
+    /// Ordered frames, resolved against the slice this index was built from
+    pub fn ordered<'a, T: AsRef<Frame>>(
+        &'a self,
+        source: &'a [T],
+    ) -> impl Iterator<Item = &'a Frame> + 'a {
+        self.frames.iter().map(move |&i| source[i].as_ref())
     }
 
-    // Build lookup table
-    let mut frame_map: BTreeMap<u64, Frame> = BTreeMap::new();
-    for frame in frames {
-        frame_map.insert(frame.header.frame_id, frame);
+    /// Orphaned frames, resolved against the slice this index was built from
+    pub fn orphaned<'a, T: AsRef<Frame>>(
+        &'a self,
+        source: &'a [T],
+    ) -> impl Iterator<Item = &'a Frame> + 'a {
+        self.orphans.iter().map(move |&i| source[i].as_ref())
     }
 
-    // Find first frame (prev_hash is all zeros)
-    let first_frames: Vec<_> = frame_map.values().filter(|f| f.header.is_first()).collect();
+    /// Clone the referenced frames into an owned [`Timeline`]
+    pub fn to_timeline<T: AsRef<Frame>>(&self, source: &[T]) -> Timeline {
+        Timeline {
+            frames: self.ordered(source).cloned().collect(),
+            gaps: self.gaps.clone(),
+            orphans: self.orphaned(source).cloned().collect(),
+            conflicting: self
+                .conflicting
+                .iter()
+                .map(|&i| source[i].as_ref().clone())
+                .collect(),
+        }
+    }
 
-    if first_frames.is_empty() {
-        #[cfg(feature = "logging")]
-        tracing::warn!("No first frame found (prev_hash = 0), attempting to reconstruct anyway");
+    /// Move the referenced frames out of `source` into an owned [`Timeline`]
+    fn into_timeline(self, source: Vec<Frame>) -> Timeline {
+        let mut slots: Vec<Option<Frame>> = source.into_iter().map(Some).collect();
+        Timeline {
+            frames: self
+                .frames
+                .iter()
+                .filter_map(|&i| slots[i].take())
+                .collect(),
+            gaps: self.gaps,
+            orphans: self
+                .orphans
+                .iter()
+                .filter_map(|&i| slots[i].take())
+                .collect(),
+            conflicting: self
+                .conflicting
+                .iter()
+                .filter_map(|&i| slots[i].take())
+                .collect(),
+        }
+    }
 
-        return reconstruct_without_first(frame_map);
+    /// Get statistics about this timeline
+    pub fn stats(&self) -> TimelineStats {
+        timeline_stats(self.frames.len(), self.orphans.len(), self.gaps.len())
     }
 
-    if first_frames.len() > 1 {
-        #[cfg(feature = "logging")]
-        tracing::warn!("Multiple first frames found, using lowest frame_id");
+    /// Check IS_FIRST/IS_LAST to tell a whole sequence from a truncated one
+    pub fn completeness<T: AsRef<Frame>>(&self, source: &[T]) -> Completeness {
+        let chain: Vec<&Frame> = self.ordered(source).collect();
+        completeness(&chain, self.orphaned(source), self.gaps.len())
     }
+}
+
+/// Analysis of a [`TimelineIndex`], without owning any frames
+#[derive(Debug, Clone)]
+pub struct TimelineIndexReport {
+    /// The base timeline, as indices into the analyzed slice
+    pub index: TimelineIndex,
+    /// Gap classification
+    pub gap_details: Vec<GapDetail>,
+    /// Conflicts detected where multiple successors reference the same predecessor
+    pub conflicts: Vec<ChainConflict>,
+    /// Same frame ID seen with different content
+    pub equivocations: Vec<Equivocation>,
+    /// Orphan clusters (connected components among orphans)
+    pub orphan_clusters: Vec<OrphanCluster>,
+    /// Recovery suggestions
+    pub recipes: Vec<RecoveryRecipe>,
+    /// Whether the timeline holds the whole sequence
+    pub completeness: Completeness,
+}
 
-    // Start with the first frame (lowest ID if multiple)
-    let mut first_frame = first_frames[0].clone();
-    for f in &first_frames[1..] {
-        if f.header.frame_id < first_frame.header.frame_id {
-            first_frame = (*f).clone();
+impl TimelineIndexReport {
+    /// Attach owned frames, turning this into a [`TimelineReport`]
+    fn into_report(self, source: Vec<Frame>) -> TimelineReport {
+        TimelineReport {
+            timeline: self.index.into_timeline(source),
+            gap_details: self.gap_details,
+            conflicts: self.conflicts,
+            equivocations: self.equivocations,
+            orphan_clusters: self.orphan_clusters,
+            recipes: self.recipes,
+            completeness: self.completeness,
         }
     }
+}
 
-    let mut ordered_frames = vec![first_frame.clone()];
-    let mut gaps = Vec::new();
-    let mut visited: BTreeMap<u64, bool> = BTreeMap::new();
-    visited.insert(first_frame.header.frame_id, true);
+/// Successor index: frames grouped by the hash they link back to
+///
+/// Each bucket lists positions (into the ID-sorted frame list) in ascending
+/// frame_id order. `next_unvisited` advances a per-bucket cursor past visited
+/// frames, so following a chain touches every index entry at most once.
+struct SuccessorIndex {
+    buckets: BTreeMap<[u8; BLAKE3_HASH_SIZE], (Vec<usize>, usize)>,
+}
 
-    let mut current_hash = first_frame.compute_hash();
-    let mut current_id = first_frame.header.frame_id;
+impl SuccessorIndex {
+    fn new<'a>(frames: impl Iterator<Item = &'a Frame>) -> Self {
+        let mut buckets: BTreeMap<[u8; BLAKE3_HASH_SIZE], (Vec<usize>, usize)> = BTreeMap::new();
+        for (i, f) in frames.enumerate() {
+            buckets.entry(f.header.prev_hash).or_default().0.push(i);
+        }
+        Self { buckets }
+    }
 
-    // Follow the chain forward by looking for frames that reference the current frame
-    loop {
-        // Find the next frame (one that has prev_hash matching current_hash)
-        let next_frame = frame_map.values().find(|f| {
-            !visited.contains_key(&f.header.frame_id) && f.header.prev_hash == current_hash
-        });
+    fn next_unvisited(&mut self, hash: &[u8; BLAKE3_HASH_SIZE], visited: &[bool]) -> Option<usize> {
+        let (list, cursor) = self.buckets.get_mut(hash)?;
+        while *cursor < list.len() && visited[list[*cursor]] {
+            *cursor += 1;
+        }
+        list.get(*cursor).copied()
+    }
+}
 
-        match next_frame {
-            Some(frame) => {
-                #[cfg(feature = "logging")]
-                tracing::debug!("Linked frame {} -> {}", current_id, frame.header.frame_id);
+/// Link frames into a timeline of indices, without cloning any frame
+///
+/// Accepts anything that exposes a [`Frame`]: `&[Frame]`, `&[LocatedFrame]`,
+/// `&[Arc<Frame>]`, ... This function:
+/// 1. Builds a map of frame_id -> index; the first frame with an ID wins, and
+///    later ones go to `duplicates` (same content) or `conflicting`
+/// 2. Follows back-links forward from the first frame (lowest ID with IS_FIRST)
+/// 3. Bridges breaks by continuing at the lowest unvisited frame_id, recording a gap
+///
+/// Successors are found through a `prev_hash` index and every frame is
+/// hashed once, so linking is O(n log n) in the number of frames.
+///
+/// Without any first frame the chain is rebuilt from hash links instead of
+/// IDs; see [`link_indexed_with_confidence`].
+pub fn link_indexed<T: AsRef<Frame>>(source: &[T]) -> TimelineIndex {
+    link_indexed_with_confidence(source, |_| 1.0)
+}
 
-                visited.insert(frame.header.frame_id, true);
-                ordered_frames.push(frame.clone());
-                current_hash = frame.compute_hash();
-                current_id = frame.header.frame_id;
+/// Like [`link_indexed`], anchoring headless reconstruction at confident frames
+///
+/// `confidence(i)` scores `source[i]` (e.g. [`LocatedFrame::confidence`]).
+/// When no first frame is present, chains are grown from the highest-confidence
+/// frames by walking predecessor hashes backward and successor hashes forward,
+/// so hash links win over frame IDs. A frame whose ID collides with another
+/// frame is placed by its links when it bridges two chains. Links to a
+/// repeated or lower ID are listed in [`TimelineIndex::renumbered`].
+pub fn link_indexed_with_confidence<T: AsRef<Frame>>(
+    source: &[T],
+    confidence: impl Fn(usize) -> f32,
+) -> TimelineIndex {
+    #[cfg(feature = "logging")]
+    tracing::debug!("Linking {} frames into timeline", source.len());
+
+    // Build lookup table: source indices in frame_id order. The first frame
+    // with a given ID is kept; later ones are exact duplicates or equivocations.
+    let mut id_map: BTreeMap<u64, usize> = BTreeMap::new();
+    let mut duplicates = Vec::new();
+    let mut conflicting = Vec::new();
+    for (i, item) in source.iter().enumerate() {
+        match id_map.entry(item.as_ref().header.frame_id) {
+            alloc::collections::btree_map::Entry::Vacant(e) => {
+                e.insert(i);
             }
-            None => {
-                // No matching frame found - check if there are unvisited frames
-                let unvisited: Vec<_> = frame_map
-                    .values()
-                    .filter(|f| !visited.contains_key(&f.header.frame_id))
-                    .collect();
-
-                if !unvisited.is_empty() {
+            alloc::collections::btree_map::Entry::Occupied(e) => {
+                if source[*e.get()].as_ref().compute_hash() == item.as_ref().compute_hash() {
+                    duplicates.push(i);
+                } else {
                     #[cfg(feature = "logging")]
                     tracing::warn!(
-                        "Gap detected after frame {}: {} unvisited frames remain",
-                        current_id,
-                        unvisited.len()
+                        "Frame ID {} seen again with different content",
+                        item.as_ref().header.frame_id
                     );
 
-                    // Try to find the next sequential frame by ID
-                    if let Some(next_by_id) = unvisited.iter().min_by_key(|f| f.header.frame_id) {
-                        gaps.push(SequenceGap {
-                            before: current_id,
-                            after: next_by_id.header.frame_id,
-                            expected_hash: Some(next_by_id.header.prev_hash),
-                        });
-
-                        visited.insert(next_by_id.header.frame_id, true);
-                        ordered_frames.push((*next_by_id).clone());
-                        current_hash = next_by_id.compute_hash();
-                        current_id = next_by_id.header.frame_id;
-                        continue;
-                    }
+                    conflicting.push(i);
                 }
+            }
+        }
+    }
+    let by_id: Vec<usize> = id_map.into_values().collect();
+    let frame = |pos: usize| source[by_id[pos]].as_ref();
 
-                break;
+    // Find first frame (lowest frame_id carrying IS_FIRST or an all-zero prev_hash)
+    let Some(first) = (0..by_id.len()).find(|&p| frame(p).header.is_first()) else {
+        #[cfg(feature = "logging")]
+        if !by_id.is_empty() {
+            tracing::warn!(
+                "No first frame found (prev_hash = 0), attempting to reconstruct anyway"
+            );
+        }
+
+        return TimelineIndex {
+            duplicates,
+            ..reconstruct_without_first(source, by_id, conflicting, &confidence)
+        };
+    };
+
+    #[cfg(feature = "logging")]
+    if (0..by_id.len())
+        .filter(|&p| frame(p).header.is_first())
+        .count()
+        > 1
+    {
+        tracing::warn!("Multiple first frames found, using lowest frame_id");
+    }
+
+    let mut index = SuccessorIndex::new((0..by_id.len()).map(frame));
+    let mut visited = vec![false; by_id.len()];
+    let mut order = Vec::with_capacity(by_id.len());
+    let mut gaps = Vec::new();
+    let mut renumbered = Vec::new();
+    // Lowest-ID frame not yet visited, used to bridge gaps
+    let mut lowest_unvisited = 0usize;
+
+    let mut current = first;
+    loop {
+        visited[current] = true;
+        order.push(by_id[current]);
+        let current_hash = frame(current).compute_hash();
+
+        // Follow the chain forward by looking for frames that reference the current frame
+        if let Some(next) = index.next_unvisited(&current_hash, &visited) {
+            #[cfg(feature = "logging")]
+            tracing::debug!(
+                "Linked frame {} -> {}",
+                frame(current).header.frame_id,
+                frame(next).header.frame_id
+            );
+
+            if renumbered_link(frame(current), frame(next)) {
+                renumbered.push(by_id[next]);
             }
+            current = next;
+            continue;
         }
+
+        // No matching frame found - continue at the next unvisited frame by ID
+        while lowest_unvisited < by_id.len() && visited[lowest_unvisited] {
+            lowest_unvisited += 1;
+        }
+        if lowest_unvisited == by_id.len() {
+            break;
+        }
+        let next_by_id = frame(lowest_unvisited);
+
+        #[cfg(feature = "logging")]
+        tracing::warn!(
+            "Gap detected after frame {}: {} unvisited frames remain",
+            frame(current).header.frame_id,
+            by_id.len() - order.len()
+        );
+
+        gaps.push(SequenceGap {
+            before: frame(current).header.frame_id,
+            after: next_by_id.header.frame_id,
+            expected_hash: Some(next_by_id.header.prev_hash),
+        });
+        current = lowest_unvisited;
     }
 
-    // Collect orphans (frames that were never visited)
-    let orphans: Vec<Frame> = frame_map
-        .into_iter()
-        .filter(|(id, _)| !visited.contains_key(id))
-        .map(|(_, frame)| frame)
+    // Anything never visited is an orphan
+    let orphans: Vec<usize> = by_id
+        .iter()
+        .zip(&visited)
+        .filter(|(_, &seen)| !seen)
+        .map(|(&i, _)| i)
         .collect();
 
     #[cfg(feature = "logging")]
     tracing::debug!(
         "Timeline reconstruction complete: {} ordered frames, {} gaps, {} orphans",
-        ordered_frames.len(),
+        order.len(),
         gaps.len(),
         orphans.len()
     );
 
-    Timeline {
-        frames: ordered_frames,
+    TimelineIndex {
+        frames: order,
         gaps,
         orphans,
+        duplicates,
+        conflicting,
+        renumbered,
     }
 }
 
-/// Reconstruct timeline when no first frame is available
-fn reconstruct_without_first(frame_map: BTreeMap<u64, Frame>) -> Timeline {
-    let mut frames: Vec<_> = frame_map.into_values().collect();
-    frames.sort_by_key(|f| f.header.frame_id);
+/// True when `next`, hash-linked to `prev`, repeats or goes back on its ID
+fn renumbered_link(prev: &Frame, next: &Frame) -> bool {
+    next.header.frame_id <= prev.header.frame_id
+}
 
-    // Detect gaps by looking at frame ID sequence
-    let mut gaps = Vec::new();
-    for window in frames.windows(2) {
-        let curr = &window[0];
-        let next = &window[1];
+/// Reconstruct a timeline when no first frame is available
+///
+/// Frame IDs are not trusted for ordering here: chains are grown from anchors,
+/// highest confidence first, by following `prev_hash` backward and successors
+/// forward. Versions of a reused ID join only where they bridge the tail of
+/// one chain to the head of another; the rest stay in `conflicting`. Chains
+/// are then ordered by the ID of their head, with a gap between consecutive
+/// chains whose endpoints are in ID order even when the chains overlap.
+fn reconstruct_without_first<T: AsRef<Frame>>(
+    source: &[T],
+    by_id: Vec<usize>,
+    conflicting: Vec<usize>,
+    confidence: &dyn Fn(usize) -> f32,
+) -> TimelineIndex {
+    let frame = |i: usize| source[i].as_ref();
+    let hashes: Vec<[u8; BLAKE3_HASH_SIZE]> =
+        by_id.iter().map(|&i| frame(i).compute_hash()).collect();
+    let by_hash: BTreeMap<[u8; BLAKE3_HASH_SIZE], usize> =
+        hashes.iter().enumerate().map(|(p, h)| (*h, p)).collect();
+    let mut successors = SuccessorIndex::new(by_id.iter().map(|&i| frame(i)));
+    let mut visited = vec![false; by_id.len()];
+    let mut renumbered = Vec::new();
+
+    let mut anchors: Vec<usize> = (0..by_id.len()).collect();
+    anchors.sort_by(|&a, &b| {
+        confidence(by_id[b])
+            .total_cmp(&confidence(by_id[a]))
+            .then(a.cmp(&b))
+    });
+
+    // Grow one chain (source indices) per unvisited anchor
+    let mut chains: Vec<VecDeque<usize>> = Vec::new();
+    for anchor in anchors {
+        if visited[anchor] {
+            continue;
+        }
+        visited[anchor] = true;
+        let mut chain = VecDeque::from([by_id[anchor]]);
 
-        // Check if there's a gap in IDs or hash mismatch
-        if next.header.frame_id != curr.header.frame_id + 1
-            || next.header.prev_hash != curr.compute_hash()
-        {
+        let mut current = anchor;
+        while let Some(&prev) = by_hash.get(&frame(by_id[current]).header.prev_hash) {
+            if visited[prev] {
+                break;
+            }
+            visited[prev] = true;
+            chain.push_front(by_id[prev]);
+            current = prev;
+        }
+
+        let mut current = anchor;
+        while let Some(next) = successors.next_unvisited(&hashes[current], &visited) {
+            visited[next] = true;
+            chain.push_back(by_id[next]);
+            current = next;
+        }
+        chains.push(chain);
+    }
+
+    // A reused ID that links one chain's tail to another's head is a renumbered
+    // frame, not an equivocation: splice it in
+    let mut chain_by_tail: BTreeMap<usize, usize> = chains
+        .iter()
+        .enumerate()
+        .map(|(c, chain)| (chain[chain.len() - 1], c))
+        .collect();
+    let mut chain_by_head_link: BTreeMap<[u8; BLAKE3_HASH_SIZE], usize> = BTreeMap::new();
+    for (c, chain) in chains.iter().enumerate() {
+        chain_by_head_link
+            .entry(frame(chain[0]).header.prev_hash)
+            .or_insert(c);
+    }
+    let mut unplaced = Vec::new();
+    for i in conflicting {
+        let tail = by_hash
+            .get(&frame(i).header.prev_hash)
+            .and_then(|&p| chain_by_tail.get(&by_id[p]).copied());
+        let head = chain_by_head_link.get(&frame(i).compute_hash()).copied();
+        match (tail, head) {
+            (Some(t), Some(h)) if t != h => {
+                let mut rest = core::mem::take(&mut chains[h]);
+                chain_by_head_link.retain(|_, c| *c != h);
+                chain_by_tail.remove(&chains[t][chains[t].len() - 1]);
+                chain_by_tail.insert(rest[rest.len() - 1], t);
+                chains[t].push_back(i);
+                chains[t].append(&mut rest);
+            }
+            _ => unplaced.push(i),
+        }
+    }
+    chains.retain(|c| !c.is_empty());
+    chains.sort_by_key(|c| (frame(c[0]).header.frame_id, c[0]));
+
+    let mut order = Vec::with_capacity(by_id.len());
+    let mut gaps = Vec::new();
+    for chain in chains {
+        if let Some(&last) = order.last() {
+            // Chains can overlap in IDs (e.g. the losing branch of a fork);
+            // gap endpoints stay in ID order
+            let head = frame(chain[0]);
+            let (a, b) = (frame(last).header.frame_id, head.header.frame_id);
             gaps.push(SequenceGap {
-                before: curr.header.frame_id,
-                after: next.header.frame_id,
-                expected_hash: Some(next.header.prev_hash),
+                before: a.min(b),
+                after: a.max(b),
+                expected_hash: Some(head.header.prev_hash),
             });
         }
+        for (k, &i) in chain.iter().enumerate() {
+            if k > 0 && renumbered_link(frame(chain[k - 1]), frame(i)) {
+                renumbered.push(i);
+            }
+            order.push(i);
+        }
     }
 
-    Timeline {
-        frames,
+    TimelineIndex {
+        frames: order,
         gaps,
-        orphans: Vec::new(),
+        conflicting: unplaced,
+        renumbered,
+        ..Default::default()
     }
 }
 
+/// Link frames into a timeline using their IDs and back-links
+///
+/// Convenience wrapper over [`link_indexed`] that moves the frames into an
+/// owned [`Timeline`]; no frame is cloned.
+pub fn link_frames(frames: Vec<Frame>) -> Timeline {
+    link_indexed(&frames).into_timeline(frames)
+}
+
 /// Link located frames (from scanner) into a timeline
 pub fn link_located_frames(located_frames: Vec<LocatedFrame>) -> Timeline {
     let frames: Vec<Frame> = located_frames.into_iter().map(|lf| lf.frame).collect();
@@ -275,41 +715,47 @@ pub fn link_located_frames(located_frames: Vec<LocatedFrame>) -> Timeline {
 
 /// Analyze a set of frames and produce a detailed report
 pub fn analyze_timeline(frames: Vec<Frame>) -> TimelineReport {
-    let timeline = link_frames(frames);
-    build_report_from_timeline(&timeline, None)
+    let index = link_indexed(&frames);
+    build_report(&frames, index, None).into_report(frames)
 }
 
 /// Analyze located frames (with offsets) to include byte-offset recipes
 pub fn analyze_located_frames(located_frames: Vec<LocatedFrame>) -> TimelineReport {
-    let frames: Vec<Frame> = located_frames.iter().map(|lf| lf.frame.clone()).collect();
-    let timeline = link_frames(frames);
-    build_report_from_timeline(&timeline, Some(&located_frames))
+    let report = analyze_indexed(&located_frames);
+    let frames: Vec<Frame> = located_frames.into_iter().map(|lf| lf.frame).collect();
+    report.into_report(frames)
 }
 
-fn build_report_from_timeline(
-    timeline: &Timeline,
+/// Analyze located frames in place, returning a report of indices into `located_frames`
+pub fn analyze_indexed(located_frames: &[LocatedFrame]) -> TimelineIndexReport {
+    let index = link_indexed_with_confidence(located_frames, |i| located_frames[i].confidence);
+    build_report(located_frames, index, Some(located_frames))
+}
+
+fn build_report<T: AsRef<Frame>>(
+    source: &[T],
+    index: TimelineIndex,
     located: Option<&[LocatedFrame]>,
-) -> TimelineReport {
-    // Build map: frame_id -> frame and hash -> frame_id
-    let mut id_map: BTreeMap<u64, &Frame> = BTreeMap::new();
+) -> TimelineIndexReport {
+    // Build map: frame_id -> (frame, hash) and hash -> frame_id, hashing each frame once
+    let mut id_map: BTreeMap<u64, (&Frame, [u8; BLAKE3_HASH_SIZE])> = BTreeMap::new();
     let mut hash_to_id: BTreeMap<[u8; BLAKE3_HASH_SIZE], u64> = BTreeMap::new();
 
-    for f in timeline.frames.iter().chain(timeline.orphans.iter()) {
-        id_map.insert(f.header.frame_id, f);
-        hash_to_id.insert(f.compute_hash(), f.header.frame_id);
+    for f in index.ordered(source).chain(index.orphaned(source)) {
+        let hash = f.compute_hash();
+        id_map.insert(f.header.frame_id, (f, hash));
+        hash_to_id.insert(hash, f.header.frame_id);
     }
-
     // Classify gaps
     let mut gap_details = Vec::new();
-    for g in &timeline.gaps {
+    for g in &index.gaps {
         let reason = if g.after != g.before + 1 {
             GapReason::MissingById
         } else {
             // If both frames exist, check backlink
             match (id_map.get(&g.before), id_map.get(&g.after)) {
-                (Some(prev), Some(next)) => {
-                    let expected = prev.compute_hash();
-                    if next.header.prev_hash == expected {
+                (Some((_, expected)), Some((next, _))) => {
+                    if next.header.prev_hash == *expected {
                         // Strictly contiguous by ID with matching hash shouldn't be a gap,
                         // but if it is in the list, default to MissingById for safety
                         GapReason::MissingById
@@ -328,7 +774,7 @@ fn build_report_from_timeline(
 
     // Conflicts: multiple frames that reference the same predecessor's hash
     let mut preds_to_successors: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
-    for f in id_map.values() {
+    for (f, _) in id_map.values() {
         if let Some(&pred_id) = hash_to_id.get(&f.header.prev_hash) {
             preds_to_successors
                 .entry(pred_id)
@@ -348,10 +794,46 @@ fn build_report_from_timeline(
         }
     }
 
+    // Equivocations: frames skipped because their ID was already taken by different content
+    let kept_by_id: BTreeMap<u64, usize> = index
+        .frames
+        .iter()
+        .chain(&index.orphans)
+        .map(|&i| (source[i].as_ref().header.frame_id, i))
+        .collect();
+    let mut versions: BTreeMap<u64, Vec<usize>> = BTreeMap::new();
+    for &i in &index.conflicting {
+        let fid = source[i].as_ref().header.frame_id;
+        versions
+            .entry(fid)
+            .or_insert_with(|| vec![kept_by_id[&fid]])
+            .push(i);
+    }
+    let mut equivocations = Vec::new();
+    for (frame_id, sites) in versions {
+        let mut hashes = Vec::new();
+        let mut distinct = Vec::new();
+        for i in sites {
+            let hash = source[i].as_ref().compute_hash();
+            if !hashes.contains(&hash) {
+                hashes.push(hash);
+                distinct.push(i);
+            }
+        }
+        let offsets = located
+            .map(|locs| distinct.iter().map(|&i| locs[i].offset).collect())
+            .unwrap_or_default();
+        equivocations.push(Equivocation {
+            frame_id,
+            hashes,
+            offsets,
+        });
+    }
+
     // Orphan clusters: connected components among orphans linking by hash relationships
-    let orphan_set: BTreeSet<u64> = timeline.orphans.iter().map(|f| f.header.frame_id).collect();
+    let orphan_set: BTreeSet<u64> = index.orphaned(source).map(|f| f.header.frame_id).collect();
     let mut orphan_links: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
-    for f in &timeline.orphans {
+    for f in index.orphaned(source) {
         let fid = f.header.frame_id;
         // Link to predecessor if it is also an orphan
         if let Some(&pred_id) = hash_to_id.get(&f.header.prev_hash) {
@@ -360,12 +842,6 @@ fn build_report_from_timeline(
                 orphan_links.entry(pred_id).or_default().push(fid);
             }
         }
-        // Link to successors among orphans
-        for (&h, &hid) in &hash_to_id {
-            if orphan_set.contains(&hid) && f.header.prev_hash == h {
-                // already handled by predecessor check above when roles swap
-            }
-        }
     }
     // BFS components
     let mut visited = BTreeSet::new();
@@ -403,9 +879,11 @@ fn build_report_from_timeline(
     }
     // Rewind/advance offsets if we have offsets
     if let Some(locs) = located {
-        // Build offset map and size map by frame_id
+        // Build offset map and size map by frame_id from the linked copies,
+        // so an equivocating later copy does not move the gap's endpoints
         let mut off: BTreeMap<u64, (usize, usize)> = BTreeMap::new();
-        for lf in locs.iter() {
+        for &i in index.frames.iter().chain(&index.orphans) {
+            let lf = &locs[i];
             off.insert(lf.frame.header.frame_id, (lf.offset, lf.size));
         }
         for gd in &gap_details {
@@ -425,12 +903,15 @@ fn build_report_from_timeline(
         }
     }
 
-    TimelineReport {
-        timeline: timeline.clone(),
+    let completeness = index.completeness(source);
+    TimelineIndexReport {
+        index,
         gap_details,
         conflicts,
+        equivocations,
         orphan_clusters,
         recipes,
+        completeness,
     }
 }
 
@@ -439,22 +920,227 @@ fn build_report_from_timeline(
 /// Returns errors for any frames where the prev_hash doesn't match
 /// the actual hash of the previous frame.
 pub fn verify_backlinks(timeline: &Timeline) -> Vec<FrameError> {
-    let mut errors = Vec::new();
+    check_backlinks(timeline.frames.iter())
+}
 
-    for window in timeline.frames.windows(2) {
-        let prev = &window[0];
-        let curr = &window[1];
+/// [`verify_backlinks`] for a [`TimelineIndex`] over the slice it was built from
+pub fn verify_backlinks_indexed<T: AsRef<Frame>>(
+    index: &TimelineIndex,
+    source: &[T],
+) -> Vec<FrameError> {
+    check_backlinks(index.ordered(source))
+}
 
-        let expected_hash = prev.compute_hash();
+fn check_backlinks<'a>(mut frames: impl Iterator<Item = &'a Frame>) -> Vec<FrameError> {
+    let mut errors = Vec::new();
 
+    // Each frame is hashed once; its hash is the expected back-link of the next
+    let Some(first) = frames.next() else {
+        return errors;
+    };
+    let mut expected_hash = first.compute_hash();
+    for curr in frames {
         if curr.header.prev_hash != expected_hash {
             errors.push(FrameError::BackLinkMismatch(curr.header.frame_id));
         }
+        expected_hash = curr.compute_hash();
     }
 
     errors
 }
 
+/// How [`link_dag`] chooses between branches where the chain forks
+#[derive(Clone, Copy)]
+pub enum BranchPolicy<'a> {
+    /// Follow the branch with the most frames
+    Longest,
+    /// Follow the branch with the highest summed scanner confidence
+    Confidence,
+    /// Follow the branch with the most frames passing the given signature check
+    SignatureValidity(&'a dyn Fn(&Frame) -> bool),
+    /// Follow the successor that appears earliest in the stream
+    EarliestOffset,
+}
+
+impl core::fmt::Debug for BranchPolicy<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Longest => f.write_str("Longest"),
+            Self::Confidence => f.write_str("Confidence"),
+            Self::SignatureValidity(_) => f.write_str("SignatureValidity"),
+            Self::EarliestOffset => f.write_str("EarliestOffset"),
+        }
+    }
+}
+
+/// A predecessor referenced by more than one successor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fork {
+    /// Source index of the shared predecessor
+    pub at: usize,
+    /// Source indices of the first frame of each branch, in stream order
+    pub branches: Vec<usize>,
+    /// The branch the policy selected
+    pub chosen: usize,
+}
+
+/// The full hash-link graph of a capture, with every branch kept
+///
+/// Each frame links to exactly one predecessor, so the graph is a forest:
+/// `roots` start the trees (their predecessor is missing), and `forks` list
+/// every node with more than one successor. All indices refer to the
+/// `LocatedFrame` slice passed to [`link_dag`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimelineDag {
+    /// One node per distinct frame content (first occurrence), in stream order
+    pub nodes: Vec<usize>,
+    /// Hash links as (predecessor, successor)
+    pub edges: Vec<(usize, usize)>,
+    /// Nodes whose predecessor is not present
+    pub roots: Vec<usize>,
+    /// Predecessors with more than one successor
+    pub forks: Vec<Fork>,
+    /// Best path under the chosen policy, from a root to a leaf
+    pub main_path: Vec<usize>,
+}
+
+impl TimelineDag {
+    /// Successors of `node`, in stream order
+    pub fn successors(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        let start = self.edges.partition_point(|&(p, _)| p < node);
+        self.edges[start..]
+            .iter()
+            .take_while(move |&&(p, _)| p == node)
+            .map(|&(_, s)| s)
+    }
+}
+
+/// Best path starting at a node, used to compare branches
+#[derive(Debug, Clone, Copy, Default)]
+struct BranchScore {
+    len: usize,
+    confidence: f64,
+    signed: usize,
+}
+
+/// Link located frames into a fork-aware DAG and pick a main path
+///
+/// Unlike [`link_indexed`], which forces a single chain, every hash link is
+/// kept. Frames with identical content are merged into one node. At each
+/// fork the branch preferred by `policy` is followed (ties go to the branch
+/// that appears earlier in the stream); the main path starts at the best
+/// root carrying IS_FIRST, or at the best root overall if none does.
+pub fn link_dag(located: &[LocatedFrame], policy: BranchPolicy<'_>) -> TimelineDag {
+    let n = located.len();
+    let mut by_hash: BTreeMap<[u8; BLAKE3_HASH_SIZE], usize> = BTreeMap::new();
+    let mut nodes = Vec::new();
+    for (i, lf) in located.iter().enumerate() {
+        if let alloc::collections::btree_map::Entry::Vacant(e) =
+            by_hash.entry(lf.frame.compute_hash())
+        {
+            e.insert(i);
+            nodes.push(i);
+        }
+    }
+
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut roots = Vec::new();
+    for &i in &nodes {
+        match by_hash.get(&located[i].frame.header.prev_hash) {
+            Some(&p) if p != i => children[p].push(i),
+            _ => roots.push(i),
+        }
+    }
+    let mut edges: Vec<(usize, usize)> = nodes
+        .iter()
+        .flat_map(|&p| children[p].iter().map(move |&c| (p, c)))
+        .collect();
+    edges.sort_unstable();
+
+    let better = |a: usize, sa: BranchScore, b: usize, sb: BranchScore| -> bool {
+        let ord = match policy {
+            BranchPolicy::Longest => sa
+                .len
+                .cmp(&sb.len)
+                .then(sa.confidence.total_cmp(&sb.confidence)),
+            BranchPolicy::Confidence => sa
+                .confidence
+                .total_cmp(&sb.confidence)
+                .then(sa.len.cmp(&sb.len)),
+            BranchPolicy::SignatureValidity(_) => {
+                sa.signed.cmp(&sb.signed).then(sa.len.cmp(&sb.len))
+            }
+            BranchPolicy::EarliestOffset => core::cmp::Ordering::Equal,
+        };
+        // Ties (and EarliestOffset) go to the frame seen first in the stream
+        ord.then(located[b].offset.cmp(&located[a].offset)).is_gt()
+    };
+
+    // Score every node from the leaves up (iterative post-order; chains can be very long)
+    let mut score = vec![BranchScore::default(); n];
+    let mut next: Vec<Option<usize>> = vec![None; n];
+    let mut stack: Vec<(usize, bool)> = roots.iter().rev().map(|&r| (r, false)).collect();
+    while let Some((i, expanded)) = stack.pop() {
+        if !expanded {
+            stack.push((i, true));
+            stack.extend(children[i].iter().map(|&c| (c, false)));
+            continue;
+        }
+        let mut best: Option<usize> = None;
+        for &c in &children[i] {
+            if best.is_none_or(|b| better(c, score[c], b, score[b])) {
+                best = Some(c);
+            }
+        }
+        let tail = best.map(|b| score[b]).unwrap_or_default();
+        let signed = match policy {
+            BranchPolicy::SignatureValidity(check) => check(&located[i].frame) as usize,
+            _ => 0,
+        };
+        score[i] = BranchScore {
+            len: tail.len + 1,
+            confidence: tail.confidence + located[i].confidence as f64,
+            signed: tail.signed + signed,
+        };
+        next[i] = best;
+    }
+
+    let forks = nodes
+        .iter()
+        .filter(|&&i| children[i].len() > 1)
+        .map(|&i| Fork {
+            at: i,
+            branches: children[i].clone(),
+            chosen: next[i].unwrap_or(children[i][0]),
+        })
+        .collect();
+
+    let has_first = roots.iter().any(|&r| located[r].frame.header.is_first());
+    let mut start: Option<usize> = None;
+    for &r in &roots {
+        if has_first && !located[r].frame.header.is_first() {
+            continue;
+        }
+        if start.is_none_or(|s| better(r, score[r], s, score[s])) {
+            start = Some(r);
+        }
+    }
+    let mut main_path = Vec::new();
+    let mut cursor = start;
+    while let Some(i) = cursor {
+        main_path.push(i);
+        cursor = next[i];
+    }
+
+    TimelineDag {
+        nodes,
+        edges,
+        roots,
+        forks,
+        main_path,
+    }
+}
+
 /// Timeline statistics
 #[derive(Debug, Clone)]
 pub struct TimelineStats {
@@ -471,22 +1157,38 @@ pub struct TimelineStats {
     pub continuity: f64,
 }
 
+fn timeline_stats(ordered: usize, orphans: usize, gaps: usize) -> TimelineStats {
+    let total = ordered + orphans;
+    let continuity = if total == 0 {
+        0.0
+    } else {
+        (ordered as f64 / total as f64) * 100.0
+    };
+
+    TimelineStats {
+        total_frames: total,
+        gaps,
+        orphans,
+        continuity,
+    }
+}
+
 impl Timeline {
+    /// Frames skipped because an earlier frame with the same ID but different
+    /// content was linked (see [`Equivocation`])
+    pub fn conflicting(&self) -> &[Frame] {
+        &self.conflicting
+    }
+
     /// Get statistics about this timeline
     pub fn stats(&self) -> TimelineStats {
-        let total = self.frames.len() + self.orphans.len();
-        let continuity = if total == 0 {
-            0.0
-        } else {
-            (self.frames.len() as f64 / total as f64) * 100.0
-        };
+        timeline_stats(self.frames.len(), self.orphans.len(), self.gaps.len())
+    }
 
-        TimelineStats {
-            total_frames: total,
-            gaps: self.gaps.len(),
-            orphans: self.orphans.len(),
-            continuity,
-        }
+    /// Check IS_FIRST/IS_LAST to tell a whole sequence from a truncated one
+    pub fn completeness(&self) -> Completeness {
+        let chain: Vec<&Frame> = self.frames.iter().collect();
+        completeness(&chain, self.orphans.iter(), self.gaps.len())
     }
 
     /// Seek to a target frame ID using skip-list backlinks if available
@@ -537,21 +1239,55 @@ impl Timeline {
 
 /// Render a TimelineReport as a Graphviz DOT string
 pub fn report_to_dot(report: &TimelineReport) -> String {
+    let ids: Vec<u64> = report
+        .timeline
+        .frames
+        .iter()
+        .map(|f| f.header.frame_id)
+        .collect();
+    render_dot(
+        &ids,
+        &report.gap_details,
+        &report.conflicts,
+        &report.orphan_clusters,
+        &report.recipes,
+    )
+}
+
+/// Render a TimelineIndexReport as a Graphviz DOT string
+pub fn index_report_to_dot<T: AsRef<Frame>>(report: &TimelineIndexReport, source: &[T]) -> String {
+    let ids: Vec<u64> = report
+        .index
+        .ordered(source)
+        .map(|f| f.header.frame_id)
+        .collect();
+    render_dot(
+        &ids,
+        &report.gap_details,
+        &report.conflicts,
+        &report.orphan_clusters,
+        &report.recipes,
+    )
+}
+
+fn render_dot(
+    ordered_ids: &[u64],
+    gap_details: &[GapDetail],
+    conflicts: &[ChainConflict],
+    orphan_clusters: &[OrphanCluster],
+    recipes: &[RecoveryRecipe],
+) -> String {
     use core::fmt::Write as _;
     let mut s = String::new();
     let _ = writeln!(&mut s, "digraph timeline {{");
     let _ = writeln!(&mut s, "  rankdir=LR;");
 
     // Nodes: ordered frames
-    for f in &report.timeline.frames {
-        let _ = writeln!(
-            &mut s,
-            "  {} [label=\"{}\"];",
-            f.header.frame_id, f.header.frame_id
-        );
+    for id in ordered_ids {
+        let _ = writeln!(&mut s, "  {} [label=\"{}\"];", id, id);
     }
     // Orphan nodes grouped into clusters
-    for (idx, cluster) in report.orphan_clusters.iter().enumerate() {
+    for (idx, cluster) in orphan_clusters.iter().enumerate() {
         let _ = writeln!(&mut s, "  subgraph cluster_orphans_{} {{", idx);
         let _ = writeln!(&mut s, "    label=\"orphan cluster #{}\";", idx);
         let _ = writeln!(&mut s, "    style=dashed; color=gray;");
@@ -562,14 +1298,12 @@ pub fn report_to_dot(report: &TimelineReport) -> String {
     }
 
     // Edges: ordered links
-    for win in report.timeline.frames.windows(2) {
-        let a = win[0].header.frame_id;
-        let b = win[1].header.frame_id;
-        let _ = writeln!(&mut s, "  {} -> {};", a, b);
+    for win in ordered_ids.windows(2) {
+        let _ = writeln!(&mut s, "  {} -> {};", win[0], win[1]);
     }
 
     // Gaps with reasons
-    for gd in &report.gap_details {
+    for gd in gap_details {
         let label = match gd.reason {
             GapReason::MissingById => "gap: missing-by-id",
             GapReason::MissingByHash => "gap: missing-by-hash",
@@ -582,7 +1316,7 @@ pub fn report_to_dot(report: &TimelineReport) -> String {
     }
 
     // Conflicts at a predecessor
-    for c in &report.conflicts {
+    for c in conflicts {
         for succ in &c.contenders {
             let _ = writeln!(
                 &mut s,
@@ -593,7 +1327,7 @@ pub fn report_to_dot(report: &TimelineReport) -> String {
     }
 
     // Recovery recipes as notes
-    for (i, r) in report.recipes.iter().enumerate() {
+    for (i, r) in recipes.iter().enumerate() {
         match r {
             RecoveryRecipe::InsertParityFrame { between, reason } => {
                 let _ = writeln!(
@@ -623,6 +1357,9 @@ pub fn report_to_dot(report: &TimelineReport) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::constants::FrameFlags;
+    use crate::encoder::FrameBuilder;
+    use crate::test_util::{chain, chain_from, plain};
     use crate::types::FrameHeader;
     use bytes::Bytes;
 
@@ -703,9 +1440,419 @@ mod tests {
             frames: vec![frame1, frame2],
             gaps: Vec::new(),
             orphans: Vec::new(),
+            conflicting: Vec::new(),
         };
 
         let errors = verify_backlinks(&timeline);
         assert_eq!(errors.len(), 0);
     }
+
+    #[test]
+    fn test_link_large_shuffled_chain() {
+        let mut frames = chain(1..=20_000, plain);
+        // Deterministic shuffle
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        for i in (1..frames.len()).rev() {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            frames.swap(i, (state % (i as u64 + 1)) as usize);
+        }
+
+        let timeline = link_frames(frames);
+        assert_eq!(timeline.frames.len(), 20_000);
+        assert!(timeline.gaps.is_empty());
+        assert!(timeline
+            .frames
+            .iter()
+            .enumerate()
+            .all(|(i, f)| f.header.frame_id == i as u64 + 1));
+        assert!(verify_backlinks(&timeline).is_empty());
+    }
+
+    #[test]
+    fn test_link_bridges_gaps_in_id_order() {
+        let mut frames = chain(1..=6, plain);
+        frames.remove(3); // drop frame 4
+        frames.remove(1); // drop frame 2
+
+        let timeline = link_frames(frames);
+        let ids: Vec<u64> = timeline.frames.iter().map(|f| f.header.frame_id).collect();
+        assert_eq!(ids, vec![1, 3, 5, 6]);
+        let gaps: Vec<(u64, u64)> = timeline.gaps.iter().map(|g| (g.before, g.after)).collect();
+        assert_eq!(gaps, vec![(1, 3), (3, 5)]);
+    }
+
+    #[test]
+    fn test_link_indexed_matches_owned_timeline() {
+        let mut frames = chain(1..=8, plain);
+        frames.remove(4);
+        frames.reverse();
+
+        let index = link_indexed(&frames);
+        let owned = link_frames(frames.clone());
+        let ids: Vec<u64> = index.ordered(&frames).map(|f| f.header.frame_id).collect();
+        let owned_ids: Vec<u64> = owned.frames.iter().map(|f| f.header.frame_id).collect();
+        assert_eq!(ids, owned_ids);
+        assert_eq!(index.gaps, owned.gaps);
+        assert_eq!(index.stats().continuity, owned.stats().continuity);
+        assert_eq!(index.to_timeline(&frames).frames.len(), owned.frames.len());
+
+        // Shared frames link the same way without cloning
+        let shared: Vec<alloc::sync::Arc<Frame>> =
+            frames.into_iter().map(alloc::sync::Arc::new).collect();
+        assert_eq!(link_indexed(&shared), index);
+    }
+
+    #[test]
+    fn test_analyze_indexed_over_located_frames() {
+        let mut offset = 0;
+        let located: Vec<LocatedFrame> = chain(1..=5, plain)
+            .into_iter()
+            .filter(|f| f.header.frame_id != 3)
+            .map(|frame| {
+                offset += 100;
+                locate(frame, offset - 100, 1.0)
+            })
+            .collect();
+
+        let report = analyze_indexed(&located);
+        assert_eq!(report.index.frames, vec![0, 1, 2, 3]);
+        assert_eq!(report.gap_details.len(), 1);
+        assert_eq!(report.gap_details[0].gap.before, 2);
+        assert_eq!(verify_backlinks_indexed(&report.index, &located).len(), 1);
+
+        let owned = analyze_located_frames(located);
+        assert_eq!(owned.gap_details, report.gap_details);
+        assert_eq!(owned.recipes, report.recipes);
+    }
+
+    fn locate(frame: Frame, offset: usize, confidence: f32) -> LocatedFrame {
+        LocatedFrame {
+            offset,
+            frame,
+            size: 100,
+            confidence,
+            bit_shift: 0,
+            inverted: false,
+            repair: None,
+        }
+    }
+
+    fn child(parent: &Frame, id: u64, payload: &'static str) -> Frame {
+        Frame::new(
+            FrameHeader::new(id, parent.compute_hash(), payload.len() as u32),
+            Bytes::from(payload),
+        )
+    }
+
+    /// Chain 1-2-3 forking after 3 into a long low-confidence branch (4a, 5a)
+    /// and a short high-confidence one (4b) that appears first in the stream
+    fn forked() -> Vec<LocatedFrame> {
+        let base = chain(1..=3, plain);
+        let a4 = child(&base[2], 4, "a4");
+        let a5 = child(&a4, 5, "a5");
+        let b4 = child(&base[2], 4, "signed b4");
+        let mut located: Vec<LocatedFrame> = base
+            .into_iter()
+            .enumerate()
+            .map(|(i, f)| locate(f, i * 100, 0.9))
+            .collect();
+        located.push(locate(b4, 300, 0.95));
+        located.push(locate(a4, 400, 0.3));
+        located.push(locate(a5, 500, 0.3));
+        located
+    }
+
+    #[test]
+    fn test_dag_keeps_all_branches() {
+        let located = forked();
+        let dag = link_dag(&located, BranchPolicy::Longest);
+        assert_eq!(dag.nodes.len(), 6);
+        assert_eq!(dag.roots, vec![0]);
+        assert_eq!(dag.edges.len(), 5);
+        assert_eq!(
+            dag.forks,
+            vec![Fork {
+                at: 2,
+                branches: vec![3, 4],
+                chosen: 4,
+            }]
+        );
+        assert_eq!(dag.successors(2).collect::<Vec<_>>(), vec![3, 4]);
+        assert_eq!(dag.main_path, vec![0, 1, 2, 4, 5]);
+    }
+
+    #[test]
+    fn test_dag_branch_policies() {
+        let located = forked();
+        let path = |policy| link_dag(&located, policy).main_path;
+
+        assert_eq!(path(BranchPolicy::Confidence), vec![0, 1, 2, 3]);
+        assert_eq!(path(BranchPolicy::EarliestOffset), vec![0, 1, 2, 3]);
+
+        let signed = |f: &Frame| f.payload.starts_with(b"signed");
+        assert_eq!(
+            path(BranchPolicy::SignatureValidity(&signed)),
+            vec![0, 1, 2, 3]
+        );
+
+        // Confidence is summed along the branch, so a long, fairly confident branch wins
+        let mut located = located;
+        located[4].confidence = 0.9;
+        located[5].confidence = 0.9;
+        let dag = link_dag(&located, BranchPolicy::Confidence);
+        assert_eq!(dag.main_path, vec![0, 1, 2, 4, 5]);
+        assert_eq!(dag.forks[0].chosen, 4);
+    }
+
+    #[test]
+    fn test_dag_merges_identical_frames() {
+        let mut located = forked();
+        let dup = located[1].frame.clone();
+        located.push(locate(dup, 600, 0.9));
+
+        let dag = link_dag(&located, BranchPolicy::Longest);
+        assert_eq!(dag.nodes.len(), 6);
+        assert!(!dag.nodes.contains(&6));
+        assert_eq!(dag.main_path, vec![0, 1, 2, 4, 5]);
+    }
+
+    #[test]
+    fn test_duplicates_and_equivocations() {
+        let frames = chain(1..=3, plain);
+        let forged = Frame::new(
+            FrameHeader::new(2, frames[0].compute_hash(), 6),
+            Bytes::from("forged"),
+        );
+        let mut located: Vec<LocatedFrame> = frames
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(i, f)| locate(f, i * 100, 1.0))
+            .collect();
+        located.push(locate(frames[1].clone(), 300, 1.0)); // exact replay
+        located.push(locate(forged.clone(), 400, 1.0)); // rewrite
+
+        let index = link_indexed(&located);
+        assert_eq!(index.frames, vec![0, 1, 2]);
+        assert_eq!(index.duplicates, vec![3]);
+        assert_eq!(index.conflicting, vec![4]);
+        assert!(index.gaps.is_empty());
+
+        // The owned timeline keeps the losing copy instead of dropping it
+        let timeline = link_located_frames(located.clone());
+        assert_eq!(timeline.frames.len(), 3);
+        assert_eq!(timeline.conflicting(), core::slice::from_ref(&forged));
+
+        let report = analyze_indexed(&located);
+        assert_eq!(
+            report.equivocations,
+            vec![Equivocation {
+                frame_id: 2,
+                hashes: vec![frames[1].compute_hash(), forged.compute_hash()],
+                offsets: vec![100, 400],
+            }]
+        );
+
+        // Without offsets the finding is still reported
+        let owned = analyze_timeline(located.into_iter().map(|lf| lf.frame).collect());
+        assert_eq!(owned.equivocations.len(), 1);
+        assert!(owned.equivocations[0].offsets.is_empty());
+    }
+
+    #[test]
+    fn test_rewind_recipe_uses_linked_copy_offsets() {
+        let frames = chain(1..=4, plain);
+        let forged = Frame::new(
+            FrameHeader::new(2, frames[0].compute_hash(), 6),
+            Bytes::from("forged"),
+        );
+        // Frame 3 is lost; a rewritten frame 2 turns up after frame 4
+        let located = vec![
+            locate(frames[0].clone(), 0, 1.0),
+            locate(frames[1].clone(), 100, 1.0),
+            locate(frames[3].clone(), 300, 1.0),
+            locate(forged, 500, 1.0),
+        ];
+
+        let report = analyze_indexed(&located);
+        assert_eq!(report.index.conflicting, vec![3]);
+        let rewinds: Vec<_> = report
+            .recipes
+            .iter()
+            .filter_map(|r| match r {
+                RecoveryRecipe::RewindOffset {
+                    near_frame,
+                    by_bytes,
+                    ..
+                } => Some((*near_frame, *by_bytes)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(rewinds, vec![(4, 100)]);
+    }
+
+    /// Flags frame 4, the end of the four-frame test chains, IS_LAST
+    fn last_marked(id: u64, builder: FrameBuilder) -> FrameBuilder {
+        if id == 4 {
+            builder.mark_last()
+        } else {
+            builder
+        }
+    }
+
+    #[test]
+    fn test_completeness_first_through_last() {
+        let complete = link_frames(chain(1..=4, last_marked)).completeness();
+        assert!(complete.is_complete());
+        assert_eq!(complete.end, SequenceEnd::Clean { last: 4 });
+
+        // No IS_LAST: the tail may have been cut off
+        let truncated = link_frames(chain(1..=4, plain)).completeness();
+        assert!(!truncated.is_complete());
+        assert_eq!(truncated.end, SequenceEnd::Truncated { last_seen: 4 });
+
+        // Zero prev_hash alone does not prove the start
+        let unmarked = link_frames(chain_from(1..=4, &mut [0; 32], last_marked)).completeness();
+        assert!(!unmarked.starts_at_first);
+        assert!(!unmarked.is_complete());
+
+        // A missing middle frame keeps the sequence incomplete even with both ends
+        let mut frames = chain(1..=4, last_marked);
+        frames.remove(2);
+        let report = analyze_timeline(frames);
+        assert_eq!(report.completeness.gaps, 1);
+        assert!(!report.completeness.is_truncated());
+        assert!(!report.completeness.is_complete());
+
+        assert_eq!(
+            link_frames(Vec::new()).completeness().end,
+            SequenceEnd::Empty
+        );
+    }
+
+    #[test]
+    fn test_completeness_flags_contradicting_links() {
+        let mut frames = chain(1..=3, plain);
+        // IS_LAST in the middle of a chain that carries on
+        let last_mid = Frame::new(
+            FrameHeader::with_flags(
+                2,
+                frames[0].compute_hash(),
+                4,
+                FrameFlags::new(FrameFlags::IS_LAST),
+            ),
+            Bytes::from("test"),
+        );
+        let third = Frame::new(
+            FrameHeader::with_flags(
+                3,
+                last_mid.compute_hash(),
+                4,
+                FrameFlags::new(FrameFlags::IS_LAST),
+            ),
+            Bytes::from("test"),
+        );
+        frames[1] = last_mid;
+        frames[2] = third;
+        // IS_FIRST on a frame that links back to something
+        frames.push(Frame::new(
+            FrameHeader::with_flags(7, [9u8; 32], 4, FrameFlags::new(FrameFlags::IS_FIRST)),
+            Bytes::from("test"),
+        ));
+
+        let located: Vec<LocatedFrame> = frames
+            .into_iter()
+            .enumerate()
+            .map(|(i, f)| locate(f, i * 100, 1.0))
+            .collect();
+        let report = analyze_indexed(&located);
+        let c = &report.completeness;
+        assert!(c
+            .inconsistencies
+            .contains(&FlagInconsistency::LastWithSuccessor {
+                frame_id: 2,
+                successor: 3
+            }));
+        assert!(c
+            .inconsistencies
+            .contains(&FlagInconsistency::FirstWithPrevHash { frame_id: 7 }));
+        assert!(!c.is_complete());
+        assert_eq!(report.index.completeness(&located), *c);
+    }
+
+    #[test]
+    fn test_headless_chain_ordered_by_hash_not_id() {
+        // Renumbered IDs: hash links say 10 -> 4 -> 7 -> 8
+        let mut frames = chain_from([10, 4, 7, 8], &mut [0xAA; 32], plain);
+        frames.reverse();
+        let index = link_indexed(&frames);
+        let ids: Vec<u64> = index.ordered(&frames).map(|f| f.header.frame_id).collect();
+        assert_eq!(ids, vec![10, 4, 7, 8]);
+        assert!(index.gaps.is_empty());
+        let renumbered: Vec<u64> = index
+            .renumbered
+            .iter()
+            .map(|&i| frames[i].header.frame_id)
+            .collect();
+        // 10 -> 4 goes back on the ID; 4 -> 7 only skips ahead
+        assert_eq!(renumbered, vec![4]);
+        assert!(verify_backlinks_indexed(&index, &frames).is_empty());
+    }
+
+    #[test]
+    fn test_headless_reused_id_bridges_chains() {
+        // 5 -> 6 -> 6' -> 7: the second 6 is a renumbered frame, not a rewrite
+        let frames = chain_from([5, 6, 6, 7], &mut [0xAA; 32], plain);
+        let index = link_indexed(&frames);
+        assert_eq!(index.frames, vec![0, 1, 2, 3]);
+        assert!(index.conflicting.is_empty());
+        assert_eq!(index.renumbered, vec![2]);
+        assert!(index.gaps.is_empty());
+
+        // A rewrite of 6 that links nowhere new is still an equivocation
+        let mut frames = chain_from([5, 6, 7], &mut [0xAA; 32], plain);
+        frames.push(Frame::new(
+            FrameHeader::new(6, frames[0].compute_hash(), 6),
+            Bytes::from("forged"),
+        ));
+        let report = analyze_timeline(frames);
+        assert_eq!(report.timeline.frames.len(), 3);
+        assert_eq!(report.equivocations.len(), 1);
+        assert!(report.timeline.gaps.is_empty());
+    }
+
+    #[test]
+    fn test_headless_fork_follows_confident_anchor() {
+        // 3 -> 4 -> {5, 6}: both claim 4 as predecessor
+        let mut frames = chain_from([3, 4, 5], &mut [0xAA; 32], plain);
+        frames.push(Frame::new(
+            FrameHeader::new(6, frames[1].compute_hash(), 4),
+            Bytes::from("other"),
+        ));
+
+        let ids = |index: &TimelineIndex| -> Vec<u64> {
+            index.ordered(&frames).map(|f| f.header.frame_id).collect()
+        };
+
+        let plain = link_indexed(&frames);
+        assert_eq!(ids(&plain), vec![3, 4, 5, 6]);
+        assert_eq!(plain.gaps.len(), 1);
+
+        // Frame 6 was received cleanly and 5 barely: keep 6's ancestry together
+        let confidence = [0.9, 0.9, 0.3, 1.0];
+        let anchored = link_indexed_with_confidence(&frames, |i| confidence[i]);
+        assert_eq!(ids(&anchored), vec![3, 4, 6, 5]);
+        assert_eq!(
+            anchored.gaps,
+            vec![SequenceGap {
+                before: 5,
+                after: 6,
+                expected_hash: Some(frames[1].compute_hash()),
+            }]
+        );
+        // 4 -> 6 is a fork, not a reused ID
+        assert!(anchored.renumbered.is_empty());
+    }
 }
@@ -0,0 +1,563 @@
+//! Consensus merge of several captures of the same stream
+//!
+//! Ground stations receiving the same downlink see different damage. [`merge_streams`]
+//! scans every capture, keeps the best intact copy of each frame, and rebuilds
+//! frames that every station received damaged by byte-wise majority vote, accepting
+//! a vote only when the frame's trailer verifies.
+//!
+//! Voting needs header-CRC frames (`HEADER_CRC_VERSION`): only a verified header
+//! says which frame a damaged copy belongs to and how long it is. Damaged
+//! version 1 frames are not located at all and stay missing from the merge.
+
+use crate::constants::{TrailerType, BLAKE3_HASH_SIZE, FRAME_MARKER};
+use crate::decoder::decode_frame_from_bytes;
+use crate::linker::{link_indexed, TimelineIndex};
+use crate::scanner::{scan_stream_with_damaged, ScanStats};
+use crate::types::Frame;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec;
+use alloc::vec::Vec;
+use bytes::Bytes;
+
+/// Most byte combinations tried when damaged copies disagree without a majority
+pub const MAX_VOTE_CANDIDATES: usize = 1 << 12;
+
+/// Where one copy of a frame was found
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Provenance {
+    /// Index of the input the copy came from
+    pub source: usize,
+    /// Byte offset of the frame marker in that input
+    pub offset: usize,
+    /// Scanner confidence (0.0 for damaged copies)
+    pub confidence: f32,
+    /// Whether the copy decoded and verified on its own
+    pub intact: bool,
+}
+
+/// How a merged frame was obtained
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeOrigin {
+    /// An intact copy, `copies[chosen]`
+    Copy {
+        /// Index into [`MergedFrame::copies`]
+        chosen: usize,
+    },
+    /// Rebuilt by majority vote across damaged copies, then verified by its trailer
+    Voted {
+        /// Bytes without a strict majority, settled by trying each tied value
+        tied_bytes: usize,
+    },
+}
+
+/// One frame of the merged stream
+#[derive(Debug, Clone)]
+pub struct MergedFrame {
+    /// The decoded frame
+    pub frame: Frame,
+    /// Encoded frame bytes (marker through trailer), ready to be written out
+    pub bytes: Bytes,
+    /// Every copy seen across the inputs
+    pub copies: Vec<Provenance>,
+    /// Whether the frame was copied or voted
+    pub origin: MergeOrigin,
+    /// Intact copies whose content differs from the chosen one
+    pub dissenting: usize,
+}
+
+impl AsRef<Frame> for MergedFrame {
+    fn as_ref(&self) -> &Frame {
+        &self.frame
+    }
+}
+
+/// A frame seen only damaged, which voting could not rebuild
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnrecoveredFrame {
+    /// Frame ID from the verified header
+    pub frame_id: u64,
+    /// The damaged copies
+    pub copies: Vec<Provenance>,
+}
+
+/// Result of merging several captures
+#[derive(Debug, Clone)]
+pub struct MergeReport {
+    /// Merged frames in frame ID order
+    pub frames: Vec<MergedFrame>,
+    /// Timeline over `frames`
+    pub index: TimelineIndex,
+    /// Frames that no input holds intact and that voting could not repair
+    pub unrecovered: Vec<UnrecoveredFrame>,
+    /// Scan statistics per input
+    pub source_stats: Vec<ScanStats>,
+}
+
+impl MergeReport {
+    /// Number of frames rebuilt by voting
+    pub fn voted(&self) -> usize {
+        self.frames
+            .iter()
+            .filter(|f| matches!(f.origin, MergeOrigin::Voted { .. }))
+            .count()
+    }
+}
+
+/// An intact copy awaiting selection
+struct Intact {
+    frame: Frame,
+    hash: [u8; BLAKE3_HASH_SIZE],
+    provenance: Provenance,
+}
+
+/// Merge several captures of the same stream into one timeline
+///
+/// For each frame ID the copy shared by the most inputs wins (content is
+/// compared by hash, and repeats within one input count once); ties go to the
+/// highest confidence, then the earliest input. When no input holds an intact
+/// copy, damaged copies with a verified header (`HEADER_CRC_VERSION` frames,
+/// see [`scan_stream_with_damaged`]) from at least two inputs are combined
+/// byte by byte, again with one vote per input. Positions without a strict majority are resolved by
+/// trying each tied value, up to [`MAX_VOTE_CANDIDATES`] combinations; the
+/// result is kept only if it decodes and its trailer verifies.
+pub fn merge_streams<D: AsRef<[u8]>>(inputs: &[D]) -> MergeReport {
+    let mut intact: BTreeMap<u64, Vec<Intact>> = BTreeMap::new();
+    let mut damaged: BTreeMap<u64, Vec<(Provenance, usize)>> = BTreeMap::new();
+    let mut source_stats = Vec::with_capacity(inputs.len());
+
+    for (source, data) in inputs.iter().enumerate() {
+        let (located, broken, stats) = scan_stream_with_damaged(data.as_ref());
+
+        #[cfg(feature = "logging")]
+        tracing::debug!(
+            "Input {}: {} intact, {} damaged frames",
+            source,
+            located.len(),
+            broken.len()
+        );
+
+        for lf in located {
+            intact
+                .entry(lf.frame.header.frame_id)
+                .or_default()
+                .push(Intact {
+                    hash: lf.frame.compute_hash(),
+                    provenance: Provenance {
+                        source,
+                        offset: lf.offset,
+                        confidence: lf.confidence,
+                        intact: true,
+                    },
+                    frame: lf.frame,
+                });
+        }
+        // Only a verified header says which frame a damaged copy is
+        for df in broken.into_iter().filter(|df| df.header_verified()) {
+            let provenance = Provenance {
+                source,
+                offset: df.offset,
+                confidence: 0.0,
+                intact: false,
+            };
+            damaged
+                .entry(df.header.frame_id)
+                .or_default()
+                .push((provenance, df.size));
+        }
+        source_stats.push(stats);
+    }
+
+    let mut frames = Vec::with_capacity(intact.len());
+    let mut unrecovered = Vec::new();
+    let ids: BTreeSet<u64> = intact.keys().chain(damaged.keys()).copied().collect();
+
+    for id in ids {
+        let broken = damaged.remove(&id).unwrap_or_default();
+        match intact.remove(&id) {
+            Some(copies) => {
+                let mut merged = pick_copy(inputs, copies);
+                merged.copies.extend(broken.into_iter().map(|(p, _)| p));
+                frames.push(merged);
+            }
+            None => match vote_copies(inputs, &broken) {
+                Some(merged) => frames.push(merged),
+                None => {
+                    #[cfg(feature = "logging")]
+                    tracing::warn!(
+                        "Frame {} could not be rebuilt from {} damaged copies",
+                        id,
+                        broken.len()
+                    );
+
+                    unrecovered.push(UnrecoveredFrame {
+                        frame_id: id,
+                        copies: broken.into_iter().map(|(p, _)| p).collect(),
+                    });
+                }
+            },
+        }
+    }
+
+    let index = link_indexed(&frames);
+    MergeReport {
+        frames,
+        index,
+        unrecovered,
+        source_stats,
+    }
+}
+
+/// Choose among intact copies of one frame ID
+fn pick_copy<D: AsRef<[u8]>>(inputs: &[D], copies: Vec<Intact>) -> MergedFrame {
+    // Inputs holding each distinct content; repeats within one input count once
+    let mut holders: BTreeMap<[u8; BLAKE3_HASH_SIZE], BTreeSet<usize>> = BTreeMap::new();
+    for c in &copies {
+        holders
+            .entry(c.hash)
+            .or_default()
+            .insert(c.provenance.source);
+    }
+    let support: BTreeMap<_, usize> = holders.into_iter().map(|(h, s)| (h, s.len())).collect();
+
+    // Inputs are scanned in order, so on equal support and confidence the earliest wins
+    let mut chosen = 0;
+    for (i, c) in copies.iter().enumerate().skip(1) {
+        let best = &copies[chosen];
+        let key = (support[&c.hash], c.provenance.confidence);
+        let best_key = (support[&best.hash], best.provenance.confidence);
+        if key > best_key {
+            chosen = i;
+        }
+    }
+
+    let hash = copies[chosen].hash;
+    let dissenting = copies.iter().filter(|c| c.hash != hash).count();
+    let p = copies[chosen].provenance;
+    let data = inputs[p.source].as_ref();
+    let mut raw = data[p.offset..p.offset + copies[chosen].frame.total_size()].to_vec();
+    // The scanner accepts frames behind a damaged marker once their trailer verifies
+    raw[..FRAME_MARKER.len()].copy_from_slice(FRAME_MARKER);
+
+    let provenance = copies.iter().map(|c| c.provenance).collect();
+    let frame = copies
+        .into_iter()
+        .nth(chosen)
+        .map(|c| c.frame)
+        .expect("chosen copy");
+    MergedFrame {
+        frame,
+        bytes: Bytes::from(raw),
+        copies: provenance,
+        origin: MergeOrigin::Copy { chosen },
+        dissenting,
+    }
+}
+
+/// Rebuild a frame from damaged copies by byte-wise majority vote
+fn vote_copies<D: AsRef<[u8]>>(
+    inputs: &[D],
+    broken: &[(Provenance, usize)],
+) -> Option<MergedFrame> {
+    // Copies are only comparable at the size their verified headers declare;
+    // use the size most inputs agree on (an equivocating copy may differ).
+    // As in `pick_copy`, repeats within one input count once.
+    let mut sizes: Vec<(usize, BTreeSet<usize>)> = Vec::new();
+    for &(p, s) in broken {
+        match sizes.iter_mut().find(|(size, _)| *size == s) {
+            Some((_, holders)) => {
+                holders.insert(p.source);
+            }
+            None => sizes.push((s, BTreeSet::from([p.source]))),
+        }
+    }
+    let top = sizes.iter().map(|(_, h)| h.len()).max()?;
+    let size = sizes.iter().find(|(_, h)| h.len() == top)?.0;
+    let raws: Vec<(usize, &[u8])> = broken
+        .iter()
+        .filter(|(_, s)| *s == size)
+        .filter_map(|(p, _)| {
+            let raw = inputs[p.source].as_ref().get(p.offset..p.offset + size)?;
+            Some((p.source, raw))
+        })
+        .collect();
+    let sources: BTreeSet<usize> = raws.iter().map(|&(source, _)| source).collect();
+    if sources.len() < 2 {
+        return None;
+    }
+
+    let mut candidate = vec![0u8; size];
+    let mut tied: Vec<(usize, Vec<u8>)> = Vec::new();
+    let mut combinations = 1usize;
+    for (pos, slot) in candidate.iter_mut().enumerate() {
+        let mut votes: Vec<(u8, BTreeSet<usize>)> = Vec::new();
+        for &(source, raw) in &raws {
+            match votes.iter_mut().find(|(b, _)| *b == raw[pos]) {
+                Some((_, holders)) => {
+                    holders.insert(source);
+                }
+                None => votes.push((raw[pos], BTreeSet::from([source]))),
+            }
+        }
+        let top = votes.iter().map(|(_, h)| h.len()).max().unwrap_or(0);
+        let leaders: Vec<u8> = votes
+            .iter()
+            .filter(|(_, h)| h.len() == top)
+            .map(|&(b, _)| b)
+            .collect();
+        *slot = leaders[0];
+        if leaders.len() > 1 {
+            combinations = combinations.saturating_mul(leaders.len());
+            tied.push((pos, leaders));
+        }
+    }
+    candidate[..FRAME_MARKER.len()].copy_from_slice(FRAME_MARKER);
+    if combinations > MAX_VOTE_CANDIDATES {
+        return None;
+    }
+
+    // Walk every assignment of tied positions (mixed-radix counter)
+    let mut choice = vec![0usize; tied.len()];
+    loop {
+        for ((pos, leaders), &c) in tied.iter().zip(&choice) {
+            candidate[*pos] = leaders[c];
+        }
+        if let Ok(frame) = decode_frame_from_bytes(&candidate) {
+            if frame.header.flags.trailer_type() != TrailerType::None {
+                return Some(MergedFrame {
+                    frame,
+                    bytes: Bytes::from(candidate),
+                    copies: broken.iter().map(|(p, _)| *p).collect(),
+                    origin: MergeOrigin::Voted {
+                        tied_bytes: tied.len(),
+                    },
+                    dissenting: 0,
+                });
+            }
+        }
+
+        let mut digit = 0;
+        loop {
+            if digit == tied.len() {
+                return None;
+            }
+            choice[digit] += 1;
+            if choice[digit] < tied[digit].1.len() {
+                break;
+            }
+            choice[digit] = 0;
+            digit += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::MAX_HEADER_SIZE;
+    use crate::encoder::FrameBuilder;
+    use crate::test_util::{chain, encode_all};
+
+    /// A header-CRC stream of `n` BLAKE3 frames and the offset of each frame
+    fn stream(n: u64) -> (Vec<u8>, Vec<usize>) {
+        encode_all(&chain(1..=n, |_, b| b.with_blake3().with_header_crc()))
+    }
+
+    /// Flip a payload byte of the frame starting at `offset`
+    fn damage(data: &mut [u8], offset: usize, at: usize) {
+        data[offset + MAX_HEADER_SIZE + at] ^= 0xFF;
+    }
+
+    #[test]
+    fn test_merge_fills_losses_from_other_inputs() {
+        let (clean, offsets) = stream(5);
+        let mut a = clean.clone();
+        let mut b = clean.clone();
+        damage(&mut a, offsets[1], 2);
+        damage(&mut b, offsets[3], 2);
+
+        let report = merge_streams(&[a, b]);
+        let ids: Vec<u64> = report
+            .frames
+            .iter()
+            .map(|f| f.frame.header.frame_id)
+            .collect();
+        assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+        assert!(report.index.gaps.is_empty());
+        assert!(report.unrecovered.is_empty());
+
+        // Frame 2 was only intact in input 1
+        let f2 = &report.frames[1];
+        assert!(matches!(f2.origin, MergeOrigin::Copy { chosen } if f2.copies[chosen].source == 1));
+        assert_eq!(f2.copies.len(), 2);
+        assert!(f2.copies.iter().any(|p| p.source == 0 && !p.intact));
+
+        let rebuilt: Vec<u8> = report
+            .frames
+            .iter()
+            .flat_map(|f| f.bytes.to_vec())
+            .collect();
+        assert_eq!(rebuilt, clean);
+    }
+
+    #[test]
+    fn test_majority_vote_repairs_frame_damaged_everywhere() {
+        let (clean, offsets) = stream(3);
+        let mut copies = vec![clean.clone(), clean.clone(), clean.clone()];
+        for (i, copy) in copies.iter_mut().enumerate() {
+            damage(copy, offsets[1], i);
+        }
+
+        let report = merge_streams(&copies);
+        assert_eq!(report.frames.len(), 3);
+        assert_eq!(report.voted(), 1);
+        assert_eq!(
+            report.frames[1].origin,
+            MergeOrigin::Voted { tied_bytes: 0 }
+        );
+        assert_eq!(&report.frames[1].bytes[..], &clean[offsets[1]..offsets[2]]);
+        assert!(report.index.gaps.is_empty());
+    }
+
+    #[test]
+    fn test_two_damaged_copies_resolve_ties_by_trailer() {
+        let (clean, offsets) = stream(2);
+        let mut a = clean.clone();
+        let mut b = clean.clone();
+        damage(&mut a, offsets[1], 0);
+        damage(&mut b, offsets[1], 5);
+
+        let report = merge_streams(&[a, b]);
+        assert_eq!(report.frames.len(), 2);
+        assert_eq!(
+            report.frames[1].origin,
+            MergeOrigin::Voted { tied_bytes: 2 }
+        );
+        assert_eq!(&report.frames[1].bytes[..], &clean[offsets[1]..]);
+    }
+
+    #[test]
+    fn test_vote_uses_the_size_most_copies_declare() {
+        let (clean, offsets) = stream(2);
+        // A damaged, longer rewrite of frame 2 comes first
+        let mut rewrite = clean[..offsets[1]].to_vec();
+        let prev = decode_frame_from_bytes(&clean).unwrap().compute_hash();
+        let start = rewrite.len();
+        rewrite.extend_from_slice(
+            &FrameBuilder::new(2)
+                .payload(Bytes::from("a considerably longer rewritten record"))
+                .prev_hash(prev)
+                .with_blake3()
+                .with_header_crc()
+                .build()
+                .unwrap(),
+        );
+        damage(&mut rewrite, start, 0);
+        let mut a = clean.clone();
+        let mut b = clean.clone();
+        damage(&mut a, offsets[1], 0);
+        damage(&mut b, offsets[1], 5);
+
+        let report = merge_streams(&[rewrite, a, b]);
+        assert_eq!(report.voted(), 1);
+        assert_eq!(&report.frames[1].bytes[..], &clean[offsets[1]..]);
+    }
+
+    #[test]
+    fn test_version_1_frames_are_not_voted() {
+        // Without a header CRC a damaged copy cannot be attributed, so it is not voted
+        let (clean, offsets) = encode_all(&chain(1..=3, |_, b| b.with_blake3()));
+        let mut copies = vec![clean.clone(), clean.clone(), clean];
+        for (i, copy) in copies.iter_mut().enumerate() {
+            damage(copy, offsets[1], i);
+        }
+
+        let report = merge_streams(&copies);
+        assert_eq!(report.frames.len(), 2);
+        assert_eq!(report.voted(), 0);
+        assert!(report.unrecovered.is_empty());
+        assert_eq!(report.index.gaps.len(), 1);
+    }
+
+    #[test]
+    fn test_single_damaged_copy_is_unrecovered() {
+        let (clean, offsets) = stream(3);
+        let mut a = clean.clone();
+        damage(&mut a, offsets[1], 0);
+
+        let report = merge_streams(&[a]);
+        assert_eq!(report.frames.len(), 2);
+        assert_eq!(report.unrecovered.len(), 1);
+        assert_eq!(report.unrecovered[0].frame_id, 2);
+        assert_eq!(report.index.gaps.len(), 1);
+    }
+
+    #[test]
+    fn test_majority_content_wins() {
+        let (clean, _) = stream(2);
+        let (rewritten, _) = encode_all(&chain(1..=2, |id, b| {
+            let b = b.with_blake3().with_header_crc();
+            if id == 2 {
+                b.payload(Bytes::from("rewritten"))
+            } else {
+                b
+            }
+        }));
+
+        let report = merge_streams(&[rewritten, clean.clone(), clean]);
+        let f2 = &report.frames[1];
+        assert_eq!(&f2.frame.payload[..], b"frame 2");
+        assert_eq!(f2.dissenting, 1);
+        assert!(matches!(f2.origin, MergeOrigin::Copy { chosen } if f2.copies[chosen].source == 1));
+    }
+
+    #[test]
+    fn test_repeats_within_one_input_count_once() {
+        let (clean, offsets) = stream(2);
+        let forged = FrameBuilder::new(2)
+            .payload(Bytes::from("rewritten"))
+            .prev_hash(decode_frame_from_bytes(&clean).unwrap().compute_hash())
+            .with_blake3()
+            .with_header_crc()
+            .build()
+            .unwrap();
+        // One capture holds three copies of the rewritten frame 2
+        let mut replayed = clean[..offsets[1]].to_vec();
+        for _ in 0..3 {
+            replayed.extend_from_slice(&forged);
+        }
+
+        let report = merge_streams(&[replayed, clean.clone(), clean]);
+        let f2 = &report.frames[1];
+        assert_eq!(&f2.frame.payload[..], b"frame 2");
+        assert_eq!(f2.dissenting, 3);
+    }
+
+    #[test]
+    fn test_damaged_repeats_within_one_input_vote_once() {
+        let (clean, offsets) = stream(2);
+        let frame2 = &clean[offsets[1]..];
+        // One capture holds two copies of frame 2 with the same damage
+        let mut replayed = clean[..offsets[1]].to_vec();
+        for _ in 0..2 {
+            let start = replayed.len();
+            replayed.extend_from_slice(frame2);
+            damage(&mut replayed, start, 0);
+        }
+
+        // Alone, the retransmission is still a single input
+        let report = merge_streams(&[replayed.clone()]);
+        assert_eq!(report.voted(), 0);
+        assert_eq!(report.unrecovered.len(), 1);
+
+        // Against one copy from another input it ties instead of outvoting it
+        let mut b = clean.clone();
+        damage(&mut b, offsets[1], 5);
+        let report = merge_streams(&[replayed, b]);
+        assert_eq!(report.voted(), 1);
+        assert_eq!(
+            report.frames[1].origin,
+            MergeOrigin::Voted { tied_bytes: 2 }
+        );
+        assert_eq!(&report.frames[1].bytes[..], frame2);
+    }
+}
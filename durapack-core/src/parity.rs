@@ -0,0 +1,448 @@
+//! In-band parity frames: FEC shards that describe the block they protect
+//!
+//! A parity frame sets `IS_SUPERFRAME` and starts its payload with a
+//! [`ParityDescriptor`]: the code, the block's data and parity counts, the
+//! first protected frame ID and the frame's shard index, sealed by a CRC32C.
+//! The shard follows. Index superframes share the flag; the descriptor's
+//! `DPAR` magic tells the two apart (`Frame::is_index_superframe`). Readers
+//! can therefore tell parity from data and rebuild [`ParityBlock`]s from the
+//! scanned frames alone, without the JSON sidecar. [`in_band_blocks`] does
+//! the grouping; the [`ParityBlock`] of each result feeds
+//! [`recover::link_with_fec`] and [`recover::apply_recipes`].
+//!
+//! [`recover::link_with_fec`]: crate::recover::link_with_fec
+//! [`recover::apply_recipes`]: crate::recover::apply_recipes
+
+use crate::constants::FrameFlags;
+use crate::recover::ParityBlock;
+use crate::types::Frame;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+use bytes::Bytes;
+
+/// Tag at the start of a parity frame payload
+pub const PARITY_MAGIC: &[u8; 4] = b"DPAR";
+
+/// Layout version of the descriptor
+pub const PARITY_DESCRIPTOR_VERSION: u8 = 1;
+
+/// Size of the descriptor ahead of the shard
+///
+/// Layout (big-endian): magic (4), descriptor version (1), code (1), code
+/// parameter (2), first data frame ID (8), data shards N (4), parity shards K
+/// (4), shard index (4), CRC32C over the preceding 28 bytes (4).
+pub const PARITY_DESCRIPTOR_LEN: usize = 32;
+
+/// Reed–Solomon parameter marking whole-frame shards (`fec::RsEncoder::whole_frames`)
+pub const RS_PARAM_WHOLE_FRAME: u16 = 1;
+
+/// Most data plus parity shards in one Reed–Solomon block (GF(2^8))
+pub const MAX_RS_BLOCK_SHARDS: u32 = 255;
+
+/// Most data plus parity shards in one RaptorQ or LDPC block
+///
+/// Covers RaptorQ's 56403 source symbols with room for repair symbols, and
+/// bounds the IDs a descriptor makes readers enumerate.
+pub const MAX_BLOCK_SHARDS: u32 = 1 << 16;
+
+/// Erasure code that produced a parity shard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FecCode {
+    /// Reed–Solomon over GF(2^8) (`fec::RsEncoder`)
+    ReedSolomon,
+    /// RaptorQ fountain code (`fec::RaptorQEncoder`)
+    RaptorQ,
+    /// LDPC erasure code (`fec::LdpcEncoder`); the parameter is the column weight
+    Ldpc,
+}
+
+impl FecCode {
+    /// Wire value of the code
+    pub const fn as_u8(self) -> u8 {
+        match self {
+            FecCode::ReedSolomon => 1,
+            FecCode::RaptorQ => 2,
+            FecCode::Ldpc => 3,
+        }
+    }
+
+    /// Code for a wire value
+    pub const fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            1 => Some(FecCode::ReedSolomon),
+            2 => Some(FecCode::RaptorQ),
+            3 => Some(FecCode::Ldpc),
+            _ => None,
+        }
+    }
+
+    /// Short name, as used by the CLI and the FEC sidecar's `scheme`
+    pub const fn name(self) -> &'static str {
+        match self {
+            FecCode::ReedSolomon => "rs",
+            FecCode::RaptorQ => "raptorq",
+            FecCode::Ldpc => "ldpc",
+        }
+    }
+
+    /// Most data plus parity shards a block of this code may declare
+    pub const fn max_block_shards(self) -> u32 {
+        match self {
+            FecCode::ReedSolomon => MAX_RS_BLOCK_SHARDS,
+            FecCode::RaptorQ | FecCode::Ldpc => MAX_BLOCK_SHARDS,
+        }
+    }
+
+    /// Code for a short name as returned by [`FecCode::name`]
+    pub fn from_name(name: &str) -> Option<Self> {
+        [FecCode::ReedSolomon, FecCode::RaptorQ, FecCode::Ldpc]
+            .into_iter()
+            .find(|c| c.name() == name)
+    }
+}
+
+/// Block descriptor carried at the start of every parity frame payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParityDescriptor {
+    /// Code that produced the shard
+    pub code: FecCode,
+    /// Code-specific parameter: LDPC column weight, [`RS_PARAM_WHOLE_FRAME`]
+    /// for whole-frame Reed–Solomon, 0 otherwise
+    pub param: u16,
+    /// Frame ID of the first protected data frame; the block covers N consecutive IDs
+    pub block_start_id: u64,
+    /// Number of data frames (N)
+    pub data: u32,
+    /// Number of parity frames (K)
+    pub parity: u32,
+    /// Position of this frame among the block's parity frames (0..K)
+    pub index: u32,
+}
+
+impl ParityDescriptor {
+    /// Serialize the descriptor, CRC included
+    pub fn to_bytes(&self) -> [u8; PARITY_DESCRIPTOR_LEN] {
+        let mut out = [0u8; PARITY_DESCRIPTOR_LEN];
+        out[0..4].copy_from_slice(PARITY_MAGIC);
+        out[4] = PARITY_DESCRIPTOR_VERSION;
+        out[5] = self.code.as_u8();
+        out[6..8].copy_from_slice(&self.param.to_be_bytes());
+        out[8..16].copy_from_slice(&self.block_start_id.to_be_bytes());
+        out[16..20].copy_from_slice(&self.data.to_be_bytes());
+        out[20..24].copy_from_slice(&self.parity.to_be_bytes());
+        out[24..28].copy_from_slice(&self.index.to_be_bytes());
+        let crc = crc32c::crc32c(&out[..28]);
+        out[28..32].copy_from_slice(&crc.to_be_bytes());
+        out
+    }
+
+    /// Parse a descriptor from the start of a payload
+    ///
+    /// Returns `None` unless the magic, version, code and CRC all check out,
+    /// the shard index lies within the block, the block's data and parity
+    /// counts fit [`FecCode::max_block_shards`] and its IDs do not overflow.
+    pub fn parse(payload: &[u8]) -> Option<Self> {
+        let d = payload.get(..PARITY_DESCRIPTOR_LEN)?;
+        if &d[0..4] != PARITY_MAGIC || d[4] != PARITY_DESCRIPTOR_VERSION {
+            return None;
+        }
+        let be32 = |r: core::ops::Range<usize>| u32::from_be_bytes(d[r].try_into().unwrap());
+        if crc32c::crc32c(&d[..28]) != be32(28..32) {
+            return None;
+        }
+        let desc = Self {
+            code: FecCode::from_u8(d[5])?,
+            param: u16::from_be_bytes([d[6], d[7]]),
+            block_start_id: u64::from_be_bytes(d[8..16].try_into().unwrap()),
+            data: be32(16..20),
+            parity: be32(20..24),
+            index: be32(24..28),
+        };
+        let shards = desc.data.checked_add(desc.parity)?;
+        let ids_fit = desc.block_start_id.checked_add(desc.data as u64).is_some();
+        (desc.data > 0
+            && desc.index < desc.parity
+            && shards <= desc.code.max_block_shards()
+            && ids_fit)
+            .then_some(desc)
+    }
+
+    /// Payload of a parity frame: this descriptor followed by the shard
+    pub fn payload(&self, shard: &[u8]) -> Bytes {
+        let mut out = Vec::with_capacity(PARITY_DESCRIPTOR_LEN + shard.len());
+        out.extend_from_slice(&self.to_bytes());
+        out.extend_from_slice(shard);
+        Bytes::from(out)
+    }
+}
+
+/// Descriptor of an in-band parity frame, if `frame` is one
+pub fn parity_descriptor(frame: &Frame) -> Option<ParityDescriptor> {
+    if frame.header.flags.as_u8() & FrameFlags::IS_SUPERFRAME == 0 {
+        return None;
+    }
+    ParityDescriptor::parse(&frame.payload)
+}
+
+/// Shard bytes of a parity frame: the payload after an in-band descriptor
+///
+/// Parity frames written before descriptors existed carry the bare shard;
+/// their whole payload is returned.
+pub fn parity_shard(frame: &Frame) -> &[u8] {
+    match parity_descriptor(frame) {
+        Some(_) => &frame.payload[PARITY_DESCRIPTOR_LEN..],
+        None => &frame.payload,
+    }
+}
+
+/// A parity block reassembled from the descriptors of its surviving parity frames
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InBandBlock {
+    /// Code that produced the block's shards
+    pub code: FecCode,
+    /// Code-specific parameter from the descriptors
+    pub param: u16,
+    /// Block layout; IDs of lost parity frames are inferred from the surviving ones
+    pub block: ParityBlock,
+    /// IDs of the parity frames actually found
+    pub parity_found: Vec<u64>,
+}
+
+impl InBandBlock {
+    /// Whether the block's Reed–Solomon shards are complete encoded frames
+    pub fn whole_frames(&self) -> bool {
+        self.code == FecCode::ReedSolomon && self.param == RS_PARAM_WHOLE_FRAME
+    }
+
+    /// Data frame IDs of the block missing from `present`
+    pub fn missing_data(&self, present: &BTreeSet<u64>) -> Vec<u64> {
+        self.block
+            .data_ids()
+            .filter(|id| !present.contains(id))
+            .collect()
+    }
+
+    /// Whether the block has losses and at least as many surviving parity
+    /// frames as lost data frames
+    ///
+    /// Exact for Reed–Solomon; RaptorQ and LDPC occasionally need one or two
+    /// shards more.
+    pub fn repairable(&self, present: &BTreeSet<u64>) -> bool {
+        let missing = self.missing_data(present).len();
+        missing > 0 && missing <= self.parity_found.len()
+    }
+}
+
+/// Group the in-band parity frames among `frames` into blocks
+///
+/// Frames agreeing on code, parameter, first ID and block size form one
+/// block. Duplicate shard indices keep the first frame seen. Lost parity
+/// frames are assumed to sit at consecutive IDs next to the surviving ones,
+/// as `durapack pack` and `durapack fec` write them. Blocks are ordered by
+/// their first data frame ID.
+pub fn in_band_blocks<'a>(frames: impl IntoIterator<Item = &'a Frame>) -> Vec<InBandBlock> {
+    type Key = (u64, FecCode, u16, u32, u32);
+    let mut groups: BTreeMap<Key, BTreeMap<u32, u64>> = BTreeMap::new();
+    for f in frames {
+        if let Some(d) = parity_descriptor(f) {
+            groups
+                .entry((d.block_start_id, d.code, d.param, d.data, d.parity))
+                .or_default()
+                .entry(d.index)
+                .or_insert(f.header.frame_id);
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|((start, code, param, data, parity), found)| {
+            let (&j0, &id0) = found.iter().next().expect("group has a frame");
+            let parity_frame_ids = (0..parity)
+                .map(|j| {
+                    found
+                        .get(&j)
+                        .copied()
+                        .unwrap_or_else(|| id0.wrapping_add(j as u64).wrapping_sub(j0 as u64))
+                })
+                .collect();
+            InBandBlock {
+                code,
+                param,
+                block: ParityBlock {
+                    block_start_id: start,
+                    data: data as usize,
+                    parity: parity as usize,
+                    parity_frame_ids,
+                },
+                parity_found: found.into_values().collect(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoder::FrameBuilder;
+    use alloc::vec;
+
+    fn parity_frame(id: u64, d: ParityDescriptor) -> Frame {
+        FrameBuilder::new(id)
+            .as_parity(&d, b"shard bytes")
+            .build_struct()
+            .unwrap()
+    }
+
+    fn rs(index: u32) -> ParityDescriptor {
+        ParityDescriptor {
+            code: FecCode::ReedSolomon,
+            param: 0,
+            block_start_id: 10,
+            data: 4,
+            parity: 3,
+            index,
+        }
+    }
+
+    #[test]
+    fn test_descriptor_round_trip_and_rejects_damage() {
+        assert_eq!(FecCode::from_name("raptorq"), Some(FecCode::RaptorQ));
+        assert_eq!(FecCode::from_name("xor"), None);
+
+        let d = rs(2);
+        let f = parity_frame(16, d);
+        assert_eq!(parity_descriptor(&f), Some(d));
+        assert_eq!(parity_shard(&f), b"shard bytes");
+
+        let mut bytes = d.to_bytes();
+        bytes[9] ^= 1;
+        assert_eq!(ParityDescriptor::parse(&bytes), None);
+
+        // A data frame whose payload happens to carry the tag is still data
+        let data = FrameBuilder::new(1)
+            .payload(d.payload(b"x"))
+            .build_struct()
+            .unwrap();
+        assert_eq!(parity_descriptor(&data), None);
+        assert_eq!(parity_shard(&data).len(), PARITY_DESCRIPTOR_LEN + 1);
+    }
+
+    #[test]
+    fn test_descriptor_rejects_oversized_blocks() {
+        let parse = |d: ParityDescriptor| ParityDescriptor::parse(&d.to_bytes());
+        let max = ParityDescriptor {
+            data: 200,
+            parity: 55,
+            ..rs(0)
+        };
+        assert_eq!(parse(max), Some(max));
+        assert_eq!(parse(ParityDescriptor { parity: 56, ..max }), None);
+        assert_eq!(
+            parse(ParityDescriptor {
+                parity: u32::MAX,
+                ..rs(0)
+            }),
+            None
+        );
+
+        let ldpc = ParityDescriptor {
+            code: FecCode::Ldpc,
+            param: 3,
+            data: u32::MAX - 1,
+            ..rs(0)
+        };
+        assert_eq!(parse(ldpc), None);
+        assert!(parse(ParityDescriptor { data: 1000, ..ldpc }).is_some());
+
+        // Data IDs past u64::MAX
+        let wraps = ParityDescriptor {
+            block_start_id: u64::MAX - 2,
+            ..rs(0)
+        };
+        assert_eq!(parse(wraps), None);
+
+        // None of them is grouped into a block
+        let frames = [
+            parity_frame(
+                1,
+                ParityDescriptor {
+                    parity: u32::MAX,
+                    ..rs(0)
+                },
+            ),
+            parity_frame(2, wraps),
+        ];
+        assert!(in_band_blocks(&frames).is_empty());
+    }
+
+    #[test]
+    fn test_parity_frames_are_not_index_superframes() {
+        let parity = parity_frame(16, rs(0));
+        assert!(parity.header.flags.is_superframe());
+        assert!(!parity.is_index_superframe());
+
+        let index = FrameBuilder::new(17)
+            .as_superframe()
+            .payload(Bytes::from_static(b"index"))
+            .build_struct()
+            .unwrap();
+        assert!(index.is_index_superframe());
+
+        let data = FrameBuilder::new(18).build_struct().unwrap();
+        assert!(!data.is_index_superframe());
+    }
+
+    #[test]
+    fn test_blocks_from_surviving_parity_frames() {
+        // Parity frames 14..=16 protect data 10..=13; frame 15 is lost
+        let frames = [parity_frame(16, rs(2)), parity_frame(14, rs(0))];
+        let blocks = in_band_blocks(&frames);
+        assert_eq!(blocks.len(), 1);
+        let b = &blocks[0];
+        assert_eq!(b.block.parity_frame_ids, vec![14, 15, 16]);
+        assert_eq!(b.parity_found, vec![14, 16]);
+
+        let present: BTreeSet<u64> = [10, 13, 14, 16].into_iter().collect();
+        assert_eq!(b.missing_data(&present), vec![11, 12]);
+        assert!(b.repairable(&present));
+        let present: BTreeSet<u64> = [10, 14, 16].into_iter().collect();
+        assert!(!b.repairable(&present));
+    }
+
+    #[cfg(feature = "fec-rs")]
+    #[test]
+    fn test_in_band_rs_parity_heals_gap_without_sidecar() {
+        use crate::fec::{RedundancyEncoder, RsDecoder, RsEncoder};
+        use crate::recover::link_with_fec;
+        use crate::test_util::{chain, chain_from};
+
+        let mut frames = chain(1..=4, |id, b| {
+            b.payload(Bytes::from(alloc::format!("reading {}", id * 11)))
+                .with_blake3()
+        });
+        let shards = RsEncoder::new(4, 2).encode_batch(&frames, 0).unwrap();
+        let mut prev = frames[3].compute_hash();
+        frames.extend(chain_from(5..=6, &mut prev, |id, b| {
+            let j = id - 5;
+            let d = ParityDescriptor {
+                code: FecCode::ReedSolomon,
+                param: 0,
+                block_start_id: 1,
+                data: 4,
+                parity: 2,
+                index: j as u32,
+            };
+            b.as_parity(&d, &shards[4 + j as usize].data).with_blake3()
+        }));
+
+        let lost = frames.remove(2);
+        let blocks: Vec<ParityBlock> = in_band_blocks(&frames)
+            .into_iter()
+            .map(|b| b.block)
+            .collect();
+        let recovered = link_with_fec(frames, &blocks, &RsDecoder);
+        assert_eq!(recovered.reconstructed, vec![3]);
+        assert!(recovered.timeline.gaps.is_empty());
+        assert_eq!(recovered.timeline.frames[2], lost);
+    }
+}
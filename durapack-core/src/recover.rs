@@ -0,0 +1,1149 @@
+//! Gap repair from forward error correction during linking
+//!
+//! Parity frames written by `durapack pack --fec-rs-*` or `durapack fec` protect
+//! blocks of consecutive data frames, described by [`ParityBlock`] entries (the
+//! FEC sidecar, or [`crate::parity::in_band_blocks`] for parity frames that
+//! carry their own descriptor). [`link_with_fec`] finds blocks whose missing frames the
+//! configured [`RedundancyDecoder`] can rebuild, restores each rebuilt frame's
+//! original header, and relinks the timeline.
+//!
+//! Decoders that protect header fields (Reed–Solomon, RaptorQ and LDPC, see
+//! [`RedundancyDecoder::restores_headers`]) return complete frames; they are
+//! checked against the block position and the back-links of whichever
+//! neighbors are known. A run with no known neighbor on either side (e.g. a
+//! whole stream's worth of lost frames) is checked only against the block
+//! position and its own internal links, plus the trailer for whole-frame
+//! shards, so it is not hash-verified against the surviving chain.
+//! Other decoders only return payloads, so headers are recovered by search:
+//! the frame ID follows from the block position, the back-link from the
+//! previous frame, and version/flags from the block's surviving frames.
+//! Payload length and the first/last flags are tried until the next frame's
+//! back-link matches the candidate's hash. A rebuilt frame is only reinserted
+//! once these checks pass.
+//!
+//! [`apply_recipes`] goes further and executes the [`RecoveryRecipe`]s a
+//! timeline analysis suggests: `RewindOffset` rescans the bytes around a gap
+//! with relaxed marker matching, and `InsertParityFrame` decodes the parity
+//! blocks covering it. Rounds repeat until no recipe recovers anything new.
+//!
+//! [`splice_rebuilt`] writes rebuilt frames back into the original bytes,
+//! next to the frames they link to, without touching anything else.
+
+use crate::constants::{FrameFlags, TrailerType, BLAKE3_HASH_SIZE};
+use crate::encoder::encode_frame_struct_exact;
+use crate::error::FrameError;
+use crate::fec::{FecBlock, RedundancyDecoder};
+use crate::linker::{
+    analyze_indexed, analyze_timeline, link_frames, verify_backlinks, RecoveryRecipe, SequenceGap,
+    Timeline, TimelineReport,
+};
+use crate::parity::parity_shard;
+use crate::scanner::{decode_at, scan_stream, LocatedFrame};
+use crate::types::{Frame, FrameHeader};
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec;
+use alloc::vec::Vec;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+
+/// Most header candidates tried for one run of missing frames
+pub const MAX_HEADER_HYPOTHESES: usize = 1 << 12;
+
+/// Parity frames protecting a block of consecutive data frames
+///
+/// Mirrors an entry of the FEC sidecar index written by the CLI.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParityBlock {
+    /// Frame ID of the first data frame in the block
+    pub block_start_id: u64,
+    /// Number of data frames (N)
+    pub data: usize,
+    /// Number of parity frames (K)
+    pub parity: usize,
+    /// Frame IDs of the parity frames, in shard order
+    pub parity_frame_ids: Vec<u64>,
+}
+
+impl ParityBlock {
+    /// Frame IDs of the protected data frames, cut off at `u64::MAX`
+    pub fn data_ids(&self) -> core::ops::Range<u64> {
+        let end = self.block_start_id.checked_add(self.data as u64);
+        self.block_start_id..end.unwrap_or(u64::MAX)
+    }
+}
+
+/// A gap closed entirely by frames rebuilt from parity
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealedGap {
+    /// The gap as reported before repair
+    pub gap: SequenceGap,
+    /// Rebuilt frame IDs that now fill it
+    pub frame_ids: Vec<u64>,
+}
+
+/// Result of [`link_with_fec`]
+#[derive(Debug, Clone)]
+pub struct RecoveredTimeline {
+    /// Timeline relinked with the rebuilt frames
+    pub timeline: Timeline,
+    /// Gaps that no longer exist thanks to rebuilt frames
+    pub healed: Vec<HealedGap>,
+    /// IDs of frames rebuilt from parity and reinserted
+    ///
+    /// Each was verified against the back-links of its known neighbors; a
+    /// header-restoring run with no known neighbor on either side is accepted
+    /// on its block position alone (see the module docs).
+    pub reconstructed: Vec<u64>,
+    /// IDs the decoder rebuilt but whose header could not be verified (not reinserted)
+    pub unverified: Vec<u64>,
+    /// Back-link errors remaining in the relinked timeline
+    pub backlink_errors: Vec<FrameError>,
+}
+
+/// Link frames, rebuilding missing ones from parity blocks first
+///
+/// A block is decoded when at least one of its data frames is missing and
+/// `decoder.can_reconstruct` accepts the surviving data and parity frames.
+/// Blocks are revisited until no more frames can be rebuilt, so a frame
+/// restored in one block can verify the frames before it in another.
+/// Rebuilt frames carry no trailer, and it is recomputed when they are
+/// encoded, unless the decoder rebuilds whole frames (`RsWholeFrameDecoder`).
+pub fn link_with_fec(
+    frames: Vec<Frame>,
+    blocks: &[ParityBlock],
+    decoder: &dyn RedundancyDecoder,
+) -> RecoveredTimeline {
+    let before = link_frames(frames);
+    let mut known: BTreeMap<u64, Frame> = BTreeMap::new();
+    for f in before.frames.iter().chain(&before.orphans) {
+        known.entry(f.header.frame_id).or_insert_with(|| f.clone());
+    }
+
+    let mut reconstructed = Vec::new();
+    let mut unverified = Vec::new();
+    loop {
+        let mut progress = false;
+        unverified.clear();
+        for block in blocks {
+            let (rebuilt, pending) = heal_block(&known, block, decoder);
+            progress |= !rebuilt.is_empty();
+            for f in rebuilt {
+                reconstructed.push(f.header.frame_id);
+                known.insert(f.header.frame_id, f);
+            }
+            unverified.extend(pending);
+        }
+        if !progress {
+            break;
+        }
+    }
+    reconstructed.sort_unstable();
+    unverified.retain(|id| !known.contains_key(id));
+    unverified.sort_unstable();
+    unverified.dedup();
+
+    if reconstructed.is_empty() {
+        let backlink_errors = verify_backlinks(&before);
+        return RecoveredTimeline {
+            timeline: before,
+            healed: Vec::new(),
+            reconstructed,
+            unverified,
+            backlink_errors,
+        };
+    }
+
+    #[cfg(feature = "logging")]
+    tracing::info!("Rebuilt {} frames from parity", reconstructed.len());
+
+    // Relink: keep the original orphans and every frame the first pass placed
+    let mut relinked: Vec<Frame> = before.frames;
+    relinked.extend(before.orphans);
+    for id in &reconstructed {
+        relinked.push(known.remove(id).expect("rebuilt frame is known"));
+    }
+    // After the frames they lost to, so they lose again
+    relinked.extend(before.conflicting);
+    let timeline = link_frames(relinked);
+    let backlink_errors = verify_backlinks(&timeline);
+
+    let healed = before
+        .gaps
+        .into_iter()
+        .filter(|gap| {
+            !timeline
+                .gaps
+                .iter()
+                .any(|g| g.before < gap.after && g.after > gap.before)
+        })
+        .map(|gap| HealedGap {
+            frame_ids: reconstructed
+                .iter()
+                .copied()
+                .filter(|&id| id > gap.before && id < gap.after)
+                .collect(),
+            gap,
+        })
+        .filter(|h| !h.frame_ids.is_empty())
+        .collect();
+
+    RecoveredTimeline {
+        timeline,
+        healed,
+        reconstructed,
+        unverified,
+        backlink_errors,
+    }
+}
+
+/// Settings for [`apply_recipes`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecipeOptions {
+    /// Marker bit errors tolerated when rescanning around a gap
+    ///
+    /// The first scan allows `MAX_MARKER_HAMMING`; frames found through a
+    /// damaged marker are still only accepted when their trailer verifies.
+    pub max_hamming: u32,
+    /// Bytes searched beyond the region a `RewindOffset` recipe points at
+    pub slack: usize,
+    /// Most rounds of recipes to apply
+    pub max_rounds: usize,
+}
+
+impl Default for RecipeOptions {
+    fn default() -> Self {
+        Self {
+            max_hamming: 8,
+            slack: 64,
+            max_rounds: 8,
+        }
+    }
+}
+
+/// How a recipe was carried out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecipeAction {
+    /// Bytes `start..end` were rescanned with relaxed marker matching
+    Rescanned {
+        /// First offset tried
+        start: usize,
+        /// One past the last offset tried
+        end: usize,
+    },
+    /// The parity block starting at `block_start_id` was decoded
+    Parity {
+        /// First data frame ID of the block
+        block_start_id: u64,
+    },
+}
+
+/// A recipe that recovered frames
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecipeStep {
+    /// Round in which the recipe was applied, starting at 1
+    pub round: usize,
+    /// The recipe as the analysis suggested it
+    pub recipe: RecoveryRecipe,
+    /// What was done
+    pub action: RecipeAction,
+    /// IDs of the frames it recovered
+    pub recovered: Vec<u64>,
+}
+
+/// Result of [`apply_recipes`]
+#[derive(Debug, Clone)]
+pub struct RecipeRun {
+    /// Analysis of the timeline with every recovered frame included
+    pub report: TimelineReport,
+    /// Scanned frames plus those found by rescans, in offset order
+    pub located: Vec<LocatedFrame>,
+    /// Frames rebuilt from parity (they have no position in the input)
+    pub rebuilt: Vec<Frame>,
+    /// Recipes that recovered frames, in the order they were applied
+    pub steps: Vec<RecipeStep>,
+    /// Rounds run, including the final one that found nothing new
+    pub rounds: usize,
+}
+
+impl RecipeRun {
+    /// The step that recovered `frame_id`, if a recipe did
+    pub fn recovered_by(&self, frame_id: u64) -> Option<&RecipeStep> {
+        self.steps.iter().find(|s| s.recovered.contains(&frame_id))
+    }
+}
+
+/// Scan `data`, then apply recovery recipes until none makes progress
+///
+/// Each round analyzes the timeline and carries out its recipes:
+/// `RewindOffset` rescans every offset between the frames around the gap
+/// (plus `options.slack` bytes) with markers allowed `options.max_hamming`
+/// bit errors, keeping new frames whose trailer verifies or whose hash links
+/// them to a known frame; `InsertParityFrame` decodes the `parity` blocks
+/// covering the gap. A frame found in one round can enable recipes in the
+/// next, for example by supplying the back-link a parity block needs.
+pub fn apply_recipes(
+    data: &[u8],
+    parity: Option<(&[ParityBlock], &dyn RedundancyDecoder)>,
+    options: RecipeOptions,
+) -> RecipeRun {
+    let mut located = scan_stream(data);
+    let mut rebuilt: Vec<Frame> = Vec::new();
+    let mut steps = Vec::new();
+    let mut rescanned: BTreeSet<(usize, usize)> = BTreeSet::new();
+    let mut rounds = 0;
+
+    while rounds < options.max_rounds {
+        rounds += 1;
+        let report = analyze_indexed(&located);
+        let mut progress = false;
+
+        for recipe in report.recipes {
+            let (action, found) = match &recipe {
+                RecoveryRecipe::RewindOffset {
+                    near_frame,
+                    by_bytes,
+                    ..
+                } => {
+                    let Some(after) = located
+                        .iter()
+                        .find(|lf| lf.frame.header.frame_id == *near_frame)
+                    else {
+                        continue;
+                    };
+                    let start = after
+                        .offset
+                        .saturating_sub((*by_bytes).max(0) as usize + options.slack);
+                    let end = (after.offset + options.slack).min(data.len());
+                    if !rescanned.insert((start, end)) {
+                        continue;
+                    }
+                    let found = rescan(data, start..end, &located, &rebuilt, options.max_hamming);
+                    let ids: Vec<u64> = found.iter().map(|lf| lf.frame.header.frame_id).collect();
+                    located.extend(found);
+                    (RecipeAction::Rescanned { start, end }, ids)
+                }
+                RecoveryRecipe::InsertParityFrame { between, .. } => {
+                    let Some((blocks, decoder)) = parity else {
+                        continue;
+                    };
+                    let mut known: BTreeMap<u64, Frame> = BTreeMap::new();
+                    for f in located.iter().map(|lf| &lf.frame).chain(&rebuilt) {
+                        known.entry(f.header.frame_id).or_insert_with(|| f.clone());
+                    }
+                    let Some((block, frames)) = blocks
+                        .iter()
+                        .filter(|b| b.data_ids().any(|id| id > between.0 && id < between.1))
+                        .map(|b| (b, heal_block(&known, b, decoder).0))
+                        .find(|(_, frames)| !frames.is_empty())
+                    else {
+                        continue;
+                    };
+                    let ids: Vec<u64> = frames.iter().map(|f| f.header.frame_id).collect();
+                    rebuilt.extend(frames);
+                    let action = RecipeAction::Parity {
+                        block_start_id: block.block_start_id,
+                    };
+                    (action, ids)
+                }
+            };
+            if found.is_empty() {
+                continue;
+            }
+
+            #[cfg(feature = "logging")]
+            tracing::info!("Recipe {:?} recovered frames {:?}", recipe, found);
+
+            progress = true;
+            steps.push(RecipeStep {
+                round: rounds,
+                recipe,
+                action,
+                recovered: found,
+            });
+        }
+
+        located.sort_by_key(|lf| lf.offset);
+        if !progress {
+            break;
+        }
+    }
+
+    let frames = located
+        .iter()
+        .map(|lf| lf.frame.clone())
+        .chain(rebuilt.iter().cloned())
+        .collect();
+    RecipeRun {
+        report: analyze_timeline(frames),
+        located,
+        rebuilt,
+        steps,
+        rounds,
+    }
+}
+
+/// Insert rebuilt frames into the stream they were lost from
+///
+/// Every byte of `data` is kept; each frame of `rebuilt` is encoded with its
+/// stored trailer (so signatures survive) and inserted right after the frame
+/// its back-link points to, or else right before the frame linking to it.
+/// Runs of rebuilt frames follow each other in ID order; frames with neither
+/// neighbor in `located` or `rebuilt` are appended at the end.
+pub fn splice_rebuilt(
+    data: &[u8],
+    located: &[LocatedFrame],
+    rebuilt: &[Frame],
+) -> Result<Vec<u8>, FrameError> {
+    let mut order: Vec<&Frame> = rebuilt.iter().collect();
+    order.sort_by_key(|f| f.header.frame_id);
+    let hashes: Vec<_> = order.iter().map(|f| f.compute_hash()).collect();
+
+    // Hash each frame once: end offset of the first located frame with a
+    // given hash, start offset of the first one linking back to it
+    let mut end_of: BTreeMap<[u8; BLAKE3_HASH_SIZE], usize> = BTreeMap::new();
+    let mut start_after: BTreeMap<[u8; BLAKE3_HASH_SIZE], usize> = BTreeMap::new();
+    for lf in located {
+        end_of
+            .entry(lf.frame.compute_hash())
+            .or_insert(lf.offset + lf.size);
+        start_after
+            .entry(lf.frame.header.prev_hash)
+            .or_insert(lf.offset);
+    }
+    // Rebuilt frames by (hash, position) and (prev_hash, position)
+    let rebuilt_hash: BTreeSet<([u8; BLAKE3_HASH_SIZE], usize)> =
+        hashes.iter().copied().zip(0..).collect();
+    let rebuilt_prev: BTreeSet<([u8; BLAKE3_HASH_SIZE], usize)> =
+        order.iter().map(|f| f.header.prev_hash).zip(0..).collect();
+
+    let mut at: Vec<Option<usize>> = vec![None; order.len()];
+    for i in 0..order.len() {
+        let prev = order[i].header.prev_hash;
+        at[i] = end_of.get(&prev).copied().or_else(|| {
+            rebuilt_hash
+                .range((prev, 0)..(prev, i))
+                .next()
+                .and_then(|&(_, j)| at[j])
+        });
+    }
+    for i in (0..order.len()).rev() {
+        if at[i].is_none() {
+            at[i] = start_after.get(&hashes[i]).copied().or_else(|| {
+                rebuilt_prev
+                    .range((hashes[i], i + 1)..)
+                    .next()
+                    .filter(|&&(h, _)| h == hashes[i])
+                    .and_then(|&(_, j)| at[j])
+            });
+        }
+    }
+
+    let mut inserts: Vec<(usize, usize)> = at
+        .iter()
+        .enumerate()
+        .map(|(i, pos)| (pos.unwrap_or(data.len()).min(data.len()), i))
+        .collect();
+    inserts.sort();
+
+    let mut out = Vec::with_capacity(data.len());
+    let mut copied = 0;
+    for (pos, i) in inserts {
+        out.extend_from_slice(&data[copied..pos]);
+        out.extend_from_slice(&encode_frame_struct_exact(order[i])?);
+        copied = pos;
+    }
+    out.extend_from_slice(&data[copied..]);
+    Ok(out)
+}
+
+/// Try every offset in `range` with relaxed marker matching, keeping frames not seen before
+fn rescan(
+    data: &[u8],
+    range: core::ops::Range<usize>,
+    located: &[LocatedFrame],
+    rebuilt: &[Frame],
+    max_hamming: u32,
+) -> Vec<LocatedFrame> {
+    let known = located.iter().map(|lf| &lf.frame).chain(rebuilt);
+    let mut hashes: BTreeSet<[u8; BLAKE3_HASH_SIZE]> =
+        known.clone().map(|f| f.compute_hash()).collect();
+    let mut links: BTreeSet<[u8; BLAKE3_HASH_SIZE]> = known.map(|f| f.header.prev_hash).collect();
+
+    let mut found = Vec::new();
+    let mut pos = range.start;
+    while pos < range.end {
+        let Some(lf) = decode_at(data, pos, max_hamming) else {
+            pos += 1;
+            continue;
+        };
+        let hash = lf.frame.compute_hash();
+        let verified = lf.frame.header.flags.trailer_type() != TrailerType::None;
+        let linked = hashes.contains(&lf.frame.header.prev_hash) || links.contains(&hash);
+        if hashes.contains(&hash) || !(verified || linked) {
+            pos += 1;
+            continue;
+        }
+        hashes.insert(hash);
+        links.insert(lf.frame.header.prev_hash);
+        pos += lf.size;
+        found.push(lf);
+    }
+    found
+}
+
+/// Decode one block; returns verified rebuilt frames and IDs that could not be verified
+fn heal_block(
+    known: &BTreeMap<u64, Frame>,
+    block: &ParityBlock,
+    decoder: &dyn RedundancyDecoder,
+) -> (Vec<Frame>, Vec<u64>) {
+    let missing: Vec<u64> = block
+        .data_ids()
+        .filter(|id| !known.contains_key(id))
+        .collect();
+    if missing.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+
+    let parity: Vec<(usize, &Frame)> = block
+        .parity_frame_ids
+        .iter()
+        .enumerate()
+        .filter_map(|(j, id)| known.get(id).map(|f| (j, f)))
+        .collect();
+    let Some(shard_len) = parity.first().map(|(_, f)| parity_shard(f).len()) else {
+        return (Vec::new(), Vec::new());
+    };
+    let present: Vec<(usize, &Frame)> = block
+        .data_ids()
+        .enumerate()
+        .filter_map(|(i, id)| known.get(&id).map(|f| (i, f)))
+        .collect();
+    // Shards as the encoder laid them out: zero-padded data, then parity
+    let data_shards: Vec<(usize, Vec<u8>)> = present
+        .iter()
+        .map(|&(i, f)| (i, decoder.data_shard(f)))
+        .collect();
+    if !decoder.can_reconstruct(present.len() + parity.len(), block.data)
+        || data_shards.iter().any(|(_, d)| d.len() > shard_len)
+        || parity
+            .iter()
+            .any(|(_, f)| parity_shard(f).len() != shard_len)
+    {
+        return (Vec::new(), Vec::new());
+    }
+
+    let total_blocks = (block.data + block.parity) as u32;
+    let mut shards: Vec<FecBlock> = data_shards
+        .into_iter()
+        .map(|(i, mut data)| {
+            data.resize(shard_len, 0);
+            FecBlock {
+                block_id: block.block_start_id,
+                index: i as u32,
+                total_blocks,
+                data,
+            }
+        })
+        .collect();
+    shards.extend(parity.iter().map(|&(j, f)| FecBlock {
+        block_id: block.block_start_id,
+        index: (block.data + j) as u32,
+        total_blocks,
+        data: parity_shard(f).to_vec(),
+    }));
+
+    let decoded = match decoder.decode_batch(&shards, block.data) {
+        Ok(decoded) if decoded.len() == block.data => decoded,
+        _ => {
+            #[cfg(feature = "logging")]
+            tracing::warn!("Parity block at {} failed to decode", block.block_start_id);
+
+            return (Vec::new(), Vec::new());
+        }
+    };
+
+    if decoder.restores_headers() {
+        return check_restored(known, block, &missing, decoded);
+    }
+
+    // Version and flags of the surviving frames, minus the positional bits
+    let template = present
+        .first()
+        .or(parity.first())
+        .map(|(_, f)| &f.header)
+        .expect("block has a surviving frame");
+    let positional = FrameFlags::IS_FIRST | FrameFlags::IS_LAST;
+    let base_flags = template.flags.as_u8() & !positional;
+
+    let mut rebuilt: Vec<Frame> = Vec::new();
+    let mut pending = Vec::new();
+    for run in runs(&missing) {
+        let (first, last) = (run[0], run[run.len() - 1]);
+        let payloads: Vec<&[u8]> = run
+            .iter()
+            .map(|id| &decoded[(id - block.block_start_id) as usize].payload[..])
+            .collect();
+
+        // The previous frame may have just been rebuilt from this block;
+        // a run starting at ID 0 has none
+        let prev = first
+            .checked_sub(1)
+            .and_then(|p| {
+                known
+                    .get(&p)
+                    .or_else(|| rebuilt.iter().find(|f| f.header.frame_id == p))
+            })
+            .map(|f| f.compute_hash())
+            .or_else(|| {
+                // Nothing below: the run starts the stream
+                known
+                    .range(..first)
+                    .next()
+                    .is_none()
+                    .then_some([0u8; BLAKE3_HASH_SIZE])
+            });
+        let next = last.checked_add(1).and_then(|n| known.get(&n));
+        let (Some(prev), Some(next)) = (prev, next) else {
+            pending.extend_from_slice(run);
+            continue;
+        };
+
+        let search = HeaderSearch {
+            version: template.version,
+            base_flags,
+            shard_len,
+            payloads: &payloads,
+            first_id: first,
+            expected: next.header.prev_hash,
+            budget: MAX_HEADER_HYPOTHESES,
+        };
+        match search.run(prev) {
+            Some(frames) => rebuilt.extend(frames),
+            None => {
+                #[cfg(feature = "logging")]
+                tracing::warn!(
+                    "Rebuilt frames {}..={} do not match frame {}'s back-link",
+                    first,
+                    last,
+                    last + 1
+                );
+
+                pending.extend_from_slice(run);
+            }
+        }
+    }
+    (rebuilt, pending)
+}
+
+/// Accept decoded frames that sit at their block position and agree with every known neighbor
+fn check_restored(
+    known: &BTreeMap<u64, Frame>,
+    block: &ParityBlock,
+    missing: &[u64],
+    decoded: Vec<Frame>,
+) -> (Vec<Frame>, Vec<u64>) {
+    let mut rebuilt: Vec<Frame> = Vec::new();
+    let mut pending = Vec::new();
+    for run in runs(missing) {
+        let (first, last) = (run[0], run[run.len() - 1]);
+        let frames: Vec<Frame> = run
+            .iter()
+            .map(|id| decoded[(id - block.block_start_id) as usize].clone())
+            .collect();
+
+        let in_place = frames
+            .iter()
+            .zip(run)
+            .all(|(f, &id)| f.header.frame_id == id);
+        let chained = frames
+            .windows(2)
+            .all(|w| w[1].header.prev_hash == w[0].compute_hash());
+        let prev_ok = first
+            .checked_sub(1)
+            .and_then(|p| {
+                known
+                    .get(&p)
+                    .or_else(|| rebuilt.iter().find(|f| f.header.frame_id == p))
+            })
+            .is_none_or(|p| frames[0].header.prev_hash == p.compute_hash());
+        let next_ok = last
+            .checked_add(1)
+            .and_then(|n| known.get(&n))
+            .is_none_or(|n| n.header.prev_hash == frames[frames.len() - 1].compute_hash());
+
+        if in_place && chained && prev_ok && next_ok {
+            rebuilt.extend(frames);
+        } else {
+            #[cfg(feature = "logging")]
+            tracing::warn!(
+                "Rebuilt frames {}..={} disagree with their block position or neighbors",
+                first,
+                last
+            );
+
+            pending.extend_from_slice(run);
+        }
+    }
+    (rebuilt, pending)
+}
+
+/// Split sorted IDs into runs of consecutive IDs
+fn runs(ids: &[u64]) -> Vec<&[u64]> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    for i in 1..=ids.len() {
+        if i == ids.len() || ids[i] != ids[i - 1] + 1 {
+            out.push(&ids[start..i]);
+            start = i;
+        }
+    }
+    out
+}
+
+/// Depth-first search over header candidates for a run of rebuilt payloads
+struct HeaderSearch<'a> {
+    version: u8,
+    base_flags: u8,
+    shard_len: usize,
+    /// Decoded payloads with padding stripped; the original may end in zeros
+    payloads: &'a [&'a [u8]],
+    first_id: u64,
+    /// Back-link carried by the frame after the run
+    expected: [u8; BLAKE3_HASH_SIZE],
+    budget: usize,
+}
+
+impl HeaderSearch<'_> {
+    fn run(mut self, prev: [u8; BLAKE3_HASH_SIZE]) -> Option<Vec<Frame>> {
+        let mut out = Vec::with_capacity(self.payloads.len());
+        self.step(0, prev, &mut out).then_some(out)
+    }
+
+    fn step(&mut self, k: usize, prev: [u8; BLAKE3_HASH_SIZE], out: &mut Vec<Frame>) -> bool {
+        if k == self.payloads.len() {
+            return prev == self.expected;
+        }
+        let trimmed = self.payloads[k];
+        let mut flag_options = vec![self.base_flags, self.base_flags | FrameFlags::IS_LAST];
+        if prev == [0u8; BLAKE3_HASH_SIZE] {
+            flag_options.push(self.base_flags | FrameFlags::IS_FIRST);
+        }
+
+        for len in trimmed.len()..=self.shard_len {
+            let mut payload = trimmed.to_vec();
+            payload.resize(len, 0);
+            let payload = Bytes::from(payload);
+            for &flags in &flag_options {
+                if self.budget == 0 {
+                    return false;
+                }
+                self.budget -= 1;
+
+                let mut header = FrameHeader::with_flags(
+                    self.first_id + k as u64,
+                    prev,
+                    len as u32,
+                    FrameFlags::new(flags),
+                );
+                header.version = self.version;
+                let frame = Frame::new(header, payload.clone());
+                let hash = frame.compute_hash();
+                out.push(frame);
+                if self.step(k + 1, hash, out) {
+                    return true;
+                }
+                out.pop();
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fec::NoopDecoder;
+    use crate::test_util::chain_from;
+    use alloc::format;
+    use alloc::string::ToString;
+
+    /// Single-parity XOR code: enough to exercise the repair path without `fec-rs`
+    struct XorDecoder;
+
+    impl RedundancyDecoder for XorDecoder {
+        fn decode_batch(
+            &self,
+            blocks: &[FecBlock],
+            total_frames: usize,
+        ) -> Result<Vec<Frame>, FrameError> {
+            let shard_len = blocks[0].data.len();
+            let mut shards: Vec<Option<Vec<u8>>> = vec![None; total_frames];
+            let mut missing = vec![0u8; shard_len];
+            for b in blocks {
+                for (m, x) in missing.iter_mut().zip(&b.data) {
+                    *m ^= x;
+                }
+                if (b.index as usize) < total_frames {
+                    shards[b.index as usize] = Some(b.data.clone());
+                }
+            }
+            Ok(shards
+                .into_iter()
+                .map(|s| {
+                    let mut data = s.unwrap_or_else(|| missing.clone());
+                    while data.last() == Some(&0) {
+                        data.pop();
+                    }
+                    Frame::new(FrameHeader::new(0, [0; 32], data.len() as u32), data.into())
+                })
+                .collect())
+        }
+
+        fn can_reconstruct(&self, available_blocks: usize, total_frames: usize) -> bool {
+            available_blocks >= total_frames
+        }
+    }
+
+    /// `n` chained data frames in blocks of `data`, each followed by one XOR parity frame
+    fn protected_stream(
+        n: u64,
+        data: usize,
+        payload: impl Fn(u64) -> Vec<u8>,
+    ) -> (Vec<Frame>, Vec<ParityBlock>) {
+        protected_stream_from(1, n, data, payload)
+    }
+
+    /// [`protected_stream`] with frame IDs starting at `first`
+    fn protected_stream_from(
+        first: u64,
+        n: u64,
+        data: usize,
+        payload: impl Fn(u64) -> Vec<u8>,
+    ) -> (Vec<Frame>, Vec<ParityBlock>) {
+        let mut frames = Vec::new();
+        let mut blocks = Vec::new();
+        let mut prev = [0u8; BLAKE3_HASH_SIZE];
+        let mut next_id = first;
+        let mut done = 0;
+        while done < n {
+            let count = (n - done).min(data as u64);
+            let start = next_id;
+            let block = chain_from(start..start + count, &mut prev, |id, b| {
+                let i = done + id - start + 1;
+                let b = b.payload(payload(i).into()).with_crc32c();
+                if i == 1 {
+                    b.mark_first()
+                } else {
+                    b
+                }
+            });
+            done += count;
+            next_id += count;
+
+            if block.len() == data {
+                let shard_len = block.iter().map(|f| f.payload.len()).max().unwrap();
+                let mut parity = vec![0u8; shard_len];
+                for f in &block {
+                    for (p, x) in parity.iter_mut().zip(f.payload.iter()) {
+                        *p ^= x;
+                    }
+                }
+                let parity = Bytes::from(parity);
+                let pf = chain_from([next_id], &mut prev, |_, b| {
+                    b.payload(parity.clone()).with_crc32c()
+                });
+                blocks.push(ParityBlock {
+                    block_start_id: start,
+                    data,
+                    parity: 1,
+                    parity_frame_ids: vec![next_id],
+                });
+                next_id += 1;
+                frames.extend(block);
+                frames.extend(pf);
+            } else {
+                frames.extend(block);
+            }
+        }
+        (frames, blocks)
+    }
+
+    fn text(i: u64) -> Vec<u8> {
+        format!("reading {}", "x".repeat(i as usize % 5)).into_bytes()
+    }
+
+    fn without(frames: &[Frame], ids: &[u64]) -> Vec<Frame> {
+        frames
+            .iter()
+            .filter(|f| !ids.contains(&f.header.frame_id))
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn test_heals_gap_with_original_header() {
+        let (frames, blocks) = protected_stream(9, 3, text);
+        // Data frames 1-3 are IDs 1-3 (parity 4), 4-6 are IDs 5-7 (parity 8), ...
+        let damaged = without(&frames, &[6]);
+
+        let recovered = link_with_fec(damaged, &blocks, &XorDecoder);
+        assert_eq!(recovered.reconstructed, vec![6]);
+        assert!(recovered.timeline.gaps.is_empty());
+        assert!(recovered.backlink_errors.is_empty());
+        assert_eq!(recovered.healed.len(), 1);
+        assert_eq!(
+            (
+                recovered.healed[0].gap.before,
+                recovered.healed[0].gap.after
+            ),
+            (5, 7)
+        );
+        assert_eq!(recovered.healed[0].frame_ids, vec![6]);
+
+        let original = &frames[5];
+        let rebuilt = recovered
+            .timeline
+            .frames
+            .iter()
+            .find(|f| f.header.frame_id == 6)
+            .unwrap();
+        assert_eq!(rebuilt.header, original.header);
+        assert_eq!(rebuilt.payload, original.payload);
+    }
+
+    #[test]
+    fn test_restores_trailing_zero_payload_and_first_flag() {
+        let (frames, blocks) = protected_stream(4, 2, |i| {
+            let mut v = i.to_string().into_bytes();
+            v.extend_from_slice(&[0, 0]);
+            v
+        });
+        let damaged = without(&frames, &[1]);
+
+        let recovered = link_with_fec(damaged, &blocks, &XorDecoder);
+        assert_eq!(recovered.reconstructed, vec![1]);
+        let first = &recovered.timeline.frames[0];
+        assert_eq!(first.header, frames[0].header);
+        assert_eq!(first.payload, frames[0].payload);
+        assert!(recovered.timeline.gaps.is_empty());
+    }
+
+    #[test]
+    fn test_splice_rebuilt_restores_original_bytes() {
+        let (frames, _) = protected_stream(9, 3, text);
+        let encode = |fs: &[Frame]| -> Vec<u8> {
+            fs.iter()
+                .flat_map(|f| encode_frame_struct_exact(f).unwrap().to_vec())
+                .collect()
+        };
+        let original = encode(&frames);
+        // A run in the middle, and a run at the start (anchored by successors only)
+        let lost = [1, 2, 6, 7];
+        let damaged = encode(&without(&frames, &lost));
+        let located = scan_stream(&damaged);
+        let rebuilt: Vec<Frame> = frames
+            .iter()
+            .filter(|f| lost.contains(&f.header.frame_id))
+            .rev()
+            .cloned()
+            .collect();
+
+        let spliced = splice_rebuilt(&damaged, &located, &rebuilt).unwrap();
+        assert_eq!(spliced, original);
+    }
+
+    #[test]
+    fn test_unrecoverable_blocks_are_left_alone() {
+        let (frames, blocks) = protected_stream(6, 3, text);
+        // Two losses in one single-parity block
+        let damaged = without(&frames, &[1, 2]);
+
+        let recovered = link_with_fec(damaged.clone(), &blocks, &XorDecoder);
+        assert!(recovered.reconstructed.is_empty());
+        assert!(recovered.healed.is_empty());
+
+        let recovered = link_with_fec(without(&frames, &[6]), &blocks, &NoopDecoder);
+        assert!(recovered.reconstructed.is_empty());
+        assert_eq!(recovered.timeline.gaps.len(), 1);
+    }
+
+    #[test]
+    fn test_rebuild_is_verified_by_successor_backlink() {
+        let (frames, blocks) = protected_stream(3, 3, text);
+        // Frame 3 is the last data frame; the parity frame after it carries the check
+        let recovered = link_with_fec(without(&frames, &[3]), &blocks, &XorDecoder);
+        assert_eq!(recovered.reconstructed, vec![3]);
+
+        // If that back-link does not match, the rebuilt frame is not reinserted
+        let mut damaged = without(&frames, &[3]);
+        damaged.last_mut().unwrap().header.prev_hash = [9; 32];
+        let recovered = link_with_fec(damaged, &blocks, &XorDecoder);
+        assert!(recovered.reconstructed.is_empty());
+        assert_eq!(recovered.unverified, vec![3]);
+    }
+
+    /// Four data frames and two RS parity frames chained after them
+    #[cfg(feature = "fec-rs")]
+    fn rs_protected() -> (Vec<Frame>, [ParityBlock; 1]) {
+        rs_protected_from(1)
+    }
+
+    /// One RS(4+2) block whose data frames start at `first`
+    #[cfg(feature = "fec-rs")]
+    fn rs_protected_from(first: u64) -> (Vec<Frame>, [ParityBlock; 1]) {
+        use crate::fec::{RedundancyEncoder, RsEncoder};
+
+        let mut frames = crate::test_util::chain(first..first + 4, |id, b| {
+            b.payload(Bytes::from(format!("sample {}", id * 37)))
+                .with_blake3()
+        });
+        let shards = RsEncoder::new(4, 2).encode_batch(&frames, 0).unwrap();
+        let mut prev = frames[3].compute_hash();
+        frames.extend(chain_from(first + 4..first + 6, &mut prev, |id, b| {
+            let shard = &shards[(id - first) as usize];
+            b.payload(Bytes::from(shard.data.clone())).with_blake3()
+        }));
+        let blocks = [ParityBlock {
+            block_start_id: first,
+            data: 4,
+            parity: 2,
+            parity_frame_ids: vec![first + 4, first + 5],
+        }];
+        (frames, blocks)
+    }
+
+    #[cfg(feature = "fec-rs")]
+    #[test]
+    fn test_heals_runs_with_reed_solomon() {
+        use crate::fec::RsDecoder;
+
+        let (frames, blocks) = rs_protected();
+        let recovered = link_with_fec(without(&frames, &[2, 3]), &blocks, &RsDecoder);
+        assert_eq!(recovered.reconstructed, vec![2, 3]);
+        assert!(recovered.timeline.gaps.is_empty());
+        assert_eq!(recovered.healed[0].frame_ids, vec![2, 3]);
+        for (rebuilt, original) in recovered.timeline.frames.iter().zip(&frames) {
+            assert_eq!(rebuilt.header, original.header);
+            assert_eq!(rebuilt.payload, original.payload);
+        }
+    }
+
+    #[cfg(feature = "fec-rs")]
+    #[test]
+    fn test_reed_solomon_restores_header_without_successor() {
+        use crate::fec::RsDecoder;
+
+        // Frame 4 and its successor (parity frame 5) are both lost: no
+        // back-link to search against, but the shard carries the header
+        let (frames, blocks) = rs_protected();
+        let recovered = link_with_fec(without(&frames, &[4, 5]), &blocks, &RsDecoder);
+        assert_eq!(recovered.reconstructed, vec![4]);
+        assert!(recovered.unverified.is_empty());
+        let rebuilt = recovered
+            .timeline
+            .frames
+            .iter()
+            .find(|f| f.header.frame_id == 4)
+            .unwrap();
+        assert_eq!(
+            crate::encoder::encode_frame_struct(rebuilt).unwrap(),
+            crate::encoder::encode_frame_struct(&frames[3]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_heals_block_starting_at_id_zero() {
+        let (frames, blocks) = protected_stream_from(0, 6, 3, text);
+        // IDs 0-2 data (parity 3), 4-6 data (parity 7)
+        let recovered = link_with_fec(without(&frames, &[0]), &blocks, &XorDecoder);
+        assert_eq!(recovered.reconstructed, vec![0]);
+        assert_eq!(recovered.timeline.frames[0].header, frames[0].header);
+        assert!(recovered.timeline.gaps.is_empty());
+    }
+
+    #[cfg(feature = "fec-rs")]
+    #[test]
+    fn test_reed_solomon_heals_block_starting_at_id_zero() {
+        use crate::fec::RsDecoder;
+
+        let (frames, blocks) = rs_protected_from(0);
+        for lost in [&[0u64, 1][..], &[2, 3]] {
+            let recovered = link_with_fec(without(&frames, lost), &blocks, &RsDecoder);
+            assert_eq!(recovered.reconstructed, lost.to_vec());
+            assert!(recovered.timeline.gaps.is_empty());
+        }
+    }
+
+    /// Encode frames back to back, returning the stream and each frame's offset
+    fn encode(frames: &[Frame]) -> (Vec<u8>, Vec<usize>) {
+        let mut data = Vec::new();
+        let mut offsets = Vec::new();
+        for f in frames {
+            offsets.push(data.len());
+            data.extend_from_slice(&crate::encoder::encode_frame_struct(f).unwrap());
+        }
+        (data, offsets)
+    }
+
+    /// Flip three bits of a frame marker: beyond what the first scan tolerates
+    fn damage_marker(data: &mut [u8], offset: usize) {
+        data[offset] ^= 0b0000_0111;
+    }
+
+    #[test]
+    fn test_recipes_rescan_damaged_marker() {
+        let (frames, _) = protected_stream(5, 10, text);
+        let (mut data, offsets) = encode(&frames);
+        damage_marker(&mut data, offsets[2]);
+        assert!(!scan_stream(&data)
+            .iter()
+            .any(|lf| lf.frame.header.frame_id == 3));
+
+        let run = apply_recipes(&data, None, RecipeOptions::default());
+        assert!(run.report.timeline.gaps.is_empty());
+        assert_eq!(run.report.timeline.frames.len(), 5);
+        let step = run.recovered_by(3).expect("frame 3 recovered");
+        assert_eq!(step.round, 1);
+        assert!(matches!(
+            step.recipe,
+            RecoveryRecipe::RewindOffset { near_frame: 4, .. }
+        ));
+        assert!(matches!(
+            step.action,
+            RecipeAction::Rescanned { start, end } if start <= offsets[2] && end > offsets[2]
+        ));
+        assert_eq!(run.rounds, 2);
+    }
+
+    #[test]
+    fn test_recipes_chain_rescan_into_parity() {
+        let (frames, blocks) = protected_stream(9, 3, text);
+        // Frame 6 is lost and frame 7's marker damaged: the XOR block 5-7 needs 7 back first
+        let kept = without(&frames, &[6]);
+        let (mut data, offsets) = encode(&kept);
+        let at7 = kept.iter().position(|f| f.header.frame_id == 7).unwrap();
+        damage_marker(&mut data, offsets[at7]);
+
+        let run = apply_recipes(
+            &data,
+            Some((&blocks, &XorDecoder)),
+            RecipeOptions::default(),
+        );
+        assert!(run.report.timeline.gaps.is_empty());
+        assert!(verify_backlinks(&run.report.timeline).is_empty());
+
+        let rescued = run.recovered_by(7).unwrap();
+        assert!(matches!(rescued.action, RecipeAction::Rescanned { .. }));
+        let healed = run.recovered_by(6).unwrap();
+        assert_eq!(healed.action, RecipeAction::Parity { block_start_id: 5 });
+        assert!(healed.round > rescued.round);
+        assert_eq!(run.rebuilt.len(), 1);
+        assert_eq!(run.rebuilt[0].header, frames[5].header);
+
+        // Without parity the rescan still helps, but frame 6 stays missing
+        let run = apply_recipes(&data, None, RecipeOptions::default());
+        assert!(run.recovered_by(7).is_some());
+        assert_eq!(run.report.timeline.gaps.len(), 1);
+    }
+}
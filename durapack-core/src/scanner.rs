@@ -1,21 +1,307 @@
 //! Stream scanner for damaged or noisy input
-
-use crate::constants::{FRAME_MARKER, MAX_FRAME_SIZE, MIN_HEADER_SIZE};
-use crate::decoder::decode_frame_from_bytes;
-use crate::types::Frame;
+//!
+//! Marker candidates are produced by [`MarkerCandidates`], which walks the
+//! buffer once and yields exact, sync-anchored, preamble-anchored and fuzzy
+//! (bounded-Hamming) hits in offset order. Each candidate source only ever
+//! moves forward, so scanning is linear in the input size even when the
+//! input contains no valid markers at all.
+
+use crate::constants::{
+    header_size, TrailerType, BLAKE3_HASH_SIZE, FRAME_MARKER, MAX_FRAME_SIZE, MAX_HEADER_SIZE,
+    MAX_MARKER_HAMMING, MIN_HEADER_SIZE, MIN_PREAMBLE_LEN, PREAMBLE_PATTERN, ROBUST_SYNC_WORD,
+};
+use crate::decoder::{decode_frame_from_bytes, decode_header};
+use crate::error::FrameError;
+use crate::types::{Frame, FrameHeader};
+use alloc::collections::BTreeMap;
+use alloc::vec;
 use alloc::vec::Vec;
 use bytes::Bytes;
+use memchr::memmem;
 
 #[cfg(feature = "logging")]
 use tracing::{debug, warn};
 
-/// Information about a found marker candidate used for confidence scoring
-#[derive(Debug, Clone, Copy, Default)]
-struct MarkerHit {
+/// How a marker candidate was located
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CandidateKind {
+    /// The 4-byte marker matched exactly
+    Exact,
+    /// The marker is damaged but sits immediately after a robust sync word
+    SyncAnchored,
+    /// The marker is damaged but sits immediately after a preamble run
+    PreambleAnchored,
+    /// The marker matched within the allowed Hamming distance
+    Fuzzy,
+}
+
+/// A potential frame start, used for decoding attempts and confidence scoring
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarkerCandidate {
+    /// Byte offset of the (possibly damaged) marker
+    pub offset: usize,
+    /// Bit distance between the bytes at `offset` and `FRAME_MARKER`
+    pub hamming: u32,
+    /// A robust sync word immediately precedes the marker
+    pub had_sync: bool,
+    /// A preamble run precedes the marker (or its sync word)
+    pub had_preamble: bool,
+    /// How the candidate was located
+    pub kind: CandidateKind,
+}
+
+/// Lookahead state for a single candidate source
+#[derive(Debug, Clone, Copy)]
+enum Ahead {
+    Unknown,
+    At(usize),
+    Done,
+}
+
+/// Single-pass generator of marker candidates in offset order
+///
+/// Three sources are merged lazily: marker matches within `max_hamming` bits
+/// (SIMD-accelerated where available), robust sync words, and preamble runs.
+/// Each source caches its next hit and is only re-queried once the cursor has
+/// moved past it, so the whole buffer is examined a constant number of times.
+#[derive(Debug, Clone)]
+pub struct MarkerCandidates<'a> {
+    data: &'a [u8],
     pos: usize,
-    hamming: u32,
-    had_sync: bool,
-    had_preamble: bool,
+    max_hamming: u32,
+    marker: Ahead,
+    sync: Ahead,
+    preamble: Ahead,
+}
+
+impl<'a> MarkerCandidates<'a> {
+    /// Create a candidate generator using the default `MAX_MARKER_HAMMING` tolerance
+    pub fn new(data: &'a [u8]) -> Self {
+        Self::with_max_hamming(data, MAX_MARKER_HAMMING)
+    }
+
+    /// Create a candidate generator with a custom marker Hamming tolerance (in bits)
+    pub fn with_max_hamming(data: &'a [u8], max_hamming: u32) -> Self {
+        Self {
+            data,
+            pos: 0,
+            max_hamming,
+            marker: Ahead::Unknown,
+            sync: Ahead::Unknown,
+            preamble: Ahead::Unknown,
+        }
+    }
+
+    /// Current cursor position; the next candidate will be at or after it
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Move the cursor forward (e.g., past a decoded frame)
+    ///
+    /// Seeking backwards is ignored to keep the scan linear.
+    pub fn seek(&mut self, pos: usize) {
+        if pos > self.pos {
+            self.pos = pos;
+        }
+    }
+
+    fn refresh(&mut self) {
+        let pos = self.pos;
+        if needs_refresh(self.marker, pos) {
+            self.marker = match find_marker_within(self.data, pos, self.max_hamming) {
+                Some(p) => Ahead::At(p),
+                None => Ahead::Done,
+            };
+        }
+        if needs_refresh(self.sync, pos) {
+            // The anchor is the byte after the sync word, so the word itself may start before `pos`
+            let from = pos.saturating_sub(ROBUST_SYNC_WORD.len());
+            self.sync =
+                match memmem::find(&self.data[from.min(self.data.len())..], ROBUST_SYNC_WORD) {
+                    Some(p) => Ahead::At(from + p + ROBUST_SYNC_WORD.len()),
+                    None => Ahead::Done,
+                };
+        }
+        if needs_refresh(self.preamble, pos) {
+            self.preamble = match find_preamble_end(self.data, pos) {
+                Some(p) => Ahead::At(p),
+                None => Ahead::Done,
+            };
+        }
+    }
+
+    /// Classify the position `p` produced by one of the sources
+    fn classify(&self, p: usize) -> Option<MarkerCandidate> {
+        let data = self.data;
+        if p + FRAME_MARKER.len() > data.len() {
+            return None;
+        }
+        let hamming = marker_distance(&data[p..p + FRAME_MARKER.len()]);
+        let had_sync =
+            p >= ROBUST_SYNC_WORD.len() && &data[p - ROBUST_SYNC_WORD.len()..p] == ROBUST_SYNC_WORD;
+        let had_preamble = preamble_ends_at(data, p)
+            || (had_sync && preamble_ends_at(data, p - ROBUST_SYNC_WORD.len()));
+        let kind = if hamming == 0 {
+            CandidateKind::Exact
+        } else if hamming <= self.max_hamming {
+            CandidateKind::Fuzzy
+        } else if had_sync {
+            CandidateKind::SyncAnchored
+        } else if had_preamble && !data[p..].starts_with(ROBUST_SYNC_WORD) {
+            CandidateKind::PreambleAnchored
+        } else {
+            return None;
+        };
+        Some(MarkerCandidate {
+            offset: p,
+            hamming,
+            had_sync,
+            had_preamble,
+            kind,
+        })
+    }
+}
+
+impl Iterator for MarkerCandidates<'_> {
+    type Item = MarkerCandidate;
+
+    fn next(&mut self) -> Option<MarkerCandidate> {
+        loop {
+            self.refresh();
+            let p = [self.marker, self.sync, self.preamble]
+                .into_iter()
+                .filter_map(|a| match a {
+                    Ahead::At(p) => Some(p),
+                    _ => None,
+                })
+                .min()?;
+            self.pos = p + 1;
+            if let Some(c) = self.classify(p) {
+                return Some(c);
+            }
+        }
+    }
+}
+
+fn needs_refresh(ahead: Ahead, pos: usize) -> bool {
+    match ahead {
+        Ahead::Unknown => true,
+        Ahead::At(p) => p < pos,
+        Ahead::Done => false,
+    }
+}
+
+/// Bit distance between a 4-byte window and `FRAME_MARKER`
+fn marker_distance(window: &[u8]) -> u32 {
+    let w = u32::from_le_bytes([window[0], window[1], window[2], window[3]]);
+    (w ^ u32::from_le_bytes(*FRAME_MARKER)).count_ones()
+}
+
+/// Find the first offset `>= from` whose 4-byte window is within `max` bits of the marker
+fn find_marker_within(data: &[u8], from: usize, max: u32) -> Option<usize> {
+    let m = FRAME_MARKER.len();
+    if from + m > data.len() {
+        return None;
+    }
+    if max == 0 {
+        return memmem::find(&data[from..], FRAME_MARKER).map(|p| from + p);
+    }
+    let mut i = from;
+    #[cfg(target_arch = "x86_64")]
+    {
+        if let Some(p) = simd::find_within(data, &mut i, max) {
+            return Some(p);
+        }
+    }
+    while i + m <= data.len() {
+        if marker_distance(&data[i..i + m]) <= max {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// SSE2 Hamming filter over 16 candidate offsets at a time
+#[cfg(target_arch = "x86_64")]
+mod simd {
+    use super::FRAME_MARKER;
+    use core::arch::x86_64::*;
+
+    const LANES: usize = 16;
+
+    /// Scan full 16-offset blocks starting at `*i`; on return `*i` points at the
+    /// first offset not yet examined so the caller can finish the tail.
+    pub(super) fn find_within(data: &[u8], i: &mut usize, max: u32) -> Option<usize> {
+        let max = max.min(32) as i8;
+        while *i + LANES + FRAME_MARKER.len() - 1 <= data.len() {
+            // SAFETY: SSE2 is part of the x86_64 baseline, and the loop bound guarantees
+            // that all four unaligned 16-byte loads stay within `data`.
+            let mask = unsafe { block_mask(data.as_ptr().add(*i), max) };
+            if mask != 0 {
+                return Some(*i + mask.trailing_zeros() as usize);
+            }
+            *i += LANES;
+        }
+        None
+    }
+
+    #[inline]
+    unsafe fn popcount_epi8(x: __m128i) -> __m128i {
+        let m1 = _mm_set1_epi8(0x55);
+        let m2 = _mm_set1_epi8(0x33);
+        let m4 = _mm_set1_epi8(0x0f);
+        let x = _mm_sub_epi8(x, _mm_and_si128(_mm_srli_epi16(x, 1), m1));
+        let x = _mm_add_epi8(
+            _mm_and_si128(x, m2),
+            _mm_and_si128(_mm_srli_epi16(x, 2), m2),
+        );
+        _mm_and_si128(_mm_add_epi8(x, _mm_srli_epi16(x, 4)), m4)
+    }
+
+    /// Bit `j` of the result is set when the window at `p + j` is within `max` bits
+    #[inline]
+    unsafe fn block_mask(p: *const u8, max: i8) -> u32 {
+        let mut sum = _mm_setzero_si128();
+        for (k, &b) in FRAME_MARKER.iter().enumerate() {
+            let v = _mm_loadu_si128(p.add(k) as *const __m128i);
+            let x = _mm_xor_si128(v, _mm_set1_epi8(b as i8));
+            sum = _mm_add_epi8(sum, popcount_epi8(x));
+        }
+        let within = _mm_cmpeq_epi8(_mm_min_epu8(sum, _mm_set1_epi8(max)), sum);
+        _mm_movemask_epi8(within) as u32
+    }
+}
+
+/// Find the end of the next preamble run of at least `MIN_PREAMBLE_LEN` bytes starting at or after `from`
+fn find_preamble_end(data: &[u8], from: usize) -> Option<usize> {
+    let mut i = from;
+    while i < data.len() {
+        let start = i + memchr::memchr(PREAMBLE_PATTERN[0], &data[i..])?;
+        let mut run = 0usize;
+        while start + run < data.len() && data[start + run] == PREAMBLE_PATTERN[run % 2] {
+            run += 1;
+        }
+        if run >= MIN_PREAMBLE_LEN {
+            return Some(start + run);
+        }
+        // Any run starting inside this one is a suffix of it and ends at the same place
+        i = start + run.max(1);
+    }
+    None
+}
+
+/// Whether the `MIN_PREAMBLE_LEN` bytes before `end` form an alternating preamble
+fn preamble_ends_at(data: &[u8], end: usize) -> bool {
+    if end < MIN_PREAMBLE_LEN {
+        return false;
+    }
+    let run = &data[end - MIN_PREAMBLE_LEN..end];
+    let phase = if run[0] == PREAMBLE_PATTERN[0] { 0 } else { 1 };
+    run.iter()
+        .enumerate()
+        .all(|(k, &b)| b == PREAMBLE_PATTERN[(k + phase) % 2])
 }
 
 /// A frame found at a specific offset in the stream
@@ -32,67 +318,90 @@ pub struct LocatedFrame {
 
     /// Confidence score [0.0, 1.0]
     pub confidence: f32,
+
+    /// Bits into the byte at `offset` where the frame starts (MSB first)
+    ///
+    /// Always 0 for byte-aligned scans; see [`scan_stream_bit_aligned`].
+    pub bit_shift: u8,
+
+    /// The frame was recovered from bit-inverted input
+    pub inverted: bool,
+
+    /// Byte insertion/deletion undone to make the trailer verify, if any
+    ///
+    /// Only set by [`scan_stream_with_indels`]; `size` is then the number of
+    /// stream bytes the damaged frame occupied.
+    pub repair: Option<IndelRepair>,
+}
+
+impl LocatedFrame {
+    /// Absolute position of the frame start in bits
+    pub fn bit_position(&self) -> usize {
+        self.offset * 8 + self.bit_shift as usize
+    }
+}
+
+impl AsRef<Frame> for LocatedFrame {
+    fn as_ref(&self) -> &Frame {
+        &self.frame
+    }
+}
+
+/// A frame whose header parsed but whose body did not decode
+///
+/// For `HEADER_CRC_VERSION` frames the header CRC verified, so the frame ID,
+/// prev_hash and size are trustworthy even though the payload or trailer is
+/// damaged ([`DamagedFrame::header_verified`]). Version 1 frames are reported
+/// only when the marker matched exactly, the whole frame was present and its
+/// CRC32C or BLAKE3 trailer failed; their header fields are what the damaged
+/// bytes say and may themselves be hit.
+#[derive(Debug, Clone)]
+pub struct DamagedFrame {
+    /// Byte offset where the frame marker was found
+    pub offset: usize,
+
+    /// The verified header
+    pub header: FrameHeader,
+
+    /// Total size of the frame in bytes, as declared by the verified header
+    pub size: usize,
+
+    /// Why the frame body failed to decode
+    pub error: FrameError,
+}
+
+impl DamagedFrame {
+    /// Whether a header CRC vouches for the header fields
+    pub fn header_verified(&self) -> bool {
+        self.header.has_header_crc()
+    }
+}
+
+/// Outcome of a decoding attempt at a single candidate
+enum Attempt {
+    Decoded(LocatedFrame),
+    Damaged(DamagedFrame),
+    Rejected(FrameError),
 }
 
 /// Scan a byte stream for valid frames, even if the stream is damaged
 ///
 /// This function:
-/// 1. Searches byte-by-byte for the frame marker
-/// 2. Attempts to decode a frame at each potential position
+/// 1. Walks the marker candidates produced by [`MarkerCandidates`]
+/// 2. Attempts to decode a frame at each candidate position
 /// 3. Validates the frame and collects successfully decoded frames
-/// 4. Continues scanning after each frame (or failed attempt)
+/// 4. Continues scanning after each frame (or at the next candidate after a failed attempt)
 ///
 /// This allows recovery of valid frames even when:
 /// - The start of the stream is corrupted
 /// - There are gaps or corruption between frames
 /// - Frames are missing or damaged
 pub fn scan_stream(data: &[u8]) -> Vec<LocatedFrame> {
-    let mut results = Vec::new();
-    let mut pos = 0;
-
     #[cfg(feature = "logging")]
     debug!("Starting stream scan of {} bytes", data.len());
 
-    while pos < data.len() {
-        // Look for frame marker
-        if let Some(hit) = find_marker(&data[pos..]) {
-            let absolute_pos = pos + hit.pos;
-
-            #[cfg(feature = "logging")]
-            debug!("Found potential marker at offset {}", absolute_pos);
-
-            // Try to decode frame starting at this position
-            match try_decode_at_offset(data, absolute_pos) {
-                Ok(mut located_frame) => {
-                    // Score confidence for this hit
-                    located_frame.confidence = compute_confidence(&located_frame, hit);
-
-                    #[cfg(feature = "logging")]
-                    debug!(
-                        "Decoded frame {} at {} (size {}), conf {:.2}",
-                        located_frame.frame.header.frame_id,
-                        located_frame.offset,
-                        located_frame.size,
-                        located_frame.confidence
-                    );
-
-                    // Jump past this frame
-                    pos = absolute_pos + located_frame.size;
-                    results.push(located_frame);
-                }
-                Err(_e) => {
-                    #[cfg(feature = "logging")]
-                    warn!("Failed to decode frame at offset {}: {}", absolute_pos, _e);
-
-                    // Move past this marker and continue searching
-                    pos = absolute_pos + FRAME_MARKER.len();
-                }
-            }
-        } else {
-            // No more markers found
-            break;
-        }
-    }
+    let mut stats = ScanStats::default();
+    let results = scan_candidates(data, &mut stats, &mut Vec::new());
 
     #[cfg(feature = "logging")]
     debug!(
@@ -104,14 +413,222 @@ pub fn scan_stream(data: &[u8]) -> Vec<LocatedFrame> {
     results
 }
 
+/// Shared scan loop: decode every candidate, then arbitrate overlapping decodes
+///
+/// Candidates inside a decoded frame are still tried, so a false positive
+/// that swallows bytes cannot hide the real frames behind it. Failures and
+/// damaged headers that fall strictly inside an accepted frame are payload
+/// content and are not counted.
+fn scan_candidates(
+    data: &[u8],
+    stats: &mut ScanStats,
+    damaged: &mut Vec<DamagedFrame>,
+) -> Vec<LocatedFrame> {
+    let mut decoded = Vec::new();
+    let mut examined = Vec::new();
+    let mut failures = Vec::new();
+    let mut headers_only = Vec::new();
+
+    for hit in MarkerCandidates::new(data) {
+        examined.push(hit.offset);
+
+        #[cfg(feature = "logging")]
+        debug!(
+            "Found potential marker at offset {} ({:?})",
+            hit.offset, hit.kind
+        );
+
+        match try_decode_candidate(data, &hit) {
+            Attempt::Decoded(mut located_frame) => {
+                located_frame.confidence = compute_confidence(&located_frame, hit);
+
+                #[cfg(feature = "logging")]
+                debug!(
+                    "Decoded frame {} at {} (size {}), conf {:.2}",
+                    located_frame.frame.header.frame_id,
+                    located_frame.offset,
+                    located_frame.size,
+                    located_frame.confidence
+                );
+
+                decoded.push(located_frame);
+            }
+            Attempt::Damaged(frame) => {
+                #[cfg(feature = "logging")]
+                warn!(
+                    "Frame {} at offset {} has a valid header but failed to decode: {}",
+                    frame.header.frame_id, frame.offset, frame.error
+                );
+
+                headers_only.push(frame);
+            }
+            Attempt::Rejected(_e) => {
+                #[cfg(feature = "logging")]
+                warn!("Failed to decode frame at offset {}: {}", hit.offset, _e);
+
+                failures.push((
+                    hit.offset,
+                    matches!(_e, FrameError::HeaderChecksumMismatch { .. }),
+                ));
+            }
+        }
+    }
+
+    let (results, rejected) = arbitrate(decoded);
+
+    let inside = |offset: usize| {
+        let idx = results.partition_point(|f| f.offset < offset);
+        idx > 0 && offset < results[idx - 1].offset + results[idx - 1].size
+    };
+
+    stats.markers_found += examined.into_iter().filter(|&o| !inside(o)).count();
+    for (offset, header_crc) in failures {
+        if !inside(offset) {
+            stats.decode_failures += 1;
+            if header_crc {
+                stats.header_checksum_failures += 1;
+            }
+        }
+    }
+    for frame in headers_only {
+        if !inside(frame.offset) {
+            stats.decode_failures += 1;
+            stats.damaged_frames += 1;
+            damaged.push(frame);
+        }
+    }
+    stats.bytes_recovered += results.iter().map(|f| f.size).sum::<usize>();
+    stats.rejected_overlaps.extend(rejected);
+
+    results
+}
+
+/// Confidence bonus for an accepted frame whose prev_hash names the accepted frame before it
+const ARBITRATION_BACKLINK_BONUS: f64 = 0.05;
+
+/// Choose the maximum-confidence set of non-overlapping frames
+///
+/// Decoded candidates arrive in offset order. A candidate lying entirely
+/// inside a frame whose trailer verified is that frame's payload and is
+/// dropped up front. The rest is weighted interval scheduling: each frame
+/// is worth its confidence, plus [`ARBITRATION_BACKLINK_BONUS`] when it
+/// directly follows the frame its prev_hash names.
+fn arbitrate(decoded: Vec<LocatedFrame>) -> (Vec<LocatedFrame>, Vec<RejectedOverlap>) {
+    let end = |f: &LocatedFrame| f.offset + f.size;
+
+    // Fast path: nothing overlaps in a clean stream
+    if decoded.windows(2).all(|w| end(&w[0]) <= w[1].offset) {
+        return (decoded, Vec::new());
+    }
+
+    let mut contained = vec![false; decoded.len()];
+    let mut verified_end = 0usize;
+    for (i, f) in decoded.iter().enumerate() {
+        contained[i] = end(f) <= verified_end;
+        if f.frame.header.flags.trailer_type() != TrailerType::None {
+            verified_end = verified_end.max(end(f));
+        }
+    }
+
+    let mut order: Vec<usize> = (0..decoded.len()).filter(|&i| !contained[i]).collect();
+    order.sort_by_key(|&i| end(&decoded[i]));
+    let ends: Vec<usize> = order.iter().map(|&i| end(&decoded[i])).collect();
+
+    let mut by_hash: BTreeMap<[u8; BLAKE3_HASH_SIZE], Vec<usize>> = BTreeMap::new();
+    for (k, &i) in order.iter().enumerate() {
+        by_hash
+            .entry(decoded[i].frame.compute_hash())
+            .or_default()
+            .push(k);
+    }
+
+    // score[k]: best total of a selection whose last frame is order[k]
+    // best[k]: best total using only the first k frames by end offset
+    let mut score = vec![0.0f64; order.len()];
+    let mut back: Vec<Option<usize>> = vec![None; order.len()];
+    let mut best: Vec<(f64, Option<usize>)> = Vec::with_capacity(order.len() + 1);
+    best.push((0.0, None));
+
+    for (k, &i) in order.iter().enumerate() {
+        let f = &decoded[i];
+        let p = ends[..k].partition_point(|&e| e <= f.offset);
+        let (mut value, mut from) = best[p];
+
+        if let Some(preds) = by_hash.get(&f.frame.header.prev_hash) {
+            for &q in preds {
+                if q < k && ends[q] <= f.offset {
+                    let v = score[q] + ARBITRATION_BACKLINK_BONUS;
+                    if v > value {
+                        value = v;
+                        from = Some(q);
+                    }
+                }
+            }
+        }
+
+        score[k] = value + f.confidence as f64;
+        back[k] = from;
+        let prev_best = best[k];
+        best.push(if score[k] > prev_best.0 {
+            (score[k], Some(k))
+        } else {
+            prev_best
+        });
+    }
+
+    let mut chosen = vec![false; decoded.len()];
+    let mut cur = best[order.len()].1;
+    while let Some(k) = cur {
+        chosen[order[k]] = true;
+        cur = back[k];
+    }
+
+    let mut accepted = Vec::new();
+    let mut dropped = Vec::new();
+    for (f, keep) in decoded.into_iter().zip(chosen) {
+        if keep {
+            accepted.push(f);
+        } else {
+            dropped.push(f);
+        }
+    }
+
+    let rejected = dropped
+        .into_iter()
+        .map(|f| {
+            let idx = accepted.partition_point(|a| end(a) <= f.offset);
+            let overlapped_by = accepted
+                .get(idx)
+                .filter(|a| a.offset < end(&f))
+                .map(|a| a.offset);
+
+            #[cfg(feature = "logging")]
+            debug!(
+                "Rejected overlapping frame {} at {} (conf {:.2})",
+                f.frame.header.frame_id, f.offset, f.confidence
+            );
+
+            RejectedOverlap {
+                offset: f.offset,
+                size: f.size,
+                frame_id: f.frame.header.frame_id,
+                confidence: f.confidence,
+                overlapped_by,
+            }
+        })
+        .collect();
+
+    (accepted, rejected)
+}
+
 /// Compute a confidence score for a successfully decoded frame
-fn compute_confidence(lf: &LocatedFrame, hit: MarkerHit) -> f32 {
+fn compute_confidence(lf: &LocatedFrame, hit: MarkerCandidate) -> f32 {
     let mut score = 0.5f32; // base for a structurally valid decode
 
     // Marker quality
     if hit.hamming == 0 {
         score += 0.2;
-    } else if hit.hamming <= crate::constants::MAX_MARKER_HAMMING {
+    } else if hit.hamming <= MAX_MARKER_HAMMING {
         score += 0.05;
     }
 
@@ -123,7 +640,6 @@ fn compute_confidence(lf: &LocatedFrame, hit: MarkerHit) -> f32 {
     }
 
     // Trailer presence (checksum/hash validated during decode)
-    use crate::constants::TrailerType;
     match lf.frame.header.flags.trailer_type() {
         TrailerType::Blake3 => score += 0.20,
         TrailerType::Blake3WithEd25519Sig => score += 0.22,
@@ -139,163 +655,152 @@ fn compute_confidence(lf: &LocatedFrame, hit: MarkerHit) -> f32 {
     score.clamp(0.0, 1.0)
 }
 
-/// Find the next occurrence of the frame marker
-fn find_marker(data: &[u8]) -> Option<MarkerHit> {
-    use crate::constants::{MAX_MARKER_HAMMING, PREAMBLE_PATTERN, ROBUST_SYNC_WORD};
-
-    // First try an exact fast search using memmem
-    if data.len() >= FRAME_MARKER.len() {
-        if let Some(pos) = memchr::memmem::find(data, FRAME_MARKER) {
-            return Some(MarkerHit {
-                pos,
-                hamming: 0,
-                had_sync: false,
-                had_preamble: false,
-            });
-        }
-    }
-
-    // Optional: if a robust sync word exists nearby, bias search around it
-    if data.len() >= ROBUST_SYNC_WORD.len() + FRAME_MARKER.len() {
-        if let Some(sync_pos) = memchr::memmem::find(data, ROBUST_SYNC_WORD) {
-            // Try exact marker immediately following the sync
-            let start = sync_pos + ROBUST_SYNC_WORD.len();
-            if start + FRAME_MARKER.len() <= data.len()
-                && &data[start..start + FRAME_MARKER.len()] == FRAME_MARKER
-            {
-                return Some(MarkerHit {
-                    pos: start,
-                    hamming: 0,
-                    had_sync: true,
-                    had_preamble: false,
-                });
-            }
+/// Try to decode a frame at a candidate position
+///
+/// Damaged markers (fuzzy or anchored candidates) are restored before decoding,
+/// but such frames are only accepted when a trailer verifies the result.
+fn try_decode_candidate(data: &[u8], hit: &MarkerCandidate) -> Attempt {
+    let (header, total_size) = match header_at(data, hit.offset) {
+        Ok(h) => h,
+        Err(e) => return Attempt::Rejected(e),
+    };
+    let damaged = |error: FrameError| {
+        // Without a header CRC only a failed trailer says a frame was there
+        let trailer_failed = matches!(
+            error,
+            FrameError::ChecksumMismatch { .. } | FrameError::HashMismatch
+        );
+        if header.has_header_crc() || (hit.hamming == 0 && trailer_failed) {
+            Attempt::Damaged(DamagedFrame {
+                offset: hit.offset,
+                header: header.clone(),
+                size: total_size,
+                error,
+            })
+        } else {
+            Attempt::Rejected(error)
         }
+    };
+
+    // Check if we have enough data
+    if hit.offset + total_size > data.len() {
+        return damaged(FrameError::IncompleteFrame {
+            expected: total_size,
+            actual: data.len() - hit.offset,
+        });
     }
 
-    // Check for preamble runs that may precede a marker
-    if data.len()
-        >= PREAMBLE_PATTERN.len() * crate::constants::MIN_PREAMBLE_LEN + FRAME_MARKER.len()
-    {
-        let mut i = 0usize;
-        while i + PREAMBLE_PATTERN.len() <= data.len() {
-            // Count alternating 0x55,0xAA pattern length
-            let mut run = 0usize;
-            let mut j = i;
-            let mut expect = PREAMBLE_PATTERN[0];
-            while j < data.len() {
-                if data[j] == expect {
-                    run += 1;
-                    expect = if expect == PREAMBLE_PATTERN[0] {
-                        PREAMBLE_PATTERN[1]
-                    } else {
-                        PREAMBLE_PATTERN[0]
-                    };
-                    j += 1;
-                } else {
-                    break;
-                }
-            }
-            if run >= crate::constants::MIN_PREAMBLE_LEN {
-                let start = i + run;
-                if start + FRAME_MARKER.len() <= data.len()
-                    && &data[start..start + FRAME_MARKER.len()] == FRAME_MARKER
-                {
-                    return Some(MarkerHit {
-                        pos: start,
-                        hamming: 0,
-                        had_sync: false,
-                        had_preamble: true,
-                    });
-                }
-                i = start;
-            } else {
-                i += 1;
+    let frame_data = &data[hit.offset..hit.offset + total_size];
+    let decoded = if hit.hamming == 0 {
+        decode_frame_from_bytes(frame_data)
+    } else {
+        let mut repaired = frame_data.to_vec();
+        repaired[..FRAME_MARKER.len()].copy_from_slice(FRAME_MARKER);
+        decode_frame_from_bytes(&repaired)
+    };
+
+    match decoded {
+        Ok(frame) => {
+            if hit.hamming != 0 && frame.header.flags.trailer_type() == TrailerType::None {
+                return Attempt::Rejected(FrameError::BadMarker([
+                    frame_data[0],
+                    frame_data[1],
+                    frame_data[2],
+                    frame_data[3],
+                ]));
             }
+            Attempt::Decoded(LocatedFrame {
+                offset: hit.offset,
+                frame,
+                size: total_size,
+                confidence: 0.0, // filled by caller after scoring
+                bit_shift: 0,
+                inverted: false,
+                repair: None,
+            })
         }
+        Err(e) => damaged(e),
     }
+}
 
-    // Bounded-distance Hamming check for the 4-byte marker to reduce false positives but tolerate bit flips
-    let m = FRAME_MARKER;
-    if MAX_MARKER_HAMMING > 0 && data.len() >= m.len() {
-        for i in 0..=data.len() - m.len() {
-            let mut dist = 0u32;
-            for k in 0..m.len() {
-                dist += (data[i + k] ^ m[k]).count_ones();
-                if dist > MAX_MARKER_HAMMING {
-                    break;
-                }
-            }
-            if dist <= MAX_MARKER_HAMMING {
-                return Some(MarkerHit {
-                    pos: i,
-                    hamming: dist,
-                    had_sync: false,
-                    had_preamble: false,
-                });
-            }
+/// Decode a frame whose marker at `offset` is within `max_hamming` bits of `FRAME_MARKER`
+///
+/// For targeted rescans with looser marker matching than the main scan. As
+/// there, a damaged marker is only accepted when the frame's trailer verifies.
+pub(crate) fn decode_at(data: &[u8], offset: usize, max_hamming: u32) -> Option<LocatedFrame> {
+    let hamming = marker_distance(data.get(offset..offset + FRAME_MARKER.len())?);
+    if hamming > max_hamming {
+        return None;
+    }
+    let hit = MarkerCandidate {
+        offset,
+        hamming,
+        had_sync: false,
+        had_preamble: false,
+        kind: if hamming == 0 {
+            CandidateKind::Exact
+        } else {
+            CandidateKind::Fuzzy
+        },
+    };
+    match try_decode_candidate(data, &hit) {
+        Attempt::Decoded(mut located) => {
+            located.confidence = compute_confidence(&located, hit);
+            Some(located)
         }
+        _ => None,
     }
-
-    None
 }
 
-/// Try to decode a frame at a specific offset
-fn try_decode_at_offset(
-    data: &[u8],
-    offset: usize,
-) -> Result<LocatedFrame, crate::error::FrameError> {
+/// Decode the header at `offset` (restoring a possibly damaged marker) and compute the frame size
+///
+/// For `HEADER_CRC_VERSION` frames the header CRC is verified here, before
+/// `payload_len` and flags are trusted, so a corrupted length is rejected
+/// immediately instead of swallowing the frames that follow.
+fn header_at(data: &[u8], offset: usize) -> Result<(FrameHeader, usize), FrameError> {
     // Need at least minimum header size
     if offset + MIN_HEADER_SIZE > data.len() {
-        return Err(crate::error::FrameError::IncompleteFrame {
+        return Err(FrameError::IncompleteFrame {
             expected: MIN_HEADER_SIZE,
             actual: data.len() - offset,
         });
     }
 
-    // Read payload length from header to determine total frame size
-    let payload_len_offset = offset + 4 + 1 + 8 + 32; // marker + version + frame_id + prev_hash
-    let payload_len = u32::from_be_bytes([
-        data[payload_len_offset],
-        data[payload_len_offset + 1],
-        data[payload_len_offset + 2],
-        data[payload_len_offset + 3],
-    ]);
-
-    // Read flags to determine trailer size
-    let flags_offset = payload_len_offset + 4;
-    let flags = crate::constants::FrameFlags::new(data[flags_offset]);
-    let trailer_size = flags.trailer_type().size();
+    let head_len = header_size(data[offset + FRAME_MARKER.len()]).min(data.len() - offset);
+    let mut head = [0u8; MAX_HEADER_SIZE];
+    head[..head_len].copy_from_slice(&data[offset..offset + head_len]);
+    head[..FRAME_MARKER.len()].copy_from_slice(FRAME_MARKER);
+    let header = decode_header(&head[..head_len])?;
 
     // Calculate total frame size
-    let total_size = MIN_HEADER_SIZE + payload_len as usize + trailer_size;
+    let trailer_size = header.flags.trailer_type().size();
+    let total_size = header.encoded_len() + header.payload_len as usize + trailer_size;
 
     // Sanity check: frame size must be reasonable
     if total_size > MAX_FRAME_SIZE as usize {
-        return Err(crate::error::FrameError::FrameTooLarge(
-            total_size as u32,
-            MAX_FRAME_SIZE,
-        ));
+        return Err(FrameError::FrameTooLarge(total_size as u32, MAX_FRAME_SIZE));
     }
 
-    // Check if we have enough data
-    if offset + total_size > data.len() {
-        return Err(crate::error::FrameError::IncompleteFrame {
-            expected: total_size,
-            actual: data.len() - offset,
-        });
-    }
+    Ok((header, total_size))
+}
 
-    // Try to decode the frame
-    let frame_data = &data[offset..offset + total_size];
-    let frame = decode_frame_from_bytes(frame_data)?;
+/// A decoded candidate that lost overlap arbitration
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RejectedOverlap {
+    /// Byte offset of the rejected frame
+    pub offset: usize,
 
-    Ok(LocatedFrame {
-        offset,
-        frame,
-        size: total_size,
-        confidence: 0.0, // filled by caller after scoring
-    })
+    /// Size the rejected frame would have occupied
+    pub size: usize,
+
+    /// Frame ID from the rejected header
+    pub frame_id: u64,
+
+    /// Confidence the rejected frame scored
+    pub confidence: f32,
+
+    /// Offset of the first accepted frame it overlapped, if any
+    pub overlapped_by: Option<usize>,
 }
 
 /// Scan statistics
@@ -315,6 +820,18 @@ pub struct ScanStats {
 
     /// Total bytes recovered (sum of all valid frame sizes)
     pub bytes_recovered: usize,
+
+    /// Number of candidates rejected because their header CRC did not verify
+    pub header_checksum_failures: usize,
+
+    /// Number of frames whose header parsed but whose body did not decode ([`DamagedFrame`])
+    pub damaged_frames: usize,
+
+    /// Decoded candidates dropped because they overlapped a better-scoring frame
+    pub rejected_overlaps: Vec<RejectedOverlap>,
+
+    /// Number of frames recovered by undoing a byte insertion or deletion
+    pub indel_repairs: usize,
 }
 
 impl ScanStats {
@@ -330,87 +847,414 @@ impl ScanStats {
 
 /// Scan stream with statistics
 pub fn scan_stream_with_stats(data: &[u8]) -> (Vec<LocatedFrame>, ScanStats) {
+    let (results, _, stats) = scan_stream_with_damaged(data);
+    (results, stats)
+}
+
+/// Scan stream with statistics, also reporting frames whose header parsed but body did not
+///
+/// A damaged frame's ID and prev_hash can be used to place the loss in the
+/// timeline; only `HEADER_CRC_VERSION` headers are verified (see [`DamagedFrame`]).
+pub fn scan_stream_with_damaged(data: &[u8]) -> (Vec<LocatedFrame>, Vec<DamagedFrame>, ScanStats) {
     let mut stats = ScanStats {
         bytes_scanned: data.len(),
         ..Default::default()
     };
 
-    let mut results = Vec::new();
-    let mut pos = 0;
+    let mut damaged = Vec::new();
+    let mut results = scan_candidates(data, &mut stats, &mut damaged);
 
-    while pos < data.len() {
-        if let Some(hit) = find_marker(&data[pos..]) {
-            let absolute_pos = pos + hit.pos;
-            stats.markers_found += 1;
+    apply_neighbor_bonuses(&mut results);
 
-            match try_decode_at_offset(data, absolute_pos) {
-                Ok(mut located_frame) => {
-                    located_frame.confidence = compute_confidence(&located_frame, hit);
-                    stats.bytes_recovered += located_frame.size;
-                    pos = absolute_pos + located_frame.size;
-                    results.push(located_frame);
-                }
-                Err(_) => {
-                    stats.decode_failures += 1;
-                    pos = absolute_pos + FRAME_MARKER.len();
-                }
-            }
-        } else {
-            break;
-        }
-    }
+    stats.frames_found = results.len();
+
+    (results, damaged, stats)
+}
 
-    // Neighbor-based bonuses: backlink consistency and spacing plausibility
+/// Neighbor-based bonuses: backlink consistency and spacing plausibility
+fn apply_neighbor_bonuses(results: &mut [LocatedFrame]) {
     for i in 1..results.len() {
-        let (prev, curr) = {
-            // split borrow to appease the borrow checker
-            let (head, tail) = results.split_at_mut(i);
-            (&head[i - 1] as *const LocatedFrame, &mut tail[0])
-        };
-        // SAFETY: prev is from an earlier immutable slice; not aliased with curr
-        let prev = unsafe { &*prev };
+        let prev_hash = results[i - 1].frame.compute_hash();
+        let prev_end = results[i - 1].bit_position() + results[i - 1].size * 8;
+        let curr = &mut results[i];
         // Backlink consistency
-        if curr.frame.header.prev_hash == prev.frame.compute_hash() {
+        if curr.frame.header.prev_hash == prev_hash {
             curr.confidence = (curr.confidence + 0.05).clamp(0.0, 1.0);
         }
         // Spacing plausibility (next offset immediately after previous frame)
-        if curr.offset == prev.offset + prev.size {
+        if curr.bit_position() == prev_end {
             curr.confidence = (curr.confidence + 0.05).clamp(0.0, 1.0);
         }
     }
+}
+
+/// Kind of stream edit undone by indel recovery
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndelKind {
+    /// Extra bytes were inserted into the frame; they were removed
+    Insertion,
+    /// Bytes were deleted from the frame; they were restored
+    Deletion,
+}
+
+/// The edit [`scan_stream_with_indels`] applied to recover a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndelRepair {
+    /// Whether bytes were removed or restored
+    pub kind: IndelKind,
+
+    /// Stream offset of the edit: the first inserted byte, or the byte that
+    /// followed the deleted run
+    pub position: usize,
+
+    /// Number of bytes inserted or deleted
+    pub len: usize,
+}
+
+/// Options for [`scan_stream_with_indels`]
+#[derive(Debug, Clone, Copy)]
+pub struct IndelOptions {
+    /// Largest insertion or deletion (in bytes) to search for
+    ///
+    /// The default of 2 is as far as a CRC32C trailer can vouch for guessed
+    /// bytes: each restored byte uses 8 of its 32 bits, and with 4 bytes
+    /// every position would produce a frame that "verifies".
+    pub max_bytes: usize,
+
+    /// Upper bound on decode attempts per damaged frame
+    ///
+    /// Insertions, and deletions of up to 2 bytes in CRC32C frames (whose
+    /// missing bytes are solved from the checksum), cost one attempt per
+    /// position. Other deletions cost one per position and fill value
+    /// (256^len), so with the default budget BLAKE3 frames are only repaired
+    /// for single-byte deletions. Solved CRC32C fills are further limited to
+    /// 2^(24 - 8·len) positions (256 for a 2-byte deletion), whatever this
+    /// budget allows.
+    pub max_hypotheses: usize,
+}
+
+impl Default for IndelOptions {
+    fn default() -> Self {
+        Self {
+            max_bytes: 2,
+            max_hypotheses: 1 << 16,
+        }
+    }
+}
+
+/// Scan with recovery of frames damaged by inserted or deleted bytes
+///
+/// After a regular scan, every candidate whose header parses but whose frame
+/// failed to decode is measured against the next accepted frame (or the end
+/// of input). If that boundary is up to `max_bytes` later or earlier than
+/// `payload_len` predicts, single contiguous insertions (or deletions) are
+/// tried at each position after the header until the trailer verifies.
+/// For CRC32C frames the bytes lost to a deletion of up to 2 bytes are
+/// solved from the checksum, in frames short enough for the checksum to
+/// rule out wrong positions; otherwise they are enumerated within
+/// `max_hypotheses`.
+/// Trailerless frames cannot be checked and are never repaired. Repaired
+/// frames carry the applied edit in [`LocatedFrame::repair`].
+pub fn scan_stream_with_indels(
+    data: &[u8],
+    options: IndelOptions,
+) -> (Vec<LocatedFrame>, ScanStats) {
+    let (mut results, mut stats) = scan_stream_with_stats(data);
+
+    let mut repaired = Vec::new();
+    for hit in MarkerCandidates::new(data) {
+        let idx = results.partition_point(|f| f.offset <= hit.offset);
+        if idx > 0 && hit.offset < results[idx - 1].offset + results[idx - 1].size {
+            continue;
+        }
+        let next = results.get(idx).map_or(data.len(), |f| f.offset);
+        if let Some(mut lf) = repair_indel(data, &hit, next, options) {
+            lf.confidence = (compute_confidence(&lf, hit) - 0.1).clamp(0.0, 1.0);
+
+            #[cfg(feature = "logging")]
+            debug!(
+                "Repaired frame {} at {} with {:?}",
+                lf.frame.header.frame_id, lf.offset, lf.repair
+            );
+
+            repaired.push(lf);
+        }
+    }
+
+    if !repaired.is_empty() {
+        stats.indel_repairs = repaired.len();
+        results.extend(repaired);
+        results.sort_by_key(|f| f.offset);
+        let (mut merged, rejected) = arbitrate(results);
+        apply_neighbor_bonuses(&mut merged);
+        stats.rejected_overlaps.extend(rejected);
+        stats.frames_found = merged.len();
+        stats.bytes_recovered = merged.iter().map(|f| f.size).sum();
+        results = merged;
+    }
+
+    (results, stats)
+}
+
+/// Try single insertion/deletion hypotheses for the frame at `hit`, which should end at `next`
+fn repair_indel(
+    data: &[u8],
+    hit: &MarkerCandidate,
+    next: usize,
+    options: IndelOptions,
+) -> Option<LocatedFrame> {
+    let (header, total_size) = header_at(data, hit.offset).ok()?;
+    if header.flags.trailer_type() == TrailerType::None || next <= hit.offset {
+        return None;
+    }
+
+    let mut span = data[hit.offset..next].to_vec();
+    span[..FRAME_MARKER.len()].copy_from_slice(FRAME_MARKER);
+    let head = header.encoded_len();
+    let observed = span.len();
+
+    let (kind, len) = if observed > total_size {
+        (IndelKind::Insertion, observed - total_size)
+    } else if observed < total_size {
+        (IndelKind::Deletion, total_size - observed)
+    } else {
+        return None;
+    };
+    if len > options.max_bytes || observed < head {
+        return None;
+    }
+
+    let found = match kind {
+        IndelKind::Insertion => {
+            let positions = total_size - head + 1;
+            if positions > options.max_hypotheses {
+                return None;
+            }
+            (head..=total_size).find_map(|pos| {
+                let mut candidate = Vec::with_capacity(total_size);
+                candidate.extend_from_slice(&span[..pos]);
+                candidate.extend_from_slice(&span[pos + len..]);
+                decode_frame_from_bytes(&candidate).ok().map(|f| (pos, f))
+            })
+        }
+        IndelKind::Deletion
+            if header.flags.trailer_type() == TrailerType::Crc32c && len <= MAX_CRC_FILL =>
+        {
+            if observed - head + 1 > options.max_hypotheses.min(crc_fill_positions(len)) {
+                return None;
+            }
+            let mut candidate = vec![0u8; total_size];
+            (head..=observed).find_map(|pos| {
+                candidate[..pos].copy_from_slice(&span[..pos]);
+                candidate[pos + len..].copy_from_slice(&span[pos..]);
+                solve_crc_fill(&mut candidate, pos, len).map(|f| (pos, f))
+            })
+        }
+        IndelKind::Deletion => {
+            let positions = observed - head + 1;
+            let fills = 1usize.checked_shl(8 * len as u32)?;
+            if positions.saturating_mul(fills) > options.max_hypotheses {
+                return None;
+            }
+            let mut candidate = vec![0u8; total_size];
+            (head..=observed).find_map(|pos| {
+                candidate[..pos].copy_from_slice(&span[..pos]);
+                candidate[pos + len..].copy_from_slice(&span[pos..]);
+                (0..fills).find_map(|fill| {
+                    for (i, b) in candidate[pos..pos + len].iter_mut().enumerate() {
+                        *b = (fill >> (8 * i)) as u8;
+                    }
+                    decode_frame_from_bytes(&candidate).ok().map(|f| (pos, f))
+                })
+            })
+        }
+    };
+
+    let (pos, frame) = found?;
+    Some(LocatedFrame {
+        offset: hit.offset,
+        frame,
+        size: observed,
+        confidence: 0.0,
+        bit_shift: 0,
+        inverted: false,
+        repair: Some(IndelRepair {
+            kind,
+            position: hit.offset + pos,
+            len,
+        }),
+    })
+}
+
+/// Longest deletion whose bytes are solved from a CRC32C trailer
+///
+/// Solving `len` bytes leaves 32 - 8·len checksum bits to reject a wrong
+/// position; longer fills leave too few to tell a repair from a forgery.
+const MAX_CRC_FILL: usize = 2;
+
+/// Most positions a `len`-byte CRC32C fill may be solved at
+///
+/// Each wrong position solves with probability 2^-(32 - 8·len), and the
+/// first position that solves is taken. Staying under 2^(24 - 8·len)
+/// positions keeps the chance of a wrong, CRC-valid repair below 1/256 per
+/// damaged frame.
+fn crc_fill_positions(len: usize) -> usize {
+    1 << (24 - 8 * len)
+}
+
+/// Fill the `len` deleted bytes at `pos` of a CRC32C frame so its trailer verifies
+///
+/// Over inputs of equal length, the computed CRC XOR the stored trailer is
+/// affine in the missing bits, so the fill is the solution of a linear system
+/// over GF(2) with one equation per checksum bit. CRC32C detects every burst
+/// of up to 32 bits, which makes the solution unique when one exists.
+fn solve_crc_fill(candidate: &mut [u8], pos: usize, len: usize) -> Option<Frame> {
+    let body = candidate.len() - 4;
+    let residual = |c: &[u8]| {
+        crc32c::crc32c(&c[..body])
+            ^ u32::from_be_bytes([c[body], c[body + 1], c[body + 2], c[body + 3]])
+    };
+    candidate[pos..pos + len].fill(0);
+    let base = residual(candidate);
+
+    // Residual change of each fill bit, reduced to a basis keyed by leading bit;
+    // the mask records which fill bits make up a basis vector
+    let mut basis: [Option<(u32, u32)>; 32] = [None; 32];
+    for bit in 0..8 * len {
+        candidate[pos + bit / 8] = 1 << (bit % 8);
+        let mut v = residual(candidate) ^ base;
+        candidate[pos + bit / 8] = 0;
+        let mut mask = 1u32 << bit;
+        while v != 0 {
+            let lead = 31 - v.leading_zeros() as usize;
+            match basis[lead] {
+                Some((bv, bm)) => {
+                    v ^= bv;
+                    mask ^= bm;
+                }
+                None => {
+                    basis[lead] = Some((v, mask));
+                    break;
+                }
+            }
+        }
+        if v == 0 {
+            return None;
+        }
+    }
+
+    let (mut v, mut fill) = (base, 0u32);
+    while v != 0 {
+        let (bv, bm) = basis[31 - v.leading_zeros() as usize]?;
+        v ^= bv;
+        fill ^= bm;
+    }
+    candidate[pos..pos + len].copy_from_slice(&fill.to_le_bytes()[..len]);
+    decode_frame_from_bytes(candidate).ok()
+}
+
+/// Options for [`scan_stream_bit_aligned`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BitScanOptions {
+    /// Also search the bit-inverted stream (e.g. a demodulator with swapped polarity)
+    pub allow_inverted: bool,
+}
+
+/// Scan a raw bit capture for frames starting at any bit offset
+///
+/// Serial and SDR captures often slip by a few bits, which leaves every
+/// following marker misaligned for [`scan_stream`]. This runs the regular
+/// scanner over each of the eight bit alignments (and their inversions when
+/// `allow_inverted` is set), then arbitrates overlapping results across
+/// alignments, so a stream that slips part-way through is recovered on both
+/// sides of the slip. Bits are taken MSB first; each frame reports its
+/// `bit_shift` and polarity.
+pub fn scan_stream_bit_aligned(
+    data: &[u8],
+    options: BitScanOptions,
+) -> (Vec<LocatedFrame>, ScanStats) {
+    let mut stats = ScanStats {
+        bytes_scanned: data.len(),
+        ..Default::default()
+    };
+
+    let polarities: &[bool] = if options.allow_inverted {
+        &[false, true]
+    } else {
+        &[false]
+    };
+
+    // Positions and sizes are expressed in bits while alignments are merged
+    let mut merged = Vec::new();
+    for &inverted in polarities {
+        for shift in 0..8u8 {
+            let aligned = realign_bits(data, shift, inverted);
+            let mut pass = ScanStats::default();
+            let found = scan_candidates(&aligned, &mut pass, &mut Vec::new());
+
+            stats.markers_found += pass.markers_found;
+            stats.decode_failures += pass.decode_failures;
+            stats.header_checksum_failures += pass.header_checksum_failures;
+            stats.damaged_frames += pass.damaged_frames;
+            stats.rejected_overlaps.extend(pass.rejected_overlaps);
+
+            merged.extend(found.into_iter().map(|mut lf| {
+                lf.offset = lf.offset * 8 + shift as usize;
+                lf.size *= 8;
+                lf.bit_shift = shift;
+                lf.inverted = inverted;
+                lf
+            }));
+        }
+    }
+    merged.sort_by_key(|lf| lf.offset);
 
+    let (mut results, rejected) = arbitrate(merged);
+    for lf in &mut results {
+        lf.offset /= 8;
+        lf.size /= 8;
+    }
+    stats
+        .rejected_overlaps
+        .extend(rejected.into_iter().map(|r| RejectedOverlap {
+            offset: r.offset / 8,
+            size: r.size / 8,
+            overlapped_by: r.overlapped_by.map(|o| o / 8),
+            ..r
+        }));
+
+    apply_neighbor_bonuses(&mut results);
     stats.frames_found = results.len();
+    stats.bytes_recovered = results.iter().map(|lf| lf.size).sum();
 
     (results, stats)
 }
 
+/// Re-pack `data` so that bit `shift` of the first byte becomes bit 7 of the first output byte
+fn realign_bits(data: &[u8], shift: u8, inverted: bool) -> Vec<u8> {
+    let mask = if inverted { 0xFF } else { 0x00 };
+    if shift == 0 {
+        return data.iter().map(|b| b ^ mask).collect();
+    }
+    data.windows(2)
+        .map(|w| ((w[0] << shift) | (w[1] >> (8 - shift))) ^ mask)
+        .collect()
+}
+
 /// Scan a byte buffer (Bytes) and return zero-copy frames by slicing
+///
+/// Frames whose marker had to be repaired cannot borrow from `buf` and are
+/// returned with owned payloads instead.
 pub fn scan_stream_zero_copy(buf: Bytes) -> Vec<LocatedFrame> {
-    let mut results = Vec::new();
-    let mut pos = 0;
-    while pos < buf.len() {
-        if let Some(hit) = find_marker(&buf[pos..]) {
-            let at = pos + hit.pos;
-            // Fast path: compute total size to slice just once
-            match try_decode_at_offset(&buf, at) {
-                Ok(mut loc) => {
-                    let start = at;
-                    let end = at + loc.size;
-                    let slice = buf.slice(start..end);
-                    if let Ok(frame) = crate::decoder::decode_frame_from_bytes_zero_copy(slice) {
-                        loc.frame = frame;
-                        loc.confidence = compute_confidence(&loc, hit);
-                        results.push(loc);
-                        pos = end;
-                        continue;
-                    }
-                    // Fallback to advancing by marker if zero-copy decode failed unexpectedly
-                    pos = at + FRAME_MARKER.len();
-                }
-                Err(_) => pos = at + FRAME_MARKER.len(),
+    let mut stats = ScanStats::default();
+    let mut results = scan_candidates(&buf, &mut stats, &mut Vec::new());
+    for loc in &mut results {
+        let end = loc.offset + loc.size;
+        if buf[loc.offset..].starts_with(FRAME_MARKER) {
+            let slice = buf.slice(loc.offset..end);
+            if let Ok(frame) = crate::decoder::decode_frame_from_bytes_zero_copy(slice) {
+                loc.frame = frame;
             }
-        } else {
-            break;
         }
     }
     results
@@ -420,6 +1264,7 @@ pub fn scan_stream_zero_copy(buf: Bytes) -> Vec<LocatedFrame> {
 mod tests {
     use super::*;
     use crate::encoder::FrameBuilder;
+    use alloc::format;
     use bytes::Bytes;
 
     #[test]
@@ -523,4 +1368,451 @@ mod tests {
         assert_eq!(stats.bytes_scanned, frame1.len());
         assert!(stats.recovery_rate() > 99.0); // Should be close to 100%
     }
+
+    #[test]
+    fn test_candidates_in_offset_order() {
+        let frame = FrameBuilder::new(1)
+            .payload(Bytes::from("payload"))
+            .with_crc32c()
+            .build()
+            .unwrap();
+
+        let mut stream = Vec::new();
+        stream.extend_from_slice(b"DURQ noise ");
+        stream.extend_from_slice(ROBUST_SYNC_WORD);
+        stream.extend_from_slice(b"XXXX");
+        stream.extend_from_slice(&frame);
+
+        let hits: Vec<_> = MarkerCandidates::new(&stream).collect();
+        let kinds: Vec<_> = hits.iter().map(|h| h.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                CandidateKind::Fuzzy,
+                CandidateKind::SyncAnchored,
+                CandidateKind::Exact
+            ]
+        );
+        assert!(hits.windows(2).all(|w| w[0].offset < w[1].offset));
+        assert_eq!(hits[1].offset, 11 + ROBUST_SYNC_WORD.len());
+    }
+
+    #[test]
+    fn test_scan_recovers_damaged_marker_with_trailer() {
+        let frame = FrameBuilder::new(7)
+            .payload(Bytes::from("bit flip in marker"))
+            .with_crc32c()
+            .build()
+            .unwrap();
+
+        let mut damaged = frame.to_vec();
+        damaged[1] ^= 0x01;
+        let results = scan_stream(&damaged);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].frame.header.frame_id, 7);
+
+        // Without a trailer a repaired marker cannot be trusted
+        let bare = FrameBuilder::new(8)
+            .payload(Bytes::from("no trailer"))
+            .build()
+            .unwrap();
+        let mut damaged = bare.to_vec();
+        damaged[1] ^= 0x01;
+        assert!(scan_stream(&damaged).is_empty());
+    }
+
+    #[test]
+    fn test_scan_sync_anchored_marker() {
+        let header = crate::types::FrameHeader::with_flags(
+            3,
+            [0u8; 32],
+            5,
+            crate::constants::FrameFlags::new(
+                crate::constants::FrameFlags::HAS_SYNC_PREFIX
+                    | crate::constants::FrameFlags::HAS_PREAMBLE
+                    | crate::constants::FrameFlags::HAS_BLAKE3,
+            ),
+        );
+        let encoded = crate::encoder::encode_frame(&header, b"hello").unwrap();
+        let marker_at = MIN_PREAMBLE_LEN + ROBUST_SYNC_WORD.len();
+
+        let mut damaged = encoded.to_vec();
+        damaged[marker_at..marker_at + 4].copy_from_slice(b"\x00\x00\x00\x00");
+        let (results, _) = scan_stream_with_stats(&damaged);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].offset, marker_at);
+        assert_eq!(results[0].frame.header.frame_id, 3);
+    }
+
+    #[test]
+    fn test_fuzzy_search_matches_scalar() {
+        use rand::{Rng, SeedableRng};
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let mut data = vec![0u8; 4096];
+        rng.fill(&mut data[..]);
+        // Plant a few near-miss markers
+        for &at in &[5usize, 1000, 2047, 4090] {
+            data[at..at + 4].copy_from_slice(FRAME_MARKER);
+            data[at + 2] ^= 0x10;
+        }
+        for max in 0..=3 {
+            let mut from = 0;
+            while let Some(p) = find_marker_within(&data, from, max) {
+                let expected = (from..=data.len() - 4)
+                    .find(|&i| marker_distance(&data[i..i + 4]) <= max)
+                    .unwrap();
+                assert_eq!(p, expected);
+                from = p + 1;
+            }
+            assert!((from..=data.len() - 4).all(|i| marker_distance(&data[i..i + 4]) > max));
+        }
+    }
+
+    #[test]
+    fn test_header_crc_prevents_length_hijack() {
+        let frames: Vec<_> = (1..=3)
+            .map(|id| {
+                FrameBuilder::new(id)
+                    .payload(Bytes::from("trailerless"))
+                    .with_header_crc()
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+        let mut stream: Vec<u8> = frames.iter().flat_map(|f| f.to_vec()).collect();
+
+        // Inflate frame 1's payload_len so that it would swallow frame 2
+        let swallow = frames[0][48] as usize + frames[1].len();
+        stream[48] = swallow as u8;
+        let (results, stats) = scan_stream_with_stats(&stream);
+        let ids: Vec<u64> = results.iter().map(|lf| lf.frame.header.frame_id).collect();
+        assert_eq!(ids, vec![2, 3]);
+        assert_eq!(stats.header_checksum_failures, 1);
+
+        // Without the header CRC the same flip hides frame 2 inside frame 1
+        let mut plain: Vec<u8> = (1..=3)
+            .flat_map(|id| {
+                FrameBuilder::new(id)
+                    .payload(Bytes::from("trailerless"))
+                    .build()
+                    .unwrap()
+                    .to_vec()
+            })
+            .collect();
+        let frame_len = plain.len() / 3;
+        plain[48] = (plain[48] as usize + frame_len) as u8;
+        let ids: Vec<u64> = scan_stream(&plain)
+            .iter()
+            .map(|lf| lf.frame.header.frame_id)
+            .collect();
+        assert_eq!(ids, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_damaged_payload_keeps_trusted_header() {
+        let frame = FrameBuilder::new(42)
+            .prev_hash([9u8; 32])
+            .payload(Bytes::from("payload that will be damaged"))
+            .with_crc32c()
+            .with_header_crc()
+            .build()
+            .unwrap();
+        let mut stream = frame.to_vec();
+        let last = stream.len() - 10;
+        stream[last] ^= 0xFF;
+
+        let (results, damaged, stats) = scan_stream_with_damaged(&stream);
+        assert!(results.is_empty());
+        assert_eq!(damaged.len(), 1);
+        assert_eq!(stats.damaged_frames, 1);
+        assert_eq!(damaged[0].offset, 0);
+        assert_eq!(damaged[0].size, frame.len());
+        assert_eq!(damaged[0].header.frame_id, 42);
+        assert_eq!(damaged[0].header.prev_hash, [9u8; 32]);
+        assert!(damaged[0].header_verified());
+    }
+
+    #[test]
+    fn test_version_1_trailer_failure_is_reported_unverified() {
+        let frame = FrameBuilder::new(7)
+            .payload(Bytes::from("payload that will be damaged"))
+            .with_crc32c()
+            .build()
+            .unwrap();
+        let mut stream = frame.to_vec();
+        stream[MIN_HEADER_SIZE + 3] ^= 0x01;
+
+        let (results, damaged, stats) = scan_stream_with_damaged(&stream);
+        assert!(results.is_empty());
+        assert_eq!(stats.damaged_frames, 1);
+        assert_eq!(damaged[0].header.frame_id, 7);
+        assert_eq!(damaged[0].size, frame.len());
+        assert!(!damaged[0].header_verified());
+        assert!(matches!(
+            damaged[0].error,
+            FrameError::ChecksumMismatch { .. }
+        ));
+
+        // Cut short, the length cannot be checked: not reported
+        let (_, damaged, _) = scan_stream_with_damaged(&frame[..frame.len() - 2]);
+        assert!(damaged.is_empty());
+    }
+
+    #[test]
+    fn test_overlapping_false_positive_does_not_hide_frames() {
+        let first = FrameBuilder::new(1)
+            .payload(Bytes::from("first"))
+            .with_blake3()
+            .build_struct()
+            .unwrap();
+        let first_bytes = crate::encoder::encode_frame_struct(&first).unwrap();
+        let second = FrameBuilder::new(2)
+            .prev_hash(first.compute_hash())
+            .payload(Bytes::from("second"))
+            .with_blake3()
+            .build()
+            .unwrap();
+
+        // A trailerless frame whose length swallows both real frames
+        let mut inner = first_bytes.to_vec();
+        inner.extend_from_slice(&second);
+        let bogus = FrameBuilder::new(99)
+            .payload(Bytes::from(inner))
+            .build()
+            .unwrap();
+
+        let (results, stats) = scan_stream_with_stats(&bogus);
+        let ids: Vec<u64> = results.iter().map(|f| f.frame.header.frame_id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(stats.rejected_overlaps.len(), 1);
+        let rejected = stats.rejected_overlaps[0];
+        assert_eq!(rejected.offset, 0);
+        assert_eq!(rejected.frame_id, 99);
+        assert_eq!(rejected.overlapped_by, Some(results[0].offset));
+    }
+
+    #[test]
+    fn test_verified_frame_keeps_nested_frame_as_payload() {
+        let nested = FrameBuilder::new(7)
+            .payload(Bytes::from("nested"))
+            .with_blake3()
+            .build()
+            .unwrap();
+        let outer = FrameBuilder::new(1)
+            .payload(nested)
+            .with_crc32c()
+            .build()
+            .unwrap();
+
+        let (results, stats) = scan_stream_with_stats(&outer);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].frame.header.frame_id, 1);
+        assert_eq!(stats.markers_found, 1);
+        assert_eq!(stats.decode_failures, 0);
+    }
+
+    /// Prepend `bits` one-bits to a byte stream, MSB first, padding the tail with zeros
+    fn prepend_bits(data: &[u8], bits: u8) -> Vec<u8> {
+        let mut out = Vec::with_capacity(data.len() + 1);
+        let mut carry = 0xFFu8 << (8 - bits);
+        for &b in data {
+            out.push(carry | (b >> bits));
+            carry = b << (8 - bits);
+        }
+        out.push(carry);
+        out
+    }
+
+    #[test]
+    fn test_bit_aligned_scan_recovers_shifted_stream() {
+        let mut stream = Vec::new();
+        for i in 1..=3u64 {
+            let frame = FrameBuilder::new(i)
+                .payload(Bytes::from(format!("radio {}", i)))
+                .with_crc32c()
+                .build()
+                .unwrap();
+            stream.extend_from_slice(&frame);
+        }
+        let shifted = prepend_bits(&stream, 3);
+        assert!(scan_stream(&shifted).is_empty());
+
+        let (results, stats) = scan_stream_bit_aligned(&shifted, BitScanOptions::default());
+        assert_eq!(stats.frames_found, 3);
+        let first_size = results[0].size;
+        assert_eq!(results[0].offset, 0);
+        assert_eq!(results[0].bit_shift, 3);
+        assert_eq!(results[1].bit_position(), first_size * 8 + 3);
+        assert!(results.iter().all(|lf| !lf.inverted));
+        assert_eq!(&results[2].frame.payload[..], b"radio 3");
+    }
+
+    #[test]
+    fn test_bit_aligned_scan_follows_slip() {
+        let a = FrameBuilder::new(1)
+            .payload(Bytes::from("before slip"))
+            .with_blake3()
+            .build()
+            .unwrap();
+        let b = FrameBuilder::new(2)
+            .payload(Bytes::from("after slip"))
+            .with_blake3()
+            .build()
+            .unwrap();
+        let mut stream = a.to_vec();
+        stream.extend_from_slice(&prepend_bits(&b, 5));
+
+        let (results, _) = scan_stream_bit_aligned(&stream, BitScanOptions::default());
+        assert_eq!(results.len(), 2);
+        assert_eq!((results[0].offset, results[0].bit_shift), (0, 0));
+        assert_eq!((results[1].offset, results[1].bit_shift), (a.len(), 5));
+    }
+
+    #[test]
+    fn test_bit_aligned_scan_inverted_polarity() {
+        let frame = FrameBuilder::new(9)
+            .payload(Bytes::from("inverted"))
+            .with_crc32c()
+            .build()
+            .unwrap();
+        let inverted: Vec<u8> = prepend_bits(&frame, 6).iter().map(|b| !b).collect();
+
+        let (plain, _) = scan_stream_bit_aligned(&inverted, BitScanOptions::default());
+        assert!(plain.is_empty());
+
+        let options = BitScanOptions {
+            allow_inverted: true,
+        };
+        let (results, _) = scan_stream_bit_aligned(&inverted, options);
+        assert_eq!(results.len(), 1);
+        assert!(results[0].inverted);
+        assert_eq!(results[0].bit_shift, 6);
+        assert_eq!(results[0].frame.header.frame_id, 9);
+    }
+
+    fn indel_stream() -> (Vec<u8>, Bytes, usize) {
+        let mut stream = Vec::new();
+        let mut middle = (Bytes::new(), 0);
+        for i in 1..=3u64 {
+            let frame = FrameBuilder::new(i)
+                .payload(Bytes::from(format!("payload number {} abcdefgh", i)))
+                .with_crc32c()
+                .build()
+                .unwrap();
+            if i == 2 {
+                middle = (frame.clone(), stream.len());
+            }
+            stream.extend_from_slice(&frame);
+        }
+        (stream, middle.0, middle.1)
+    }
+
+    #[test]
+    fn test_indel_recovery_removes_insertion() {
+        let (mut stream, middle, at) = indel_stream();
+        let edit = at + middle.len() - 12;
+        stream.splice(edit..edit, [0xAAu8, 0x55]);
+        assert_eq!(scan_stream(&stream).len(), 2);
+
+        let (results, stats) = scan_stream_with_indels(&stream, IndelOptions::default());
+        assert_eq!(results.len(), 3);
+        assert_eq!(stats.indel_repairs, 1);
+        let fixed = &results[1];
+        assert_eq!(fixed.offset, at);
+        assert_eq!(fixed.size, middle.len() + 2);
+        assert_eq!(&fixed.frame.payload[..], b"payload number 2 abcdefgh");
+        assert_eq!(
+            fixed.repair,
+            Some(IndelRepair {
+                kind: IndelKind::Insertion,
+                position: edit,
+                len: 2,
+            })
+        );
+        assert!(results[0].repair.is_none() && results[2].repair.is_none());
+    }
+
+    #[test]
+    fn test_indel_recovery_restores_deletion() {
+        let (mut stream, middle, at) = indel_stream();
+        let edit = at + middle.len() - 10;
+        stream.remove(edit);
+        assert_eq!(scan_stream(&stream).len(), 2);
+
+        let (results, stats) = scan_stream_with_indels(&stream, IndelOptions::default());
+        assert_eq!(results.len(), 3);
+        assert_eq!(stats.indel_repairs, 1);
+        let fixed = &results[1];
+        assert_eq!(fixed.size, middle.len() - 1);
+        assert_eq!(&fixed.frame.payload[..], b"payload number 2 abcdefgh");
+        let repair = fixed.repair.unwrap();
+        assert_eq!(
+            (repair.kind, repair.position, repair.len),
+            (IndelKind::Deletion, edit, 1)
+        );
+    }
+
+    #[test]
+    fn test_indel_recovery_restores_two_byte_deletion_by_default() {
+        for len in [1, 2] {
+            let (mut stream, middle, at) = indel_stream();
+            let edit = at + middle.len() - 14;
+            stream.drain(edit..edit + len);
+            assert_eq!(scan_stream(&stream).len(), 2);
+
+            let (results, stats) = scan_stream_with_indels(&stream, IndelOptions::default());
+            assert_eq!(results.len(), 3, "deletion of {} bytes", len);
+            assert_eq!(stats.indel_repairs, 1);
+            assert_eq!(&results[1].frame.payload[..], b"payload number 2 abcdefgh");
+            // A deleted run matching the bytes before it can be placed earlier
+            let repair = results[1].repair.unwrap();
+            assert_eq!((repair.kind, repair.len), (IndelKind::Deletion, len));
+            assert!(repair.position <= edit && repair.position + len >= edit);
+        }
+    }
+
+    #[test]
+    fn test_indel_recovery_skips_crc_fills_in_long_frames() {
+        // A 2-byte deletion in a 600-byte payload has more positions than
+        // the checksum can rule out, so it is left alone
+        let frame = |id: u64, payload: Vec<u8>| {
+            FrameBuilder::new(id)
+                .payload(Bytes::from(payload))
+                .with_crc32c()
+                .build()
+                .unwrap()
+        };
+        let mut stream = frame(1, b"first".to_vec()).to_vec();
+        let at = stream.len();
+        stream.extend_from_slice(&frame(2, (0..600).map(|i| i as u8).collect()));
+        stream.extend_from_slice(&frame(3, b"third".to_vec()));
+        stream.drain(at + 300..at + 302);
+
+        let (results, stats) = scan_stream_with_indels(&stream, IndelOptions::default());
+        assert_eq!(results.len(), 2);
+        assert_eq!(stats.indel_repairs, 0);
+
+        // A single byte still fits
+        let mut stream = frame(1, b"first".to_vec()).to_vec();
+        stream.extend_from_slice(&frame(2, (0..600).map(|i| i as u8).collect()));
+        stream.extend_from_slice(&frame(3, b"third".to_vec()));
+        stream.remove(at + 300);
+        let (results, stats) = scan_stream_with_indels(&stream, IndelOptions::default());
+        assert_eq!((results.len(), stats.indel_repairs), (3, 1));
+    }
+
+    #[test]
+    fn test_indel_recovery_respects_max_bytes() {
+        let (mut stream, middle, at) = indel_stream();
+        let edit = at + middle.len() - 12;
+        stream.splice(edit..edit, [1u8, 2, 3]);
+
+        let options = IndelOptions {
+            max_bytes: 2,
+            ..Default::default()
+        };
+        let (results, stats) = scan_stream_with_indels(&stream, options);
+        assert_eq!(results.len(), 2);
+        assert_eq!(stats.indel_repairs, 0);
+    }
 }
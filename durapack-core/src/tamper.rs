@@ -0,0 +1,553 @@
+//! Tamper classification: telling deliberate modification from media damage
+//!
+//! Damage and tampering leave different evidence. Bit rot, dropouts and cut
+//! captures break trailers, lose frames and end streams early. Editing a
+//! stream without breaking any trailer means recomputing it, which leaves
+//! intact frames that no longer fit the hash chain: back-links that point
+//! elsewhere, IDs that contradict the links, two versions of one frame, or
+//! signatures that were removed or replaced. [`classify`] runs the scanner
+//! and linker analyses and sorts every finding by its likely [`Cause`] and a
+//! [`Severity`] suitable for review.
+
+use crate::constants::TrailerType;
+use crate::linker::{analyze_indexed, FlagInconsistency, GapReason, SequenceEnd};
+use crate::parity::parity_descriptor;
+use crate::scanner::{scan_stream_with_damaged, DamagedFrame, LocatedFrame};
+use crate::types::Frame;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// How serious a finding is for an integrity review
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Worth noting; does not affect the recovered content
+    Info,
+    /// Content is missing or uncertain, most likely by accident
+    Low,
+    /// Content is missing and the cause cannot be settled from the stream alone
+    Medium,
+    /// Intact frames contradict the chain: modification is likely
+    High,
+    /// Authenticity evidence was removed or replaced
+    Critical,
+}
+
+/// Most likely origin of a finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cause {
+    /// Consistent with media damage, loss or truncation
+    Accidental,
+    /// Requires valid trailers to have been recomputed or signatures changed
+    Deliberate,
+    /// Could be either
+    Ambiguous,
+}
+
+/// What kind of evidence a finding is based on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindingKind {
+    /// A frame's header verified but its payload or trailer did not
+    TrailerFailure,
+    /// The data ends inside a frame, or the chain ends without IS_LAST
+    Truncation,
+    /// Frame IDs are skipped between two linked frames
+    MissingFrames,
+    /// A frame does not link to its ID predecessor; deliberate when its trailer verified
+    BrokenChain,
+    /// A signed frame was changed and signed again, or signed by another key
+    ReSigned,
+    /// A frame's ID contradicts its hash link
+    Renumbered,
+    /// Two frames carry the same ID with different content
+    ConflictingDuplicate,
+    /// Several frames link back to the same predecessor
+    Fork,
+    /// A frame lacks the signature the rest of the stream carries
+    StrippedSignature,
+    /// IS_FIRST/IS_LAST contradict the frame's links
+    FlagInconsistency,
+}
+
+/// One classified observation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// Evidence type
+    pub kind: FindingKind,
+    /// Likely origin
+    pub cause: Cause,
+    /// Review severity
+    pub severity: Severity,
+    /// Frames involved
+    pub frame_ids: Vec<u64>,
+    /// Stream offsets of the frames involved, where known
+    pub offsets: Vec<usize>,
+    /// Human-readable explanation
+    pub detail: String,
+}
+
+/// All findings for one stream, most severe first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TamperReport {
+    /// Classified findings
+    pub findings: Vec<Finding>,
+}
+
+impl TamperReport {
+    /// Highest severity among the findings
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.findings.iter().map(|f| f.severity).max()
+    }
+
+    /// Findings that point at deliberate modification
+    pub fn deliberate(&self) -> impl Iterator<Item = &Finding> {
+        self.findings
+            .iter()
+            .filter(|f| f.cause == Cause::Deliberate)
+    }
+
+    /// True when nothing was found
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
+
+/// Scan `data` and classify everything that looks wrong
+///
+/// `signature_check`, when given, verifies a signed frame's signature against
+/// the expected key; signed frames that fail it are reported as re-signed.
+pub fn classify(data: &[u8], signature_check: Option<&dyn Fn(&Frame) -> bool>) -> TamperReport {
+    let (located, damaged, _) = scan_stream_with_damaged(data);
+    classify_scanned(&located, &damaged, data.len(), signature_check)
+}
+
+/// Classify findings for frames already scanned from a stream of `data_len` bytes
+pub fn classify_scanned(
+    located: &[LocatedFrame],
+    damaged: &[DamagedFrame],
+    data_len: usize,
+    signature_check: Option<&dyn Fn(&Frame) -> bool>,
+) -> TamperReport {
+    let report = analyze_indexed(located);
+    let index = &report.index;
+    let mut findings = Vec::new();
+
+    let linked: BTreeMap<u64, usize> = index
+        .frames
+        .iter()
+        .chain(&index.orphans)
+        .map(|&i| (located[i].frame.header.frame_id, i))
+        .collect();
+    let offset_of = |id: u64| linked.get(&id).map(|&i| located[i].offset);
+    let offsets_of = |ids: &[u64]| ids.iter().filter_map(|&id| offset_of(id)).collect();
+    let is_signed = |f: &Frame| f.header.flags.trailer_type() == TrailerType::Blake3WithEd25519Sig;
+
+    // Media damage: bodies that fail their trailer, frames cut off by the end of data
+    for d in damaged {
+        let cut = d.offset.saturating_add(d.size) > data_len;
+        findings.push(Finding {
+            kind: if cut {
+                FindingKind::Truncation
+            } else {
+                FindingKind::TrailerFailure
+            },
+            cause: Cause::Accidental,
+            severity: Severity::Low,
+            frame_ids: vec![d.header.frame_id],
+            offsets: vec![d.offset],
+            detail: if cut {
+                format!(
+                    "frame {} is cut off by the end of data ({} of {} bytes)",
+                    d.header.frame_id,
+                    data_len - d.offset,
+                    d.size
+                )
+            } else {
+                format!(
+                    "frame {} failed verification: {:?}",
+                    d.header.frame_id, d.error
+                )
+            },
+        });
+    }
+    if let SequenceEnd::Truncated { last_seen } = report.completeness.end {
+        if report.completeness.starts_at_first {
+            findings.push(Finding {
+                kind: FindingKind::Truncation,
+                cause: Cause::Accidental,
+                severity: Severity::Low,
+                frame_ids: vec![last_seen],
+                offsets: offsets_of(&[last_seen]),
+                detail: format!("no IS_LAST frame after frame {}", last_seen),
+            });
+        }
+    }
+
+    // Gaps: lost frames versus intact frames that no longer fit the chain
+    for g in &report.gap_details {
+        let ids = [g.gap.before, g.gap.after];
+        match g.reason {
+            GapReason::MissingById => {
+                // Damaged frames inside the gap explain the loss
+                let explained = damaged
+                    .iter()
+                    .any(|d| d.header.frame_id > g.gap.before && d.header.frame_id < g.gap.after);
+                findings.push(Finding {
+                    kind: FindingKind::MissingFrames,
+                    cause: if explained {
+                        Cause::Accidental
+                    } else {
+                        Cause::Ambiguous
+                    },
+                    severity: if explained {
+                        Severity::Low
+                    } else {
+                        Severity::Medium
+                    },
+                    frame_ids: ids.to_vec(),
+                    offsets: offsets_of(&ids),
+                    detail: format!(
+                        "frames {}..{} are missing",
+                        g.gap.before.saturating_add(1),
+                        g.gap.after.saturating_sub(1)
+                    ),
+                });
+            }
+            GapReason::MissingByHash => {
+                let after = linked.get(&g.gap.after).map(|&i| &located[i].frame);
+                let signed = after.is_some_and(is_signed);
+                // Located frames passed their trailer check; without a trailer
+                // nothing says the frame was rewritten rather than hit
+                let verified =
+                    after.is_some_and(|f| f.header.flags.trailer_type() != TrailerType::None);
+                findings.push(Finding {
+                    kind: if signed {
+                        FindingKind::ReSigned
+                    } else {
+                        FindingKind::BrokenChain
+                    },
+                    cause: if verified {
+                        Cause::Deliberate
+                    } else {
+                        Cause::Ambiguous
+                    },
+                    severity: if signed {
+                        Severity::Critical
+                    } else if verified {
+                        Severity::High
+                    } else {
+                        Severity::Medium
+                    },
+                    frame_ids: ids.to_vec(),
+                    offsets: offsets_of(&ids),
+                    detail: if verified {
+                        format!(
+                            "frame {} has a valid {}trailer but does not link to frame {}",
+                            g.gap.after,
+                            if signed { "signed " } else { "" },
+                            g.gap.before
+                        )
+                    } else {
+                        format!(
+                            "frame {} has no trailer and does not link to frame {}",
+                            g.gap.after, g.gap.before
+                        )
+                    },
+                });
+            }
+        }
+    }
+
+    for &i in &index.renumbered {
+        let id = located[i].frame.header.frame_id;
+        findings.push(Finding {
+            kind: FindingKind::Renumbered,
+            cause: Cause::Deliberate,
+            severity: Severity::High,
+            frame_ids: vec![id],
+            offsets: vec![located[i].offset],
+            detail: format!("frame {} is hash-linked where its ID does not fit", id),
+        });
+    }
+
+    for eq in &report.equivocations {
+        findings.push(Finding {
+            kind: FindingKind::ConflictingDuplicate,
+            cause: Cause::Deliberate,
+            severity: Severity::High,
+            frame_ids: vec![eq.frame_id],
+            offsets: eq.offsets.clone(),
+            detail: format!(
+                "frame {} exists in {} different versions",
+                eq.frame_id,
+                eq.hashes.len()
+            ),
+        });
+    }
+
+    for c in &report.conflicts {
+        findings.push(Finding {
+            kind: FindingKind::Fork,
+            cause: Cause::Ambiguous,
+            severity: Severity::Medium,
+            frame_ids: c.contenders.clone(),
+            offsets: offsets_of(&c.contenders),
+            detail: format!("frames {:?} all link back to frame {}", c.contenders, c.at),
+        });
+    }
+
+    for issue in &report.completeness.inconsistencies {
+        let (id, detail) = match *issue {
+            FlagInconsistency::FirstWithPrevHash { frame_id } => (
+                frame_id,
+                format!(
+                    "frame {} is flagged IS_FIRST but has a predecessor",
+                    frame_id
+                ),
+            ),
+            FlagInconsistency::LastWithSuccessor {
+                frame_id,
+                successor,
+            } => (
+                frame_id,
+                format!(
+                    "frame {} is flagged IS_LAST but frame {} follows it",
+                    frame_id, successor
+                ),
+            ),
+        };
+        findings.push(Finding {
+            kind: FindingKind::FlagInconsistency,
+            cause: Cause::Ambiguous,
+            severity: Severity::Medium,
+            frame_ids: vec![id],
+            offsets: offsets_of(&[id]),
+            detail,
+        });
+    }
+
+    // Signatures: removed from some frames, or not made with the expected key.
+    // Parity appended afterwards by `durapack fec` is unsigned; `pack` signs its own.
+    let frames = index
+        .frames
+        .iter()
+        .chain(&index.orphans)
+        .map(|&i| &located[i]);
+    let any_signed = frames.clone().any(|lf| is_signed(&lf.frame));
+    for lf in frames {
+        let id = lf.frame.header.frame_id;
+        if !is_signed(&lf.frame) {
+            if any_signed && parity_descriptor(&lf.frame).is_none() {
+                findings.push(Finding {
+                    kind: FindingKind::StrippedSignature,
+                    cause: Cause::Deliberate,
+                    severity: Severity::Critical,
+                    frame_ids: vec![id],
+                    offsets: vec![lf.offset],
+                    detail: format!("frame {} is unsigned in a signed stream", id),
+                });
+            }
+        } else if signature_check.is_some_and(|check| !check(&lf.frame)) {
+            findings.push(Finding {
+                kind: FindingKind::ReSigned,
+                cause: Cause::Deliberate,
+                severity: Severity::Critical,
+                frame_ids: vec![id],
+                offsets: vec![lf.offset],
+                detail: format!("frame {} is not signed by the expected key", id),
+            });
+        }
+    }
+
+    // Most severe first, then in stream order
+    findings.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then(a.offsets.first().cmp(&b.offsets.first()))
+    });
+    TamperReport { findings }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{MAX_HEADER_SIZE, MIN_HEADER_SIZE};
+    use crate::encoder::{encode_frame_struct, FrameBuilder};
+    use crate::test_util::{chain, chain_from, encode_all, plain};
+    use bytes::Bytes;
+
+    /// Chained frames 1..=n with header CRCs, so damaged bodies stay attributable
+    fn stream(n: u64, signed: bool) -> (Vec<u8>, Vec<usize>) {
+        encode_all(&chain(1..=n, |id, b| {
+            let mut b = b.with_header_crc();
+            if id == n {
+                b = b.mark_last();
+            }
+            if signed {
+                b.with_blake3_signature()
+            } else {
+                b.with_blake3()
+            }
+        }))
+    }
+
+    fn kinds(report: &TamperReport) -> Vec<(FindingKind, Cause)> {
+        report.findings.iter().map(|f| (f.kind, f.cause)).collect()
+    }
+
+    #[test]
+    fn test_clean_stream_has_no_findings() {
+        let (data, _) = stream(4, false);
+        let report = classify(&data, None);
+        assert!(report.is_clean(), "{:?}", report.findings);
+        assert_eq!(report.max_severity(), None);
+    }
+
+    #[test]
+    fn test_media_damage_is_accidental() {
+        let (mut data, offsets) = stream(5, false);
+        // Flip a payload byte in frame 3 and cut frame 5 short
+        data[offsets[2] + MAX_HEADER_SIZE + 2] ^= 0x10;
+        data.truncate(offsets[4] + MAX_HEADER_SIZE + 3);
+
+        let report = classify(&data, None);
+        assert_eq!(report.deliberate().count(), 0, "{:?}", report.findings);
+        let found = kinds(&report);
+        assert!(found.contains(&(FindingKind::TrailerFailure, Cause::Accidental)));
+        assert!(found.contains(&(FindingKind::Truncation, Cause::Accidental)));
+        assert!(found.contains(&(FindingKind::MissingFrames, Cause::Accidental)));
+        assert!(report.max_severity() <= Some(Severity::Low));
+    }
+
+    #[test]
+    fn test_version_1_trailer_failure_is_accidental() {
+        // No header CRC: the failed CRC32C trailer alone identifies frame 2
+        let (mut data, offsets) = encode_all(&chain(1..=3, |id, b| {
+            let b = b.with_crc32c();
+            if id == 3 {
+                b.mark_last()
+            } else {
+                b
+            }
+        }));
+        data[offsets[1] + MIN_HEADER_SIZE + 1] ^= 0x01;
+
+        let report = classify(&data, None);
+        assert_eq!(
+            kinds(&report),
+            vec![
+                (FindingKind::MissingFrames, Cause::Accidental),
+                (FindingKind::TrailerFailure, Cause::Accidental),
+            ]
+        );
+        assert_eq!(report.findings[1].offsets, vec![offsets[1]]);
+        assert_eq!(report.max_severity(), Some(Severity::Low));
+    }
+
+    #[test]
+    fn test_unprotected_chain_break_is_ambiguous() {
+        // Frame 2 carries no trailer, so its wrong back-link proves nothing
+        let frames = chain_from([1], &mut [0; 32], plain);
+        let mut data = encode_frame_struct(&frames[0]).unwrap().to_vec();
+        data.extend_from_slice(
+            &FrameBuilder::new(2)
+                .payload(Bytes::from("frame 2"))
+                .prev_hash([1u8; 32])
+                .build()
+                .unwrap(),
+        );
+
+        let report = classify(&data, None);
+        assert_eq!(
+            kinds(&report),
+            vec![(FindingKind::BrokenChain, Cause::Ambiguous)]
+        );
+        assert_eq!(report.max_severity(), Some(Severity::Medium));
+        assert!(report.findings[0].detail.contains("no trailer"));
+    }
+
+    #[test]
+    fn test_rewritten_frame_is_deliberate() {
+        let (data, offsets) = stream(4, false);
+        // Replace frame 3 with a frame that verifies on its own but breaks the chain
+        let forged = FrameBuilder::new(3)
+            .payload(Bytes::from("reading 3 (edited)"))
+            .prev_hash([1u8; 32])
+            .with_header_crc()
+            .with_blake3()
+            .build()
+            .unwrap();
+        let mut tampered = data[..offsets[2]].to_vec();
+        tampered.extend_from_slice(&forged);
+        tampered.extend_from_slice(&data[offsets[3]..]);
+
+        let report = classify(&tampered, None);
+        let found = kinds(&report);
+        assert!(found.contains(&(FindingKind::BrokenChain, Cause::Deliberate)));
+        assert_eq!(report.max_severity(), Some(Severity::High));
+        assert_eq!(report.findings[0].severity, Severity::High);
+    }
+
+    #[test]
+    fn test_signature_findings() {
+        let (signed, offsets) = stream(3, true);
+        let (plain, plain_offsets) = stream(3, false);
+        // Frame 2 swapped for its unsigned twin: same content, signature stripped
+        let mut mixed = signed[..offsets[1]].to_vec();
+        mixed.extend_from_slice(&plain[plain_offsets[1]..plain_offsets[2]]);
+
+        let report = classify(&mixed, None);
+        let stripped: Vec<&Finding> = report
+            .findings
+            .iter()
+            .filter(|f| f.kind == FindingKind::StrippedSignature)
+            .collect();
+        assert_eq!(stripped.len(), 1);
+        assert_eq!(stripped[0].frame_ids, vec![2]);
+        assert_eq!(report.max_severity(), Some(Severity::Critical));
+
+        // A key check that rejects frame 3 flags it as re-signed
+        let reject_three = |f: &Frame| f.header.frame_id != 3;
+        let report = classify(&signed, Some(&reject_three));
+        assert_eq!(
+            kinds(&report),
+            vec![(FindingKind::ReSigned, Cause::Deliberate)]
+        );
+        assert_eq!(report.findings[0].frame_ids, vec![3]);
+    }
+
+    #[test]
+    fn test_unsigned_parity_in_signed_stream_is_not_stripped() {
+        use crate::parity::{FecCode, ParityDescriptor};
+
+        let (mut data, offsets) = stream(3, true);
+        let last = crate::decoder::decode_frame_from_bytes(&data[offsets[2]..]).unwrap();
+        let d = ParityDescriptor {
+            code: FecCode::ReedSolomon,
+            param: 0,
+            block_start_id: 1,
+            data: 3,
+            parity: 1,
+            index: 0,
+        };
+        let parity = FrameBuilder::new(4)
+            .as_parity(&d, b"shard bytes")
+            .prev_hash(last.compute_hash())
+            .with_crc32c()
+            .build()
+            .unwrap();
+        data.extend_from_slice(&parity);
+
+        let report = classify(&data, None);
+        assert!(
+            report
+                .findings
+                .iter()
+                .all(|f| f.kind != FindingKind::StrippedSignature),
+            "{:?}",
+            report.findings
+        );
+    }
+}
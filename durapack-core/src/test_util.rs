@@ -0,0 +1,64 @@
+//! Fixtures shared by the unit tests
+
+use crate::constants::BLAKE3_HASH_SIZE;
+use crate::encoder::{encode_frame_struct, FrameBuilder};
+use crate::types::Frame;
+use alloc::format;
+use alloc::vec::Vec;
+use bytes::Bytes;
+
+/// Frames with the given IDs, each back-linked to the one before
+///
+/// Every frame starts as `FrameBuilder::new(id)` with the payload
+/// `"frame {id}"`; `configure` adds trailers, flags or another payload. The
+/// back-link is filled in last, starting from `prev`, which is left holding
+/// the hash of the last frame so a later call can continue the chain.
+pub(crate) fn chain_from(
+    ids: impl IntoIterator<Item = u64>,
+    prev: &mut [u8; BLAKE3_HASH_SIZE],
+    mut configure: impl FnMut(u64, FrameBuilder) -> FrameBuilder,
+) -> Vec<Frame> {
+    ids.into_iter()
+        .map(|id| {
+            let builder = FrameBuilder::new(id).payload(Bytes::from(format!("frame {}", id)));
+            let frame = configure(id, builder)
+                .prev_hash(*prev)
+                .build_struct()
+                .unwrap();
+            *prev = frame.compute_hash();
+            frame
+        })
+        .collect()
+}
+
+/// [`chain_from`] for a stream with nothing before it: the first frame is IS_FIRST
+pub(crate) fn chain(
+    ids: impl IntoIterator<Item = u64>,
+    mut configure: impl FnMut(u64, FrameBuilder) -> FrameBuilder,
+) -> Vec<Frame> {
+    let mut first = true;
+    chain_from(ids, &mut [0u8; BLAKE3_HASH_SIZE], |id, builder| {
+        let builder = configure(id, builder);
+        if core::mem::take(&mut first) {
+            builder.mark_first()
+        } else {
+            builder
+        }
+    })
+}
+
+/// `configure` for frames that need nothing beyond the defaults
+pub(crate) fn plain(_id: u64, builder: FrameBuilder) -> FrameBuilder {
+    builder
+}
+
+/// `frames` encoded back to back, with the offset of each
+pub(crate) fn encode_all(frames: &[Frame]) -> (Vec<u8>, Vec<usize>) {
+    let mut data = Vec::new();
+    let mut offsets = Vec::with_capacity(frames.len());
+    for frame in frames {
+        offsets.push(data.len());
+        data.extend_from_slice(&encode_frame_struct(frame).unwrap());
+    }
+    (data, offsets)
+}
@@ -1,6 +1,9 @@
 //! Core types for Durapack frames
 
-use crate::constants::{FrameFlags, BLAKE3_HASH_SIZE, MAX_PAYLOAD_SIZE, PROTOCOL_VERSION};
+use crate::constants::{
+    header_size, is_supported_version, FrameFlags, BLAKE3_HASH_SIZE, FRAME_MARKER,
+    HEADER_CRC_VERSION, MAX_PAYLOAD_SIZE, PROTOCOL_VERSION,
+};
 use crate::error::FrameError;
 use alloc::format;
 use alloc::string::String;
@@ -59,7 +62,7 @@ impl FrameHeader {
 
     /// Validate the header
     pub fn validate(&self) -> Result<(), FrameError> {
-        if self.version != PROTOCOL_VERSION {
+        if !is_supported_version(self.version) {
             return Err(FrameError::UnsupportedVersion(self.version));
         }
 
@@ -73,9 +76,37 @@ impl FrameHeader {
         Ok(())
     }
 
-    /// Check if this is the first frame in a sequence
+    /// Check if this frame starts a sequence: IS_FIRST or an all-zero prev_hash (spec §6.3)
+    ///
+    /// A frame flagged IS_FIRST should also have an all-zero prev_hash; see
+    /// [`crate::linker::Completeness`] for how disagreements are reported.
     pub fn is_first(&self) -> bool {
-        self.prev_hash == [0u8; BLAKE3_HASH_SIZE]
+        self.flags.is_first() || self.prev_hash == [0u8; BLAKE3_HASH_SIZE]
+    }
+
+    /// Check if this frame is flagged as the last in a sequence (IS_LAST)
+    pub fn is_last(&self) -> bool {
+        self.flags.is_last()
+    }
+
+    /// Check if this header is protected by a header CRC (`HEADER_CRC_VERSION`)
+    pub fn has_header_crc(&self) -> bool {
+        self.version == HEADER_CRC_VERSION
+    }
+
+    /// Size of marker + header on the wire, including the header CRC if present
+    pub fn encoded_len(&self) -> usize {
+        header_size(self.version)
+    }
+
+    /// Compute the CRC32C over marker + header fields (as stored in v2 headers)
+    pub fn compute_header_crc(&self) -> u32 {
+        let mut crc = crc32c::crc32c(FRAME_MARKER);
+        crc = crc32c::crc32c_append(crc, &[self.version]);
+        crc = crc32c::crc32c_append(crc, &self.frame_id.to_be_bytes());
+        crc = crc32c::crc32c_append(crc, &self.prev_hash);
+        crc = crc32c::crc32c_append(crc, &self.payload_len.to_be_bytes());
+        crc32c::crc32c_append(crc, &[self.flags.as_u8()])
     }
 }
 
@@ -124,6 +155,12 @@ pub struct SkipLink {
     pub hint: Option<u32>,
 }
 
+impl AsRef<Frame> for Frame {
+    fn as_ref(&self) -> &Frame {
+        self
+    }
+}
+
 impl Frame {
     /// Create a new frame
     pub fn new(header: FrameHeader, payload: Bytes) -> Self {
@@ -164,11 +201,19 @@ impl Frame {
 
     /// Calculate the total frame size in bytes
     pub fn total_size(&self) -> usize {
-        crate::constants::MIN_HEADER_SIZE
+        self.header.encoded_len()
             + self.payload.len()
             + self.trailer.as_ref().map_or(0, |t| t.len())
     }
 
+    /// Whether this frame is an index superframe
+    ///
+    /// `IS_SUPERFRAME` is shared with in-band parity frames, which are told
+    /// apart by the descriptor at the start of their payload.
+    pub fn is_index_superframe(&self) -> bool {
+        self.header.flags.is_superframe() && crate::parity::parity_descriptor(self).is_none()
+    }
+
     /// Get frame ID
     pub fn frame_id(&self) -> u64 {
         self.header.frame_id